@@ -0,0 +1,129 @@
+// backtest/src/config.rs
+use capital_reallocator::constants::REBALANCE_THRESHOLD_BPS;
+use capital_reallocator::strategy::RebalanceParams;
+use crate::prices::to_fixed;
+
+/// LP range: fixed bounds, or a width around the first replayed price
+#[derive(Clone, Copy, Debug)]
+pub enum RangeSpec {
+    Fixed { min: u64, max: u64 },
+    WidthBps(u16),
+}
+
+impl RangeSpec {
+    pub fn resolve(&self, first_price: u64) -> (u64, u64) {
+        match *self {
+            RangeSpec::Fixed { min, max } => (min, max),
+            RangeSpec::WidthBps(width_bps) => {
+                let half = first_price as u128 * width_bps as u128 / 20_000;
+                (first_price.saturating_sub(half as u64), first_price.saturating_add(half as u64))
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SimConfig {
+    pub range: RangeSpec,
+    pub params: RebalanceParams,
+    pub lp_fee_apr_bps: u32,
+    pub lending_apy_bps: u32,
+    pub slippage_bps: u32,
+    pub protocol_fee_bps: u16,
+    pub deposit_a: f64,
+    pub deposit_b: f64,
+    pub slot_ms: u64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        Self {
+            range: RangeSpec::WidthBps(2_000),
+            params: RebalanceParams::default(),
+            lp_fee_apr_bps: 2_500,
+            lending_apy_bps: 600,
+            slippage_bps: 30,
+            protocol_fee_bps: 50,
+            deposit_a: 1_000.0,
+            deposit_b: 0.0,
+            slot_ms: 400,
+        }
+    }
+}
+
+pub const USAGE: &str = "\
+Usage: backtest <prices.csv> [options]
+
+Prices are `timestamp,price[,confidence]` rows (unix seconds, token B in token A).
+Options taking a list (comma separated) run every combination as a sweep.
+
+  --range-min <price>       Fixed LP range lower bound
+  --range-max <price>       Fixed LP range upper bound
+  --width-bps <list>        LP range width around the first price (default 2000)
+  --threshold-bps <list>    Price move needed between rebalances (default 100)
+  --min-slots <n>           Slots between rebalances (default 25)
+  --lp-apr-bps <n>          LP fee APR while in range (default 2500)
+  --lending-apy-bps <n>     Lending APY (default 600)
+  --slippage-bps <n>        Swap slippage when balancing for LP (default 30)
+  --fee-bps <n>             Protocol deposit/withdraw fee (default 50)
+  --deposit-a <amount>      Initial token A (default 1000)
+  --deposit-b <amount>      Initial token B (default 0)
+";
+
+/// Parse CLI options into one config per sweep combination
+pub fn parse_args(args: &[String]) -> Result<(String, Vec<SimConfig>), String> {
+    let mut args = args.iter();
+    let path = args.next().ok_or(USAGE)?.clone();
+
+    let mut base = SimConfig::default();
+    let mut range_min = None;
+    let mut range_max = None;
+    let mut widths = vec![];
+    let mut thresholds = vec![REBALANCE_THRESHOLD_BPS];
+
+    while let Some(flag) = args.next() {
+        let value = args.next().ok_or(format!("{} needs a value", flag))?;
+        match flag.as_str() {
+            "--range-min" => range_min = Some(to_fixed(value).ok_or("bad --range-min")?),
+            "--range-max" => range_max = Some(to_fixed(value).ok_or("bad --range-max")?),
+            "--width-bps" => widths = parse_list(value)?,
+            "--threshold-bps" => thresholds = parse_list(value)?,
+            "--min-slots" => base.params.min_slots_between = parse(value)?,
+            "--lp-apr-bps" => base.lp_fee_apr_bps = parse(value)?,
+            "--lending-apy-bps" => base.lending_apy_bps = parse(value)?,
+            "--slippage-bps" => base.slippage_bps = parse(value)?,
+            "--fee-bps" => base.protocol_fee_bps = parse(value)?,
+            "--deposit-a" => base.deposit_a = parse(value)?,
+            "--deposit-b" => base.deposit_b = parse(value)?,
+            _ => return Err(format!("unknown option {}\n\n{}", flag, USAGE)),
+        }
+    }
+
+    let ranges = match (range_min, range_max) {
+        (Some(min), Some(max)) if min < max => vec![RangeSpec::Fixed { min, max }],
+        (Some(_), Some(_)) => return Err("--range-min must be below --range-max".into()),
+        (None, None) if widths.is_empty() => vec![base.range],
+        (None, None) => widths.into_iter().map(RangeSpec::WidthBps).collect(),
+        _ => return Err("--range-min and --range-max go together".into()),
+    };
+
+    let mut configs = vec![];
+    for range in &ranges {
+        for threshold_bps in &thresholds {
+            let mut config = base.clone();
+            config.range = *range;
+            config.params.threshold_bps = *threshold_bps;
+            configs.push(config);
+        }
+    }
+
+    Ok((path, configs))
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("bad value '{}'", value))
+}
+
+fn parse_list<T: std::str::FromStr>(value: &str) -> Result<Vec<T>, String> {
+    value.split(',').map(|v| parse(v.trim())).collect()
+}
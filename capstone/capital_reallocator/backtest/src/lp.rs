@@ -0,0 +1,51 @@
+// backtest/src/lp.rs
+// Concentrated liquidity over [range_min, range_max], standing in for the
+// DLMM position. Token B is the base (x) and token A the quote (y), so
+// prices are A per B just like the program's.
+
+#[derive(Clone, Copy, Debug)]
+pub struct LpPosition {
+    pub liquidity: f64,
+    pub range_min: f64,
+    pub range_max: f64,
+}
+
+impl LpPosition {
+    /// Open with `value_a` (in token A terms) at `price`. Returns the position
+    /// and the token B amount it needs, so the caller can price the swap.
+    pub fn open(value_a: f64, price: f64, range_min: f64, range_max: f64) -> (Self, f64) {
+        let unit = LpPosition { liquidity: 1.0, range_min, range_max };
+        let (unit_a, unit_b) = unit.amounts(price);
+        let unit_value = unit_a + unit_b * price;
+
+        let liquidity = if unit_value > 0.0 { value_a / unit_value } else { 0.0 };
+        let position = LpPosition { liquidity, ..unit };
+
+        (position, unit_b * liquidity)
+    }
+
+    /// `(token_a, token_b)` held at `price`
+    pub fn amounts(&self, price: f64) -> (f64, f64) {
+        let sqrt_p = price.sqrt();
+        let sqrt_min = self.range_min.sqrt();
+        let sqrt_max = self.range_max.sqrt();
+        let l = self.liquidity;
+
+        if price <= self.range_min {
+            (0.0, l * (1.0 / sqrt_min - 1.0 / sqrt_max))
+        } else if price >= self.range_max {
+            (l * (sqrt_max - sqrt_min), 0.0)
+        } else {
+            (l * (sqrt_p - sqrt_min), l * (1.0 / sqrt_p - 1.0 / sqrt_max))
+        }
+    }
+
+    pub fn value(&self, price: f64) -> f64 {
+        let (a, b) = self.amounts(price);
+        a + b * price
+    }
+
+    pub fn in_range(&self, price: f64) -> bool {
+        price > self.range_min && price < self.range_max
+    }
+}
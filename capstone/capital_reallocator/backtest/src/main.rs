@@ -0,0 +1,46 @@
+// backtest/src/main.rs
+// Off-chain backtester: replays a historical price CSV through the same
+// rebalance decisions the program makes and reports how the strategy fared.
+mod config;
+mod lp;
+mod prices;
+mod report;
+mod simulator;
+
+use std::process::ExitCode;
+use report::Report;
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() || args[0] == "--help" || args[0] == "-h" {
+        println!("{}", config::USAGE);
+        return ExitCode::SUCCESS;
+    }
+
+    match run(&args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(args: &[String]) -> Result<(), String> {
+    let (path, configs) = config::parse_args(args)?;
+    let prices = prices::load_csv(&path)?;
+
+    println!("Replaying {} prices from {}\n", prices.len(), path);
+
+    if let [config] = configs.as_slice() {
+        println!("{}", simulator::run(config, &prices)?);
+        return Ok(());
+    }
+
+    println!("{}", Report::SUMMARY_HEADER);
+    for config in &configs {
+        println!("{}", simulator::run(config, &prices)?.summary());
+    }
+
+    Ok(())
+}
@@ -0,0 +1,73 @@
+// backtest/src/prices.rs
+use std::fs;
+use capital_reallocator::constants::PRICE_DECIMALS;
+
+/// One oracle observation, priced like the program sees it
+#[derive(Clone, Copy, Debug)]
+pub struct PricePoint {
+    pub timestamp: i64,
+    pub price: u64,
+    pub confidence: u64,
+}
+
+/// Load `timestamp,price[,confidence]` rows. Timestamps are unix seconds,
+/// prices are token B quoted in token A (e.g. `1717171717,165.42`).
+/// A header row, `#` comments and blank lines are skipped.
+pub fn load_csv(path: &str) -> Result<Vec<PricePoint>, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("failed to read {}: {}", path, e))?;
+
+    let mut points = Vec::new();
+    let mut header_allowed = true;
+    for (line_no, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let timestamp = match fields[0].parse::<i64>() {
+            Ok(ts) => ts,
+            Err(_) if header_allowed => {
+                header_allowed = false;
+                continue;
+            }
+            Err(_) => return Err(format!("line {}: bad timestamp '{}'", line_no + 1, fields[0])),
+        };
+
+        let price = fields.get(1)
+            .ok_or(format!("line {}: missing price", line_no + 1))
+            .and_then(|p| to_fixed(p).ok_or(format!("line {}: bad price '{}'", line_no + 1, p)))?;
+        let confidence = match fields.get(2) {
+            Some(c) => to_fixed(c).ok_or(format!("line {}: bad confidence '{}'", line_no + 1, c))?,
+            None => 0,
+        };
+
+        header_allowed = false;
+        points.push(PricePoint { timestamp, price, confidence });
+    }
+
+    if points.len() < 2 {
+        return Err(format!("{} needs at least two price rows", path));
+    }
+    if points.windows(2).any(|w| w[1].timestamp < w[0].timestamp) {
+        return Err(format!("{} is not sorted by timestamp", path));
+    }
+
+    Ok(points)
+}
+
+/// Decimal string -> PRICE_DECIMALS fixed point
+pub fn to_fixed(value: &str) -> Option<u64> {
+    let value: f64 = value.parse().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+
+    Some((value * 10f64.powi(PRICE_DECIMALS as i32)).round() as u64)
+}
+
+/// PRICE_DECIMALS fixed point -> float for value accounting
+pub fn to_float(price: u64) -> f64 {
+    price as f64 / 10f64.powi(PRICE_DECIMALS as i32)
+}
@@ -0,0 +1,98 @@
+// backtest/src/report.rs
+use std::fmt;
+
+/// Outcome of one replay. Values are in token A.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    pub range_min: f64,
+    pub range_max: f64,
+    pub threshold_bps: u16,
+    pub duration_seconds: f64,
+    pub seconds_in_range: f64,
+    pub rebalances: u64,
+    pub moves_to_lp: u64,
+    pub moves_to_lending: u64,
+    pub skipped_uncertain: u64,
+    pub swaps: u64,
+    pub lp_fees_earned: f64,
+    pub lending_interest: f64,
+    pub protocol_fees: f64,
+    pub slippage_paid: f64,
+    pub initial_value: f64,
+    pub final_value: f64,
+    pub hodl_value: f64,
+}
+
+impl Report {
+    pub fn time_in_range_pct(&self) -> f64 {
+        if self.duration_seconds > 0.0 {
+            self.seconds_in_range / self.duration_seconds * 100.0
+        } else {
+            0.0
+        }
+    }
+
+    pub fn fees_paid(&self) -> f64 {
+        self.protocol_fees + self.slippage_paid
+    }
+
+    pub fn net_return_pct(&self) -> f64 {
+        pct_change(self.final_value, self.initial_value)
+    }
+
+    pub fn vs_hodl_pct(&self) -> f64 {
+        pct_change(self.final_value, self.hodl_value)
+    }
+
+    pub const SUMMARY_HEADER: &'static str =
+        "range                    threshold  rebalances  in range  fees paid   net return  vs HODL";
+
+    /// One-line row for sweeps
+    pub fn summary(&self) -> String {
+        format!(
+            "{:>10.4}-{:<13.4} {:>6}bps  {:>10}  {:>7.1}%  {:>9.4}  {:>10.2}%  {:>6.2}%",
+            self.range_min,
+            self.range_max,
+            self.threshold_bps,
+            self.rebalances,
+            self.time_in_range_pct(),
+            self.fees_paid(),
+            self.net_return_pct(),
+            self.vs_hodl_pct(),
+        )
+    }
+}
+
+fn pct_change(value: f64, base: f64) -> f64 {
+    if base > 0.0 {
+        (value / base - 1.0) * 100.0
+    } else {
+        0.0
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Range:              {:.4} - {:.4}", self.range_min, self.range_max)?;
+        writeln!(f, "Threshold:          {} bps", self.threshold_bps)?;
+        writeln!(f, "Duration:           {:.1} days", self.duration_seconds / 86_400.0)?;
+        writeln!(f, "Time in range:      {:.1}%", self.time_in_range_pct())?;
+        writeln!(
+            f,
+            "Rebalances:         {} ({} to LP, {} to lending, {} skipped at range boundary)",
+            self.rebalances, self.moves_to_lp, self.moves_to_lending, self.skipped_uncertain
+        )?;
+        writeln!(f, "LP fees earned:     {:.4}", self.lp_fees_earned)?;
+        writeln!(f, "Lending interest:   {:.4}", self.lending_interest)?;
+        writeln!(
+            f,
+            "Fees paid:          {:.4} (protocol {:.4}, slippage {:.4} over {} swaps)",
+            self.fees_paid(), self.protocol_fees, self.slippage_paid, self.swaps
+        )?;
+        writeln!(f, "Initial value:      {:.4}", self.initial_value)?;
+        writeln!(f, "Final value:        {:.4}", self.final_value)?;
+        writeln!(f, "HODL value:         {:.4}", self.hodl_value)?;
+        writeln!(f, "Net return:         {:.2}%", self.net_return_pct())?;
+        write!(f, "Versus HODL:        {:.2}%", self.vs_hodl_pct())
+    }
+}
@@ -3,20 +3,88 @@ pub const PROTOCOL_SEED: &[u8] = b"protocol";
 pub const USER_SEED: &[u8] = b"user";
 pub const POSITION_SEED: &[u8] = b"position";
 pub const KEEPER_SEED: &[u8] = b"keeper";
+pub const SCHEDULE_SEED: &[u8] = b"schedule";
+pub const PAIR_SEED: &[u8] = b"pair";
+pub const REFERRER_SEED: &[u8] = b"referrer";
+pub const REFERRAL_TIER_SEED: &[u8] = b"referral_tier";
+pub const WITHDRAWAL_SEED: &[u8] = b"withdrawal";
+pub const PRESET_SEED: &[u8] = b"preset";
+pub const REBALANCE_SESSION_SEED: &[u8] = b"rebalance_session";
+pub const FEE_VAULT_SEED: &[u8] = b"fee_vault";
+pub const INSURANCE_VAULT_SEED: &[u8] = b"insurance_vault";
+
+// Account layout versions
+pub const PROTOCOL_VERSION: u8 = 5; // v2: volume fee tiers, v3: fee vaults, v4: insurance fund, v5: external program config
+pub const USER_ACCOUNT_VERSION: u8 = 4; // v2: referrer and first deposit, v3: deposit volume, v4: open position index
+pub const POSITION_VERSION: u8 = 9; // v2: net deposit tracking, v3: hedge leg, v4: out-of-range policy, v5: withdrawal queue, v6: strategy presets, v7: LP fee snapshot, v8: rebalance sessions, v9: zero-copy layout
+pub const PAIR_CONFIG_VERSION: u8 = 3; // v2: yield-aware venue choice, v3: lookup table
+pub const DEPOSIT_SCHEDULE_VERSION: u8 = 1;
+pub const REFERRER_VERSION: u8 = 1;
+pub const REFERRAL_TIER_VERSION: u8 = 1;
+pub const WITHDRAWAL_REQUEST_VERSION: u8 = 1;
+pub const STRATEGY_PRESET_VERSION: u8 = 1;
+pub const REBALANCE_SESSION_VERSION: u8 = 1;
+
+// Spare bytes at the end of each account for future fields
+pub const PROTOCOL_RESERVED_BYTES: usize = 79;
+pub const USER_RESERVED_BYTES: usize = 15;
+pub const POSITION_RESERVED_BYTES: usize = 128; // keeps the zero-copy size a multiple of 8
+pub const PAIR_RESERVED_BYTES: usize = 28;
+pub const DEPOSIT_SCHEDULE_RESERVED_BYTES: usize = 32;
+pub const REFERRER_RESERVED_BYTES: usize = 64;
+pub const REFERRAL_TIER_RESERVED_BYTES: usize = 32;
+pub const WITHDRAWAL_REQUEST_RESERVED_BYTES: usize = 32;
+pub const STRATEGY_PRESET_RESERVED_BYTES: usize = 64;
+pub const REBALANCE_SESSION_RESERVED_BYTES: usize = 32;
 
 // Oracle settings
+pub const PRICE_DECIMALS: u32 = 6; // prices are token B quoted in token A units, 6 decimals
 pub const PRICE_MAX_AGE: u64 = 60; // 60 seconds
 pub const PRICE_CONFIDENCE_MULTIPLIER: u64 = 2; // Max 2x confidence interval
 
 // Protocol limits
 pub const MAX_BATCH_SIZE: usize = 10;
 pub const MAX_FEE_BPS: u16 = 1000; // 10% max fee
+pub const MAX_FEE_TIERS: usize = 4;
+pub const MAX_FEE_VAULTS: usize = 8; // mints with a protocol fee vault
+pub const MAX_FEE_RECIPIENTS: usize = 5; // recipients one sweep can split fees between
+pub const MAX_INSURANCE_SHARE_BPS: u16 = 5000; // at most half of each protocol fee goes to insurance
+pub const PROGRAM_UPDATE_TIMELOCK: i64 = 172_800; // 2 days between proposing and applying new venue program IDs
+pub const VOLUME_DECIMALS: u32 = 6; // deposit volume is token A value at 6 decimals
 pub const MIN_POSITION_VALUE: u64 = 1_000_000; // $1 minimum position
+pub const MAX_REFERRAL_SHARE_BPS: u16 = 5000; // referrers get at most half the fee
+pub const MAX_OPEN_POSITIONS: usize = 16; // size of the open position index on the user account
+
+// Portfolio operations (positions passed as remaining accounts, up to MAX_BATCH_SIZE)
+pub const PORTFOLIO_WITHDRAW_ACCOUNTS: usize = 9; // position, vaults A/B, user tokens A/B, fee vaults A/B, insurance vaults A/B
+pub const PORTFOLIO_VALUE_ACCOUNTS: usize = 3; // position, pair config, price update
+
+// Pair address lookup tables
+pub const LOOKUP_TABLE_MAX_ADDRESSES: usize = 256;
+
+// Recurring deposit parameters
+pub const MIN_SCHEDULE_INTERVAL: i64 = 3600; // 1 hour
 
 // Rebalancing parameters
 pub const REBALANCE_THRESHOLD_BPS: u16 = 100; // 1% price movement threshold
+pub const MIN_SLOTS_BETWEEN_REBALANCES: u64 = 25; // ~10 seconds
 pub const MAX_SLIPPAGE_BPS: u16 = 200; // 2% max slippage
+pub const REBALANCE_SESSION_TIMEOUT_SLOTS: u64 = 750; // ~5 minutes to crank all phases
+
+// Venue yield comparison
+pub const YIELD_SAMPLE_MIN_SECONDS: i64 = 3600; // LP fee samples shorter than 1 hour are too noisy
+pub const YIELD_SAMPLE_WINDOW_SECONDS: i64 = 86_400; // roll the LP fee snapshot daily
+pub const SECONDS_PER_YEAR: i64 = 31_536_000;
 
 // LP parameters
 pub const LP_FEE_TIER: u16 = 500; // 0.05% fee tier for Meteora
 pub const MIN_TICK_SPACING: i32 = 10;
+
+// Laddered LP parameters
+pub const MAX_LP_RUNGS: usize = 4;
+pub const LP_RUNG_ACCOUNTS: usize = 3; // meteora position + lower/upper bin arrays per rung
+pub const RUNG_REWEIGHT_THRESHOLD_BPS: u16 = 500; // 5% drift from target weight
+
+// Hedge leg parameters
+pub const HEDGE_TARGET_LTV_BPS: u16 = 5000; // borrow against at most 50% of collateral value
+pub const HEDGE_DELEVERAGE_BUFFER_BPS: u16 = 1000; // deleverage within 10% of the liquidation threshold
@@ -51,4 +51,178 @@ pub enum ErrorCode {
 
     #[msg("Invalid account data")]
     InvalidAccountData,
+
+    #[msg("Invalid LP ladder: each rung needs a valid range and weights must sum to 10000 bps")]
+    InvalidLadderConfig,
+
+    #[msg("LP liquidity must be withdrawn before changing the ladder")]
+    LiquidityInLP,
+
+    #[msg("Missing or mismatched Meteora accounts for LP rung")]
+    RungAccountsMismatch,
+
+    #[msg("Signer is not the position owner or operator")]
+    UnauthorizedOperator,
+
+    #[msg("Range is set by the LP ladder - reconfigure the ladder instead")]
+    RangeSetByLadder,
+
+    #[msg("Invalid deposit schedule: needs a non-zero amount, interval >= 1 hour and an end after the first run")]
+    InvalidSchedule,
+
+    #[msg("Scheduled deposit is not due yet")]
+    ScheduleNotDue,
+
+    #[msg("Deposit schedule has ended")]
+    ScheduleExpired,
+
+    #[msg("Signer is not the protocol admin")]
+    UnauthorizedAdmin,
+
+    #[msg("Position is flagged by an audit - automation is halted")]
+    PositionFlagged,
+
+    #[msg("Nothing to resolve for this position")]
+    NothingToResolve,
+
+    #[msg("Account layout is not a known version")]
+    UnsupportedAccountVersion,
+
+    #[msg("Token pair is not supported")]
+    PairNotSupported,
+
+    #[msg("Position size outside the pair's min/max limits")]
+    PositionSizeOutOfBounds,
+
+    #[msg("Venue account does not match the pair configuration")]
+    VenueMismatch,
+
+    #[msg("Venue account is not owned by the position")]
+    VenueAccountNotOwned,
+
+    #[msg("Account does not match the address derived by the venue program")]
+    InvalidDerivedAccount,
+
+    #[msg("Referral share exceeds maximum")]
+    InvalidReferralShare,
+
+    #[msg("Users cannot refer themselves")]
+    SelfReferral,
+
+    #[msg("Referrer does not match the one recorded for this user")]
+    ReferrerMismatch,
+
+    #[msg("Referral tier or referrer token accounts missing or invalid")]
+    InvalidReferralAccounts,
+
+    #[msg("No referral fees to claim")]
+    NothingToClaim,
+
+    #[msg("Fee tiers must be ascending by volume with fees within the maximum")]
+    InvalidFeeTiers,
+
+    #[msg("Kamino borrow accounts required while the hedge is open")]
+    HedgeAccountsMissing,
+
+    #[msg("Hedge borrow would leave the obligation too close to liquidation")]
+    HedgeUnhealthy,
+
+    #[msg("Hedge must be unwound before a full withdrawal")]
+    HedgeStillOpen,
+
+    #[msg("Out-of-range conversion needs a Jupiter route")]
+    SwapRouteRequired,
+
+    #[msg("Swap route spent more than the conversion amount")]
+    InvalidSwapRoute,
+
+    #[msg("No vault liquidity to fill the queued withdrawal yet")]
+    NothingToFulfill,
+
+    #[msg("Open position index is full")]
+    TooManyOpenPositions,
+
+    #[msg("Position is not an open position of this user")]
+    PositionNotIndexed,
+
+    #[msg("Portfolio accounts are missing, duplicated or exceed the batch size")]
+    InvalidPortfolioAccounts,
+
+    #[msg("Position vaults can't cover the portfolio withdrawal; withdraw it on its own to queue the rest")]
+    PortfolioWithdrawShortfall,
+
+    #[msg("Portfolio positions must share token A to be valued together")]
+    PortfolioQuoteMismatch,
+
+    #[msg("Invalid strategy preset: range, rung weights or threshold out of bounds")]
+    InvalidStrategyPreset,
+
+    #[msg("Strategy preset is disabled")]
+    PresetDisabled,
+
+    #[msg("Position is locked by an open rebalance session")]
+    PositionRebalancing,
+
+    #[msg("Position doesn't need rebalancing")]
+    NothingToRebalance,
+
+    #[msg("Rebalance session has expired")]
+    RebalanceSessionExpired,
+
+    #[msg("Rebalance session has not expired yet")]
+    RebalanceSessionActive,
+
+    #[msg("Price left the range since the rebalance session started")]
+    RebalanceSessionStale,
+
+    #[msg("Pair already has a lookup table")]
+    LookupTableExists,
+
+    #[msg("Lookup table is not the pair's table")]
+    InvalidLookupTable,
+
+    #[msg("Lookup table can't hold more addresses")]
+    LookupTableFull,
+
+    #[msg("No fee vault exists for this mint")]
+    FeeVaultNotFound,
+
+    #[msg("Maximum number of fee vaults reached")]
+    TooManyFeeVaults,
+
+    #[msg("Fee split must name 1 to 5 recipients with shares summing to 10000 bps")]
+    InvalidFeeSplit,
+
+    #[msg("No fees to sweep")]
+    NothingToSweep,
+
+    #[msg("No insurance vault exists for this mint")]
+    InsuranceVaultNotFound,
+
+    #[msg("Insurance share exceeds the maximum")]
+    InvalidInsuranceShare,
+
+    #[msg("Insurance vault doesn't hold enough to pay this compensation")]
+    InsufficientInsuranceFunds,
+
+    #[msg("Compensation must be a positive amount of one of the position's tokens")]
+    InvalidCompensation,
+
+    #[msg("Program account doesn't match the configured external program")]
+    ExternalProgramMismatch,
+
+    #[msg("External program IDs must all be set")]
+    InvalidExternalPrograms,
+
+    #[msg("No external program update is pending")]
+    NoPendingProgramUpdate,
+
+    #[msg("External program update is still timelocked")]
+    ProgramUpdateTimelocked,
+
+    #[msg("Queued withdrawal must be filled or cancelled before closing the position")]
+    WithdrawalPending,
+
+    #[msg("Deposit schedule must be cancelled before closing the position")]
+    DepositScheduleActive,
 }
@@ -1,4 +1,5 @@
 use anchor_lang::prelude::*;
+use crate::state::{AuditResolution, ExternalPrograms, RebalancePhase, RewardRoute};
 
 #[event]
 pub struct DepositEvent {
@@ -8,6 +9,8 @@ pub struct DepositEvent {
     pub amount_b: u64,
     pub fee_a: u64,
     pub fee_b: u64,
+    pub fee_tier: Option<u8>, // None = flat protocol fee
+    pub fee_bps: u16,
 }
 
 #[event]
@@ -19,6 +22,56 @@ pub struct WithdrawEvent {
     pub fee_a: u64,
     pub fee_b: u64,
     pub percentage: u8,
+    pub fee_tier: Option<u8>, // None = flat protocol fee
+    pub fee_bps: u16,
+    pub queued_a: u64, // gross share left owed on the withdrawal request
+    pub queued_b: u64,
+}
+
+#[event]
+pub struct WithdrawalQueuedEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub queued_a: u64,
+    pub queued_b: u64,
+    pub owed_a: u64,
+    pub owed_b: u64,
+}
+
+#[event]
+pub struct WithdrawalFilledEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub keeper: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+    pub remaining_a: u64,
+    pub remaining_b: u64,
+}
+
+#[event]
+pub struct WithdrawalCancelledEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub owed_a: u64,
+    pub owed_b: u64,
+    pub filled_a: u64,
+    pub filled_b: u64,
+}
+
+#[event]
+pub struct ScheduledDepositEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub schedule: Pubkey,
+    pub keeper: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub fee_a: u64,
+    pub fee_b: u64,
+    pub execution: u64,
 }
 
 #[event]
@@ -40,6 +93,163 @@ pub struct RebalanceEvent {
     pub action: RebalanceAction,
 }
 
+#[event]
+pub struct RebalancePhaseEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub phase: RebalancePhase, // phase that just completed
+    pub target_lp: bool,
+    pub current_price: u64,
+}
+
+#[event]
+pub struct RebalanceSessionExpiredEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub phase: RebalancePhase, // phase that never ran
+    pub keeper: Pubkey,
+}
+
+#[event]
+pub struct CompoundEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub fee_a: u64,
+    pub fee_b: u64,
+    pub reward_mint: Option<Pubkey>,
+    pub reward_amount: u64,
+    pub reward_swapped_a: u64,
+    pub reward_swapped_b: u64,
+    pub compounded_a: u64,
+    pub compounded_b: u64,
+}
+
+#[event]
+pub struct KaminoRewardHarvestEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub farm_state: Pubkey,
+    pub reward_mint: Pubkey,
+    pub reward_index: u64,
+    pub reward_amount: u64,
+    pub route: RewardRoute,
+    pub swapped_a: u64,
+    pub swapped_b: u64,
+}
+
+#[event]
+pub struct PositionAuditEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub vault_a_tracked: u64,
+    pub vault_a_actual: u64,
+    pub vault_b_tracked: u64,
+    pub vault_b_actual: u64,
+    pub lending_a_tracked: u64,
+    pub lending_a_actual: Option<u64>,
+    pub lending_b_tracked: u64,
+    pub lending_b_actual: Option<u64>,
+    pub lp_positions_expected: u8,
+    pub lp_positions_verified: u8,
+    pub has_discrepancy: bool,
+}
+
+#[event]
+pub struct AuditResolvedEvent {
+    pub position_id: u64,
+    pub resolver: Pubkey,
+    pub resolution: AuditResolution,
+    pub credited_a: u64,
+    pub credited_b: u64,
+}
+
+#[event]
+pub struct ReferralFeeEvent {
+    pub referrer: Pubkey,
+    pub user: Pubkey,
+    pub position_id: u64,
+    pub tier: u8,
+    pub amount_a: u64,
+    pub amount_b: u64,
+}
+
+#[event]
+pub struct ReferralClaimedEvent {
+    pub referrer: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[event]
+pub struct FeeSweptEvent {
+    pub mint: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub share_bps: u16,
+}
+
+#[event]
+pub struct InsuranceCompensationEvent {
+    pub position: Pubkey,
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub reason: [u8; 32],
+    pub admin: Pubkey,
+    pub insurance_remaining: u64,
+    pub total_compensated: u64,
+}
+
+#[event]
+pub struct ExternalProgramsProposedEvent {
+    pub programs: ExternalPrograms,
+    pub eta: i64,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct ExternalProgramsUpdatedEvent {
+    pub previous: ExternalPrograms,
+    pub programs: ExternalPrograms,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct HedgeEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub current_price: u64,
+    pub collateral_a: u64,
+    pub borrowed_b: u64,
+    pub target_borrow_b: u64,
+    pub ltv_bps: u16,
+    pub liquidation_threshold_bps: u16,
+    pub deleveraged: bool,
+}
+
+#[event]
+pub struct RangeConversionEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub current_price: u64,
+    pub sold_token_a: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub reentry: bool, // converting back to a balanced LP
+}
+
+#[event]
+pub struct VenueYieldEvent {
+    pub position_id: u64,
+    pub owner: Pubkey,
+    pub lp_fee_apr_bps: u64,
+    pub lending_apy_bps: u64,
+    pub margin_bps: u16,
+    pub prefer_lending: bool,
+}
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
 pub enum RebalanceAction {
     NoAction,
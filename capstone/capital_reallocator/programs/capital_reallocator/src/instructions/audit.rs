@@ -0,0 +1,246 @@
+// instructions/audit.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::TokenAccount;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::{PositionAuditEvent, AuditResolvedEvent};
+use crate::constants::*;
+use crate::protocols::kamino::{
+    read_obligation_owner, read_obligation_liquidity, read_reserve_liquidity_mint,
+};
+use crate::protocols::meteora::read_meteora_position;
+
+// Compare tracked balances with vaults and venue state (permissionless)
+#[derive(Accounts)]
+pub struct AuditPosition<'info> {
+    #[account(
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        associated_token::mint = position.load()?.token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        associated_token::mint = position.load()?.token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+    
+    // Venue accounts are read against the configured program IDs
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    /// CHECK: Kamino obligation - must be the one tracked by the position
+    #[account(constraint = position.load()?.kamino_obligation() == Some(kamino_obligation.key()) @ ErrorCode::LendingPositionNotFound)]
+    pub kamino_obligation: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Kamino reserve for token A, validated by its liquidity mint
+    pub kamino_reserve_a: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Kamino reserve for token B, validated by its liquidity mint
+    pub kamino_reserve_b: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> AuditPosition<'info> {
+    /// Tracked Meteora positions (single range or each ladder rung) are
+    /// passed as remaining accounts in the order they appear on the position
+    pub fn audit(&self, lp_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let position = self.position.load()?;
+        
+        // 1. Idle vault balances
+        let vault_a_actual = self.position_token_a_vault.amount;
+        let vault_b_actual = self.position_token_b_vault.amount;
+        let vault_mismatch = vault_a_actual != position.token_a_vault_balance ||
+            vault_b_actual != position.token_b_vault_balance;
+        
+        // 2. Meteora positions
+        let tracked_lp: Vec<Pubkey> = if position.has_ladder() {
+            position.active_rungs().iter().filter_map(|rung| rung.meteora_position()).collect()
+        } else {
+            position.meteora_position().into_iter().collect()
+        };
+        
+        let mut lp_positions_verified: u8 = 0;
+        for (expected, account) in tracked_lp.iter().zip(lp_accounts.iter()) {
+            if account.key() != *expected {
+                continue;
+            }
+            // An account Meteora has closed or reassigned simply fails verification
+            if let Ok((_, owner)) = read_meteora_position(account, &self.protocol_authority.external_programs.meteora_dlmm) {
+                if owner == self.position.key() {
+                    lp_positions_verified += 1;
+                }
+            }
+        }
+        
+        let has_lp = position.token_a_in_lp > 0 || position.token_b_in_lp > 0;
+        let lp_mismatch = (has_lp && tracked_lp.is_empty()) ||
+            (lp_positions_verified as usize) < tracked_lp.len();
+        
+        // 3. Kamino lending
+        let (lending_a_actual, lending_b_actual) = self.lending_balances()?;
+        
+        // Interest makes actual > tracked normal, only a shortfall is a discrepancy.
+        // Hedge collateral is deposited in reserve A of the same obligation.
+        let has_lending = position.token_a_in_lending > 0 || position.token_b_in_lending > 0;
+        let expected_a = position.token_a_in_lending.saturating_add(position.hedge_collateral_a);
+        let lending_mismatch = (has_lending && position.kamino_obligation().is_none()) ||
+            lending_a_actual.is_some_and(|actual| actual < expected_a) ||
+            lending_b_actual.is_some_and(|actual| actual < position.token_b_in_lending);
+        
+        let has_discrepancy = vault_mismatch || lp_mismatch || lending_mismatch;
+        
+        emit!(PositionAuditEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            vault_a_tracked: position.token_a_vault_balance,
+            vault_a_actual,
+            vault_b_tracked: position.token_b_vault_balance,
+            vault_b_actual,
+            lending_a_tracked: position.token_a_in_lending,
+            lending_a_actual,
+            lending_b_tracked: position.token_b_in_lending,
+            lending_b_actual,
+            lp_positions_expected: tracked_lp.len() as u8,
+            lp_positions_verified,
+            has_discrepancy,
+        });
+        
+        if has_discrepancy {
+            msg!(
+                "Audit discrepancy on position {}: vault A {}/{}, vault B {}/{}, LP {}/{}",
+                position.position_id,
+                vault_a_actual, position.token_a_vault_balance,
+                vault_b_actual, position.token_b_vault_balance,
+                lp_positions_verified, tracked_lp.len()
+            );
+        } else {
+            msg!("Position {} audit passed", position.position_id);
+        }
+        Ok(())
+    }
+    
+    fn lending_balances(&self) -> Result<(Option<u64>, Option<u64>)> {
+        let Some(obligation) = self.kamino_obligation.as_ref() else {
+            return Ok((None, None));
+        };
+        
+        require_keys_eq!(
+            read_obligation_owner(obligation, &self.protocol_authority.external_programs.kamino_lending)?,
+            self.position.key(),
+            ErrorCode::InvalidAccountData
+        );
+        
+        let lending_a = match self.kamino_reserve_a.as_ref() {
+            Some(reserve) => Some(self.lending_balance(obligation, reserve, self.position.load()?.token_a_mint)?),
+            None => None,
+        };
+        let lending_b = match self.kamino_reserve_b.as_ref() {
+            Some(reserve) => Some(self.lending_balance(obligation, reserve, self.position.load()?.token_b_mint)?),
+            None => None,
+        };
+        
+        Ok((lending_a, lending_b))
+    }
+    
+    fn lending_balance(&self, obligation: &AccountInfo<'info>, reserve: &AccountInfo<'info>, mint: Pubkey) -> Result<u64> {
+        let kamino_program = self.protocol_authority.external_programs.kamino_lending;
+        require_keys_eq!(read_reserve_liquidity_mint(reserve, &kamino_program)?, mint, ErrorCode::InvalidAccountData);
+        
+        read_obligation_liquidity(obligation, reserve, &kamino_program)
+    }
+}
+
+// Resolve an audit discrepancy (owner or protocol admin)
+#[derive(Accounts)]
+pub struct ResolveAudit<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    #[account(
+        associated_token::mint = position.load()?.token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        associated_token::mint = position.load()?.token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        constraint = authority.key() == position.load()?.owner ||
+            authority.key() == protocol_authority.admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub authority: Signer<'info>,
+}
+
+impl<'info> ResolveAudit<'info> {
+    pub fn resolve(&mut self, resolution: AuditResolution) -> Result<()> {
+        let mut credited_a = 0;
+        let mut credited_b = 0;
+        let mut position = self.position.load_mut()?;
+        
+        match resolution {
+            AuditResolution::CreditDonation => {
+                // Only surplus can be adopted; a shortfall has to be flagged
+                credited_a = self.position_token_a_vault.amount
+                    .saturating_sub(position.token_a_vault_balance);
+                credited_b = self.position_token_b_vault.amount
+                    .saturating_sub(position.token_b_vault_balance);
+                
+                require!(credited_a > 0 || credited_b > 0, ErrorCode::NothingToResolve);
+                
+                position.token_a_vault_balance += credited_a;
+                position.token_b_vault_balance += credited_b;
+                
+                msg!("Credited donations of {} A and {} B", credited_a, credited_b);
+            }
+            AuditResolution::FlagPosition => {
+                position.set_flagged(true);
+                msg!("Position {} flagged", position.position_id);
+            }
+            AuditResolution::ClearFlag => {
+                require_keys_eq!(
+                    self.authority.key(),
+                    self.protocol_authority.admin,
+                    ErrorCode::UnauthorizedAdmin
+                );
+                require!(position.is_flagged(), ErrorCode::NothingToResolve);
+                
+                position.set_flagged(false);
+                msg!("Position {} flag cleared", position.position_id);
+            }
+        }
+        
+        emit!(AuditResolvedEvent {
+            position_id: position.position_id,
+            resolver: self.authority.key(),
+            resolution,
+            credited_a,
+            credited_b,
+        });
+        
+        Ok(())
+    }
+}
@@ -0,0 +1,439 @@
+// instructions/compound.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::CompoundEvent;
+use crate::constants::*;
+use crate::instructions::rebalance::get_pair_price;
+
+// Compound Meteora fees and farming rewards back into the LP
+#[derive(Accounts)]
+pub struct CompoundPosition<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        has_one = owner,
+        has_one = token_a_mint,
+        has_one = token_b_mint,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        seeds = [PAIR_SEED, token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        bump = pair_config.bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
+    pub price_update: Box<Account<'info, PriceUpdateV2>>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+    
+    // Position-owned token account that receives the farming reward
+    #[account(
+        mut,
+        constraint = position_reward_vault.owner == position.key()
+    )]
+    pub position_reward_vault: Option<Box<Account<'info, TokenAccount>>>,
+    
+    // Meteora DLMM Accounts
+    /// CHECK: Meteora DLMM program
+    #[account(address = protocol_authority.external_programs.meteora_dlmm @ ErrorCode::ExternalProgramMismatch)]
+    pub meteora_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora LB pair account - must be the pair's canonical pool
+    #[account(mut, address = pair_config.meteora_lb_pair @ ErrorCode::VenueMismatch)]
+    pub meteora_lb_pair: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora position account - must be tracked by the position
+    #[account(mut)]
+    pub meteora_position: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora reserve X
+    #[account(mut)]
+    pub meteora_reserve_x: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora reserve Y
+    #[account(mut)]
+    pub meteora_reserve_y: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora bin arrays
+    #[account(mut)]
+    pub meteora_bin_array_lower: UncheckedAccount<'info>,
+    /// CHECK: Meteora bin arrays
+    #[account(mut)]
+    pub meteora_bin_array_upper: UncheckedAccount<'info>,
+    
+    /// CHECK: Meteora reward vault for the claimed reward index
+    #[account(mut)]
+    pub meteora_reward_vault: Option<UncheckedAccount<'info>>,
+    
+    pub reward_mint: Option<Box<Account<'info, Mint>>>,
+    
+    /// CHECK: Meteora event authority
+    pub meteora_event_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: Jupiter aggregator program
+    #[account(address = protocol_authority.external_programs.jupiter @ ErrorCode::ExternalProgramMismatch)]
+    pub jupiter_program: UncheckedAccount<'info>,
+    
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+    pub owner: Signer<'info>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+impl<'info> CompoundPosition<'info> {
+    pub fn compound(
+        &mut self,
+        reward_index: Option<u64>,
+        swap_data: Vec<u8>,
+        min_swap_out_a: u64,
+        min_swap_out_b: u64,
+        route_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        {
+            let position = self.position.load()?;
+            require!(!position.is_paused(), ErrorCode::PositionPaused);
+            require!(!position.is_flagged(), ErrorCode::PositionFlagged);
+        }
+        
+        let rung_index = self.tracked_rung()?;
+        
+        // 1. Claim swap fees into the position vaults
+        let start_a = self.position_token_a_vault.amount;
+        let start_b = self.position_token_b_vault.amount;
+        
+        Position::claim_meteora_fee_cpi(
+            &self.position,
+            &self.meteora_program,
+            &self.meteora_lb_pair,
+            &self.meteora_position,
+            &self.meteora_bin_array_lower,
+            &self.meteora_bin_array_upper,
+            &self.meteora_reserve_x,
+            &self.meteora_reserve_y,
+            &self.position_token_a_vault,
+            &self.position_token_b_vault,
+            &self.token_a_mint,
+            &self.token_b_mint,
+            &self.token_program,
+            &self.meteora_event_authority,
+        )?;
+        
+        self.position_token_a_vault.reload()?;
+        self.position_token_b_vault.reload()?;
+        
+        let fee_a = self.position_token_a_vault.amount
+            .checked_sub(start_a)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let fee_b = self.position_token_b_vault.amount
+            .checked_sub(start_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // 2. Claim farming rewards into the reward vault
+        let mut reward_mint_key = None;
+        let mut reward_amount = 0;
+        
+        if let Some(reward_index) = reward_index {
+            reward_amount = self.claim_reward(reward_index)?;
+            reward_mint_key = self.reward_mint.as_ref().map(|mint| mint.key());
+        }
+        
+        // 3. Swap rewards to the pair tokens
+        let pre_swap_a = self.position_token_a_vault.amount;
+        let pre_swap_b = self.position_token_b_vault.amount;
+        
+        if reward_amount > 0 {
+            self.convert_reward(reward_amount, swap_data, min_swap_out_a, min_swap_out_b, route_accounts)?;
+        }
+        
+        let reward_swapped_a = self.position_token_a_vault.amount
+            .checked_sub(pre_swap_a)
+            .ok_or(ErrorCode::SlippageExceeded)?;
+        let reward_swapped_b = self.position_token_b_vault.amount
+            .checked_sub(pre_swap_b)
+            .ok_or(ErrorCode::SlippageExceeded)?;
+        
+        let compounded_a = fee_a.checked_add(reward_swapped_a).ok_or(ErrorCode::MathOverflow)?;
+        let compounded_b = fee_b.checked_add(reward_swapped_b).ok_or(ErrorCode::MathOverflow)?;
+        
+        {
+            let mut position = self.position.load_mut()?;
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(compounded_a)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(compounded_b)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        // 4. Redeploy into the LP that earned them
+        if compounded_a > 0 || compounded_b > 0 {
+            self.redeploy(rung_index, compounded_a, compounded_b)?;
+        }
+        
+        let position = self.position.load()?;
+        emit!(CompoundEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            fee_a,
+            fee_b,
+            reward_mint: reward_mint_key,
+            reward_amount,
+            reward_swapped_a,
+            reward_swapped_b,
+            compounded_a,
+            compounded_b,
+        });
+        
+        msg!(
+            "Compounded {} token A and {} token B (fees: {}, {}; reward: {})",
+            compounded_a, compounded_b, fee_a, fee_b, reward_amount
+        );
+        Ok(())
+    }
+    
+    // Which ladder rung (if any) owns the Meteora position being compounded
+    fn tracked_rung(&self) -> Result<Option<usize>> {
+        let meteora_position = self.meteora_position.key();
+        let position = self.position.load()?;
+        
+        if position.has_ladder() {
+            let rung_index = position.active_rungs()
+                .iter()
+                .position(|rung| rung.meteora_position() == Some(meteora_position))
+                .ok_or(ErrorCode::RungAccountsMismatch)?;
+            return Ok(Some(rung_index));
+        }
+        
+        require!(
+            position.meteora_position() == Some(meteora_position),
+            ErrorCode::LPPositionNotFound
+        );
+        Ok(None)
+    }
+    
+    fn claim_reward(&mut self, reward_index: u64) -> Result<u64> {
+        let meteora_reward_vault = self.meteora_reward_vault.as_ref()
+            .ok_or(ErrorCode::InvalidAccountData)?;
+        let reward_mint = self.reward_mint.as_ref()
+            .ok_or(ErrorCode::InvalidAccountData)?;
+        let position_reward_vault = self.position_reward_vault.as_mut()
+            .ok_or(ErrorCode::InvalidAccountData)?;
+        
+        require_keys_eq!(position_reward_vault.mint, reward_mint.key(), ErrorCode::InvalidAccountData);
+        
+        let reward_before = position_reward_vault.amount;
+        
+        Position::claim_meteora_reward_cpi(
+            &self.position,
+            &self.meteora_program,
+            &self.meteora_lb_pair,
+            &self.meteora_position,
+            &self.meteora_bin_array_lower,
+            &self.meteora_bin_array_upper,
+            meteora_reward_vault,
+            reward_mint,
+            position_reward_vault,
+            &self.token_program,
+            &self.meteora_event_authority,
+            reward_index,
+        )?;
+        
+        position_reward_vault.reload()?;
+        
+        position_reward_vault.amount
+            .checked_sub(reward_before)
+            .ok_or(ErrorCode::MathOverflow.into())
+    }
+    
+    fn convert_reward(
+        &mut self,
+        reward_amount: u64,
+        swap_data: Vec<u8>,
+        min_swap_out_a: u64,
+        min_swap_out_b: u64,
+        route_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let position_reward_vault = self.position_reward_vault.as_ref()
+            .ok_or(ErrorCode::InvalidAccountData)?;
+        
+        convert_reward_to_pair(
+            &self.position,
+            position_reward_vault,
+            &mut self.position_token_a_vault,
+            &mut self.position_token_b_vault,
+            &self.jupiter_program,
+            &self.token_program,
+            reward_amount,
+            swap_data,
+            min_swap_out_a,
+            min_swap_out_b,
+            route_accounts,
+        )
+    }
+    
+    fn redeploy(&mut self, rung_index: Option<usize>, amount_a: u64, amount_b: u64) -> Result<()> {
+        // Get price from Pyth for the bin range calculation
+        let (current_price, _) = get_pair_price(&self.price_update, &self.pair_config, PRICE_MAX_AGE)?;
+        
+        match rung_index {
+            Some(rung_index) => Position::open_meteora_rung_cpi(
+                &self.position,
+                rung_index,
+                &self.meteora_program,
+                &self.meteora_lb_pair,
+                &self.meteora_position,
+                &self.position_token_a_vault,
+                &self.position_token_b_vault,
+                &self.meteora_reserve_x,
+                &self.meteora_reserve_y,
+                &self.token_a_mint,
+                &self.token_b_mint,
+                &self.meteora_bin_array_lower,
+                &self.meteora_bin_array_upper,
+                &self.token_program,
+                &self.system_program,
+                &self.rent,
+                &self.meteora_event_authority,
+                amount_a,
+                amount_b,
+                current_price,
+            ),
+            None => Position::open_meteora_position_cpi(
+                &self.position,
+                &self.meteora_program,
+                &self.meteora_lb_pair,
+                &self.meteora_position,
+                &self.position_token_a_vault,
+                &self.position_token_b_vault,
+                &self.meteora_reserve_x,
+                &self.meteora_reserve_y,
+                &self.token_a_mint,
+                &self.token_b_mint,
+                &self.meteora_bin_array_lower,
+                &self.meteora_bin_array_upper,
+                &self.token_program,
+                &self.system_program,
+                &self.rent,
+                &self.meteora_event_authority,
+                amount_a,
+                amount_b,
+                current_price,
+            ),
+        }
+    }
+}
+
+/// Turn a reward sitting in a position-owned token account into the pair tokens.
+/// Rewards already denominated in token A or B are moved into that vault, anything
+/// else is sold through the supplied Jupiter route. The route may only add to the
+/// pair vaults, and each vault must receive at least its own minimum since raw
+/// token A and token B amounts aren't comparable.
+pub fn convert_reward_to_pair<'info>(
+    position: &AccountLoader<'info, Position>,
+    position_reward_vault: &Account<'info, TokenAccount>,
+    position_token_a_vault: &mut Box<Account<'info, TokenAccount>>,
+    position_token_b_vault: &mut Box<Account<'info, TokenAccount>>,
+    jupiter_program: &AccountInfo<'info>,
+    token_program: &Program<'info, Token>,
+    reward_amount: u64,
+    swap_data: Vec<u8>,
+    min_swap_out_a: u64,
+    min_swap_out_b: u64,
+    route_accounts: &[AccountInfo<'info>],
+) -> Result<()> {
+    // Claiming straight into a pair vault would hide the reward from the deltas below
+    require!(
+        position_reward_vault.key() != position_token_a_vault.key() &&
+        position_reward_vault.key() != position_token_b_vault.key(),
+        ErrorCode::InvalidAccountData
+    );
+    
+    let (token_a_mint, token_b_mint) = {
+        let position = position.load()?;
+        (position.token_a_mint, position.token_b_mint)
+    };
+    let pair_vault = if position_reward_vault.mint == token_a_mint {
+        Some(position_token_a_vault.to_account_info())
+    } else if position_reward_vault.mint == token_b_mint {
+        Some(position_token_b_vault.to_account_info())
+    } else {
+        None
+    };
+    
+    let pre_swap_a = position_token_a_vault.amount;
+    let pre_swap_b = position_token_b_vault.amount;
+    
+    if let Some(pair_vault) = pair_vault {
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&position_seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                token_program.to_account_info(),
+                Transfer {
+                    from: position_reward_vault.to_account_info(),
+                    to: pair_vault,
+                    authority: position.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            reward_amount,
+        )?;
+    } else {
+        require!(!swap_data.is_empty(), ErrorCode::InvalidAccountData);
+        
+        Position::jupiter_swap_cpi(
+            position,
+            jupiter_program,
+            route_accounts,
+            swap_data,
+        )?;
+    }
+    
+    position_token_a_vault.reload()?;
+    position_token_b_vault.reload()?;
+    
+    require!(
+        position_token_a_vault.amount >= pre_swap_a &&
+        position_token_b_vault.amount >= pre_swap_b,
+        ErrorCode::SlippageExceeded
+    );
+    
+    let swapped_a = position_token_a_vault.amount - pre_swap_a;
+    let swapped_b = position_token_b_vault.amount - pre_swap_b;
+    
+    require!(
+        swapped_a >= min_swap_out_a && swapped_b >= min_swap_out_b,
+        ErrorCode::SlippageExceeded
+    );
+    
+    msg!("Converted {} reward into {} token A and {} token B", reward_amount, swapped_a, swapped_b);
+    Ok(())
+}
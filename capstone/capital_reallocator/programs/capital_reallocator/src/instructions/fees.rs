@@ -0,0 +1,176 @@
+// instructions/fees.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::FeeSweptEvent;
+use crate::constants::*;
+
+// Replace the volume fee tier table (protocol admin)
+#[derive(Accounts)]
+pub struct SetFeeTiers<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetFeeTiers<'info> {
+    /// An empty table reverts everyone to the flat protocol fee
+    pub fn set_tiers(&mut self, tiers: Vec<FeeTier>) -> Result<()> {
+        require!(tiers.len() <= MAX_FEE_TIERS, ErrorCode::InvalidFeeTiers);
+        require!(
+            tiers.windows(2).all(|pair| pair[0].min_volume < pair[1].min_volume),
+            ErrorCode::InvalidFeeTiers
+        );
+        require!(
+            tiers.iter().all(|tier| tier.deposit_fee_bps <= MAX_FEE_BPS && tier.withdraw_fee_bps <= MAX_FEE_BPS),
+            ErrorCode::InvalidFeeTiers
+        );
+        
+        let protocol = &mut self.protocol_authority;
+        protocol.fee_tiers = [FeeTier::default(); MAX_FEE_TIERS];
+        protocol.fee_tiers[..tiers.len()].copy_from_slice(&tiers);
+        protocol.fee_tier_count = tiers.len() as u8;
+        
+        for (index, tier) in tiers.iter().enumerate() {
+            msg!(
+                "Fee tier {}: volume >= {} pays {} bps deposit, {} bps withdraw",
+                index, tier.min_volume, tier.deposit_fee_bps, tier.withdraw_fee_bps
+            );
+        }
+        Ok(())
+    }
+}
+
+// Create the protocol fee vault for a mint (protocol admin). Deposit and
+// withdraw fees in that mint accrue here instead of going to fee_recipient.
+#[derive(Accounts)]
+pub struct CreateFeeVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        seeds = [FEE_VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = protocol_authority
+    )]
+    pub fee_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateFeeVault<'info> {
+    pub fn create_vault(&mut self, bumps: &CreateFeeVaultBumps) -> Result<()> {
+        self.protocol_authority.register_fee_vault(self.mint.key(), bumps.fee_vault)?;
+        
+        msg!("Fee vault {} created for mint {}", self.fee_vault.key(), self.mint.key());
+        Ok(())
+    }
+}
+
+// Pay out a fee vault's balance to several recipients (protocol admin)
+#[derive(Accounts)]
+pub struct SweepFees<'info> {
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&mint.key())?.bump
+    )]
+    pub fee_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub mint: Account<'info, Mint>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> SweepFees<'info> {
+    /// Recipients are token accounts for the mint, passed as writable remaining
+    /// accounts in the order of shares_bps. The last recipient also gets the
+    /// rounding dust, so the vault is always emptied.
+    pub fn sweep(&mut self, shares_bps: Vec<u16>, recipients: &'info [AccountInfo<'info>]) -> Result<()> {
+        require!(
+            !shares_bps.is_empty() && shares_bps.len() <= MAX_FEE_RECIPIENTS && shares_bps.len() == recipients.len(),
+            ErrorCode::InvalidFeeSplit
+        );
+        require!(
+            shares_bps.iter().map(|share| *share as u32).sum::<u32>() == 10_000,
+            ErrorCode::InvalidFeeSplit
+        );
+        
+        let amount = self.fee_vault.amount;
+        require!(amount > 0, ErrorCode::NothingToSweep);
+        
+        let mint = self.mint.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[PROTOCOL_SEED, &[self.protocol_authority.bump]]];
+        let mut remaining = amount;
+        
+        for (index, (info, share_bps)) in recipients.iter().zip(shares_bps).enumerate() {
+            let recipient = Account::<TokenAccount>::try_from(info)?;
+            require_keys_eq!(recipient.mint, mint, ErrorCode::InvalidFeeSplit);
+            
+            let share = if index == recipients.len() - 1 {
+                remaining
+            } else {
+                ProtocolAuthority::fee_at(amount, share_bps)?
+            };
+            remaining -= share;
+            if share == 0 {
+                continue;
+            }
+            
+            token::transfer(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    Transfer {
+                        from: self.fee_vault.to_account_info(),
+                        to: recipient.to_account_info(),
+                        authority: self.protocol_authority.to_account_info(),
+                    },
+                    signer_seeds,
+                ),
+                share,
+            )?;
+            
+            emit!(FeeSweptEvent {
+                mint,
+                recipient: recipient.key(),
+                amount: share,
+                share_bps,
+            });
+        }
+        
+        let vault = self.protocol_authority.fee_vault_mut(&mint)?;
+        vault.swept = vault.swept.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Swept {} fees to {} recipients", amount, recipients.len());
+        Ok(())
+    }
+}
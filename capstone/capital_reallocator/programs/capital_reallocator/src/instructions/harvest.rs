@@ -0,0 +1,231 @@
+// instructions/harvest.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::KaminoRewardHarvestEvent;
+use crate::constants::*;
+use crate::protocols::kamino::FARM_USER_STATE_SEED;
+use crate::instructions::compound::convert_reward_to_pair;
+
+// Harvest farm incentives earned by the position's Kamino obligation
+#[derive(Accounts)]
+pub struct HarvestKaminoRewards<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        has_one = owner,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        mut,
+        associated_token::mint = position.load()?.token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = position.load()?.token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+    
+    // Position-owned token account that receives the farm reward
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = position
+    )]
+    pub position_reward_vault: Box<Account<'info, TokenAccount>>,
+    
+    // Owner's reward account, required for RewardRoute::SendToOwner
+    #[account(
+        mut,
+        token::mint = reward_mint,
+        token::authority = owner
+    )]
+    pub owner_reward_account: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    // Kamino Farms Accounts
+    /// CHECK: Kamino farms program
+    #[account(address = protocol_authority.external_programs.kamino_farms @ ErrorCode::ExternalProgramMismatch)]
+    pub farms_program: UncheckedAccount<'info>,
+    
+    /// CHECK: Kamino obligation - must be the one tracked by the position
+    #[account(constraint = position.load()?.kamino_obligation() == Some(kamino_obligation.key()) @ ErrorCode::LendingPositionNotFound)]
+    pub kamino_obligation: UncheckedAccount<'info>,
+    
+    /// CHECK: Reserve farm state
+    #[account(mut)]
+    pub farm_state: UncheckedAccount<'info>,
+    
+    /// CHECK: Obligation's user state in the farm, derived from farm + obligation
+    #[account(
+        mut,
+        seeds = [FARM_USER_STATE_SEED, farm_state.key().as_ref(), kamino_obligation.key().as_ref()],
+        bump,
+        seeds::program = farms_program.key()
+    )]
+    pub farm_user_state: UncheckedAccount<'info>,
+    
+    /// CHECK: Farms global config
+    pub farm_global_config: UncheckedAccount<'info>,
+    
+    /// CHECK: Farm rewards vault
+    #[account(mut)]
+    pub farm_rewards_vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Farm rewards treasury vault
+    #[account(mut)]
+    pub farm_rewards_treasury_vault: UncheckedAccount<'info>,
+    
+    /// CHECK: Farm vaults authority
+    pub farm_vaults_authority: UncheckedAccount<'info>,
+    
+    /// CHECK: Scope oracle prices, only for farms with oracle-priced rewards
+    pub scope_prices: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Jupiter aggregator program
+    #[account(address = protocol_authority.external_programs.jupiter @ ErrorCode::ExternalProgramMismatch)]
+    pub jupiter_program: UncheckedAccount<'info>,
+    
+    pub reward_mint: Box<Account<'info, Mint>>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> HarvestKaminoRewards<'info> {
+    pub fn harvest(
+        &mut self,
+        reward_index: u64,
+        route: RewardRoute,
+        swap_data: Vec<u8>,
+        min_swap_out_a: u64,
+        min_swap_out_b: u64,
+        route_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        {
+            let position = self.position.load()?;
+            require!(!position.is_paused(), ErrorCode::PositionPaused);
+            require!(!position.is_flagged(), ErrorCode::PositionFlagged);
+        }
+        
+        // 1. Harvest into the position reward vault
+        let reward_before = self.position_reward_vault.amount;
+        
+        Position::harvest_kamino_farm_reward_cpi(
+            &self.position,
+            &self.farms_program,
+            &self.farm_user_state,
+            &self.farm_state,
+            &self.farm_global_config,
+            &self.reward_mint,
+            &self.position_reward_vault,
+            &self.farm_rewards_vault,
+            &self.farm_rewards_treasury_vault,
+            &self.farm_vaults_authority,
+            self.scope_prices.as_ref().map(|account| account.as_ref()),
+            &self.token_program,
+            reward_index,
+        )?;
+        
+        self.position_reward_vault.reload()?;
+        
+        let reward_amount = self.position_reward_vault.amount
+            .checked_sub(reward_before)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        // 2. Route the reward
+        let mut swapped_a = 0;
+        let mut swapped_b = 0;
+        
+        if reward_amount > 0 {
+            match route {
+                RewardRoute::SwapToPosition => {
+                    let pre_swap_a = self.position_token_a_vault.amount;
+                    let pre_swap_b = self.position_token_b_vault.amount;
+                    
+                    convert_reward_to_pair(
+                        &self.position,
+                        &self.position_reward_vault,
+                        &mut self.position_token_a_vault,
+                        &mut self.position_token_b_vault,
+                        &self.jupiter_program,
+                        &self.token_program,
+                        reward_amount,
+                        swap_data,
+                        min_swap_out_a,
+                        min_swap_out_b,
+                        route_accounts,
+                    )?;
+                    
+                    swapped_a = self.position_token_a_vault.amount - pre_swap_a;
+                    swapped_b = self.position_token_b_vault.amount - pre_swap_b;
+                    
+                    // Converted rewards stay idle until the next rebalance deploys them
+                    let mut position = self.position.load_mut()?;
+                    position.token_a_vault_balance = position.token_a_vault_balance
+                        .checked_add(swapped_a)
+                        .ok_or(ErrorCode::MathOverflow)?;
+                    position.token_b_vault_balance = position.token_b_vault_balance
+                        .checked_add(swapped_b)
+                        .ok_or(ErrorCode::MathOverflow)?;
+                }
+                RewardRoute::SendToOwner => {
+                    self.send_to_owner(reward_amount)?;
+                }
+            }
+        }
+        
+        let position = self.position.load()?;
+        emit!(KaminoRewardHarvestEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            farm_state: self.farm_state.key(),
+            reward_mint: self.reward_mint.key(),
+            reward_index,
+            reward_amount,
+            route,
+            swapped_a,
+            swapped_b,
+        });
+        
+        msg!("Harvested {} Kamino farm reward (index {})", reward_amount, reward_index);
+        Ok(())
+    }
+    
+    fn send_to_owner(&self, amount: u64) -> Result<()> {
+        let owner_reward_account = self.owner_reward_account.as_ref()
+            .ok_or(ErrorCode::InvalidAccountData)?;
+        
+        let signer = self.position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&position_seeds];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.position_reward_vault.to_account_info(),
+                    to: owner_reward_account.to_account_info(),
+                    authority: self.position.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        
+        msg!("Sent {} reward tokens to owner", amount);
+        Ok(())
+    }
+}
@@ -2,10 +2,13 @@
 use anchor_lang::prelude::*;
 use anchor_spl::token::{Token, TokenAccount, Mint, Transfer};
 use anchor_spl::associated_token::AssociatedToken;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use crate::state::*;
 use crate::errors::ErrorCode;
 use crate::events::*;
 use crate::constants::*;
+use crate::instructions::rebalance::get_pair_price;
+use crate::instructions::referral::check_referrer_fee_account;
 
 // Initialize Protocol
 #[derive(Accounts)]
@@ -35,13 +38,38 @@ impl<'info> InitializeProtocol<'info> {
             ErrorCode::InvalidPercentage
         );
         
+        // The first initializer becomes admin; only the admin may re-initialize
+        let admin = self.protocol_authority.admin;
+        require!(
+            admin == Pubkey::default() || admin == self.payer.key(),
+            ErrorCode::UnauthorizedAdmin
+        );
+        
         self.protocol_authority.set_inner(ProtocolAuthority {
+            version: PROTOCOL_VERSION,
             program_id: crate::ID,
+            admin: self.payer.key(),
             fee_recipient: self.fee_recipient.key(),
             protocol_fee_bps: fee_bps,
-            total_positions: 0,
+            // Open positions keep counting, so re-initializing can't skip the program update timelock
+            total_positions: self.protocol_authority.total_positions,
             bump: bumps.protocol_authority,
+            // Volume tiers are only changed through set_fee_tiers
+            fee_tier_count: self.protocol_authority.fee_tier_count,
+            fee_tiers: self.protocol_authority.fee_tiers,
+            // Fee and insurance vaults keep their counters across re-initialization
+            fee_vault_count: self.protocol_authority.fee_vault_count,
+            fee_vaults: self.protocol_authority.fee_vaults,
+            insurance_share_bps: self.protocol_authority.insurance_share_bps,
+            insurance_vault_count: self.protocol_authority.insurance_vault_count,
+            insurance_vaults: self.protocol_authority.insurance_vaults,
+            // Venue program IDs only change through the timelocked update
+            external_programs: self.protocol_authority.external_programs,
+            pending_external_programs: self.protocol_authority.pending_external_programs,
+            pending_external_programs_eta: self.protocol_authority.pending_external_programs_eta,
+            reserved: [0; PROTOCOL_RESERVED_BYTES],
         });
+        self.protocol_authority.upgrade_external_programs();
         
         msg!("Protocol initialized with fee: {} bps", fee_bps);
         Ok(())
@@ -69,10 +97,17 @@ pub struct InitializeUser<'info> {
 impl<'info> InitializeUser<'info> {
     pub fn init_user(&mut self, bumps: &InitializeUserBumps) -> Result<()> {
         self.user_main_account.set_inner(UserMainAccount {
+            version: USER_ACCOUNT_VERSION,
             owner: self.owner.key(),
             position_count: 0,
             total_positions_created: 0,
             bump: bumps.user_main_account,
+            referrer: None,
+            first_deposit_at: 0,
+            cumulative_deposit_value: 0,
+            open_position_count: 0,
+            open_position_ids: [0; MAX_OPEN_POSITIONS],
+            reserved: [0; USER_RESERVED_BYTES],
         });
         
         msg!("User main account initialized for: {}", self.owner.key());
@@ -91,7 +126,7 @@ pub struct CreatePosition<'info> {
         seeds = [POSITION_SEED, owner.key().as_ref(), position_id.to_le_bytes().as_ref()],
         bump
     )]
-    pub position: Box<Account<'info, Position>>,
+    pub position: AccountLoader<'info, Position>,
     
     #[account(
         mut,
@@ -108,6 +143,13 @@ pub struct CreatePosition<'info> {
     )]
     pub protocol_authority: Box<Account<'info, ProtocolAuthority>>, 
     
+    #[account(
+        seeds = [PAIR_SEED, token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        bump = pair_config.bump,
+        constraint = pair_config.enabled @ ErrorCode::PairNotSupported
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
     pub token_a_mint: Account<'info, Mint>,
     pub token_b_mint: Account<'info, Mint>,
     
@@ -143,56 +185,16 @@ impl<'info> CreatePosition<'info> {
         lp_range_max: u64,
         bumps: &CreatePositionBumps
     ) -> Result<()> {
-        require!(
-            lp_range_min < lp_range_max,
-            ErrorCode::InvalidPriceRange
-        );
-        
-        require!(
-            position_id == self.user_main_account.total_positions_created + 1,
-            ErrorCode::InvalidPositionId
-        );
-        
-        self.position.set_inner(Position {
-            owner: self.owner.key(),
+        register_position(&mut self.user_main_account, &mut self.protocol_authority, position_id)?;
+        *self.position.load_init()? = new_position(
+            self.owner.key(),
             position_id,
-            token_a_mint: self.token_a_mint.key(),
-            token_b_mint: self.token_b_mint.key(),
-            
-            // Token balances in different locations
-            token_a_vault_balance: 0,
-            token_b_vault_balance: 0,
-            token_a_in_lp: 0,
-            token_b_in_lp: 0,
-            token_a_in_lending: 0,
-            token_b_in_lending: 0,
-            
-            // LP range configuration
+            self.token_a_mint.key(),
+            self.token_b_mint.key(),
             lp_range_min,
             lp_range_max,
-            
-            // Position state
-            pause_flag: false,
-            created_at: Clock::get()?.unix_timestamp,
-            
-            // Rebalancing tracking
-            last_rebalance_price: 0,
-            last_rebalance_slot: 0,
-            total_rebalances: 0,
-            
-            // External protocol position tracking (initially None)
-            meteora_position: None,
-            kamino_obligation: None,
-            
-            bump: bumps.position,
-        });
-        
-        // Update user main account
-        self.user_main_account.position_count += 1;
-        self.user_main_account.total_positions_created += 1;
-        
-        // Update protocol stats
-        self.protocol_authority.total_positions += 1;
+            bumps.position,
+        )?;
         
         msg!(
             "Position {} created with range: {}-{}", 
@@ -202,36 +204,146 @@ impl<'info> CreatePosition<'info> {
     }
 }
 
+// Fresh position state, shared by create_position and create_position_from_preset
+pub(crate) fn new_position(
+    owner: Pubkey,
+    position_id: u64,
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+    lp_range_min: u64,
+    lp_range_max: u64,
+    bump: u8,
+) -> Result<Position> {
+    require!(
+        lp_range_min < lp_range_max,
+        ErrorCode::InvalidPriceRange
+    );
+    
+    Ok(Position {
+        version: POSITION_VERSION,
+        bump,
+        
+        // Position state (unpaused, hedge off, out-of-range policy Hold)
+        pause_flag: 0,
+        flagged: 0,
+        hedge_enabled: 0,
+        out_of_range_policy: OutOfRangePolicy::Hold as u8,
+        out_of_range_converted: 0,
+        
+        // Laddered LP rungs (set_lp_ladder, or from a preset)
+        lp_rung_count: 0,
+        
+        owner,
+        position_id,
+        token_a_mint,
+        token_b_mint,
+        
+        // Token balances in different locations
+        token_a_vault_balance: 0,
+        token_b_vault_balance: 0,
+        token_a_in_lp: 0,
+        token_b_in_lp: 0,
+        token_a_in_lending: 0,
+        token_b_in_lending: 0,
+        
+        // LP range configuration
+        lp_range_min,
+        lp_range_max,
+        lp_rungs: [LpRung::default(); MAX_LP_RUNGS],
+        
+        created_at: Clock::get()?.unix_timestamp,
+        operator: Pubkey::default(),
+        
+        // Rebalancing tracking
+        last_rebalance_price: 0,
+        last_rebalance_slot: 0,
+        total_rebalances: 0,
+        
+        // External protocol position tracking (initially none)
+        meteora_position: Pubkey::default(),
+        kamino_obligation: Pubkey::default(),
+        
+        // Cost basis
+        net_deposit_a: 0,
+        net_deposit_b: 0,
+        hedge_collateral_a: 0,
+        hedge_borrowed_b: 0,
+        queued_withdraw_a: 0,
+        queued_withdraw_b: 0,
+        
+        // Protocol default rebalance thresholds unless set by a preset
+        rebalance_threshold_bps: 0,
+        padding: [0; 6],
+        min_rebalance_slots: 0,
+        preset: Pubkey::default(),
+        lp_fee_snapshot_x: 0,
+        lp_fee_snapshot_y: 0,
+        lp_fee_snapshot_at: 0,
+        rebalance_session: Pubkey::default(),
+        
+        reserved: [0; POSITION_RESERVED_BYTES],
+    })
+}
+
+pub(crate) fn register_position(
+    user_main_account: &mut UserMainAccount,
+    protocol_authority: &mut ProtocolAuthority,
+    position_id: u64,
+) -> Result<()> {
+    require!(
+        position_id == user_main_account.total_positions_created + 1,
+        ErrorCode::InvalidPositionId
+    );
+    
+    // Update user main account
+    user_main_account.index_position(position_id)?;
+    user_main_account.position_count += 1;
+    user_main_account.total_positions_created += 1;
+    
+    // Update protocol stats
+    protocol_authority.total_positions += 1;
+    Ok(())
+}
+
 // Deposit to Position
 #[derive(Accounts)]
 pub struct DepositToPosition<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
         has_one = owner,
         has_one = token_a_mint,
         has_one = token_b_mint,
     )]
-    pub position: Box<Account<'info, Position>>,
+    pub position: AccountLoader<'info, Position>,
     
     #[account(
+        mut,
         seeds = [PROTOCOL_SEED],
         bump = protocol_authority.bump
     )]
     pub protocol_authority: Box<Account<'info, ProtocolAuthority>>, 
     
+    #[account(
+        seeds = [PAIR_SEED, position.load()?.token_a_mint.as_ref(), position.load()?.token_b_mint.as_ref()],
+        bump = pair_config.bump,
+        constraint = pair_config.enabled @ ErrorCode::PairNotSupported
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
     #[account(
         mut,
         constraint = user_token_a.owner == owner.key(),
-        constraint = user_token_a.mint == position.token_a_mint
+        constraint = user_token_a.mint == position.load()?.token_a_mint
     )]
     pub user_token_a: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
         constraint = user_token_b.owner == owner.key(),
-        constraint = user_token_b.mint == position.token_b_mint
+        constraint = user_token_b.mint == position.load()?.token_b_mint
     )]
     pub user_token_b: Box<Account<'info, TokenAccount>>,
     
@@ -251,17 +363,63 @@ pub struct DepositToPosition<'info> {
     
     #[account(
         mut,
-        constraint = fee_token_a.owner == protocol_authority.fee_recipient,
-        constraint = fee_token_a.mint == position.token_a_mint
+        seeds = [FEE_VAULT_SEED, token_a_mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&token_a_mint.key())?.bump
     )]
-    pub fee_token_a: Box<Account<'info, TokenAccount>>,
+    pub fee_vault_a: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
-        constraint = fee_token_b.owner == protocol_authority.fee_recipient,
-        constraint = fee_token_b.mint == position.token_b_mint
+        seeds = [FEE_VAULT_SEED, token_b_mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&token_b_mint.key())?.bump
     )]
-    pub fee_token_b: Box<Account<'info, TokenAccount>>,
+    pub fee_vault_b: Box<Account<'info, TokenAccount>>,
+    
+    // Required while the protocol routes a share of fees to insurance
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, token_a_mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&token_a_mint.key())?.bump
+    )]
+    pub insurance_vault_a: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, token_b_mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&token_b_mint.key())?.bump
+    )]
+    pub insurance_vault_b: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [USER_SEED, owner.key().as_ref()],
+        bump = user_main_account.bump
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+    
+    // Optional oracle price; without it only token A counts toward fee tier volume
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    
+    // Optional referral accounts: the referrer, its tier and the referrer
+    // PDA's token accounts that accrue its share of the fee
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED, referrer.owner.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Option<Box<Account<'info, Referrer>>>,
+    
+    #[account(
+        seeds = [REFERRAL_TIER_SEED, &[referral_tier.tier]],
+        bump = referral_tier.bump
+    )]
+    pub referral_tier: Option<Box<Account<'info, ReferralTier>>>,
+    
+    #[account(mut, constraint = referrer_token_a.mint == position.load()?.token_a_mint)]
+    pub referrer_token_a: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(mut, constraint = referrer_token_b.mint == position.load()?.token_b_mint)]
+    pub referrer_token_b: Option<Box<Account<'info, TokenAccount>>>,
     
     pub owner: Signer<'info>,
     pub token_a_mint: Account<'info, Mint>,
@@ -271,22 +429,28 @@ pub struct DepositToPosition<'info> {
 
 impl<'info> DepositToPosition<'info> {
     pub fn deposit(&mut self, amount_a: u64, amount_b: u64) -> Result<()> {
-        // Calculate fees
-        let fee_a = (amount_a as u128)
-            .checked_mul(self.protocol_authority.protocol_fee_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10_000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
-            
-        let fee_b = (amount_b as u128)
-            .checked_mul(self.protocol_authority.protocol_fee_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10_000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        // Calculate fees at the user's volume tier
+        let applied_fee = self.protocol_authority.fee_for_volume(self.user_main_account.cumulative_deposit_value);
+        let fee_a = ProtocolAuthority::fee_at(amount_a, applied_fee.deposit_fee_bps)?;
+        let fee_b = ProtocolAuthority::fee_at(amount_b, applied_fee.deposit_fee_bps)?;
         
         let deposit_a = amount_a.checked_sub(fee_a).ok_or(ErrorCode::MathOverflow)?;
         let deposit_b = amount_b.checked_sub(fee_b).ok_or(ErrorCode::MathOverflow)?;
         
+        {
+            let position = self.position.load()?;
+            self.pair_config.check_position_size(
+                position.total_token_a().saturating_add(deposit_a),
+                position.total_token_b().saturating_add(deposit_b),
+            )?;
+        }
+        
+        let referral_tier = self.record_referral()?;
+        let (referral_fee_a, referral_fee_b) = match referral_tier.as_ref() {
+            Some(tier) => (tier.referral_fee(fee_a)?, tier.referral_fee(fee_b)?),
+            None => (0, 0),
+        };
+        
         // Transfer token A
         if amount_a > 0 {
             // Transfer deposit amount to vault
@@ -302,22 +466,59 @@ impl<'info> DepositToPosition<'info> {
                 deposit_a,
             )?;
             
-            // Transfer fee
-            if fee_a > 0 {
+            // Transfer fee, less the referrer's share and the insurance share
+            let (protocol_fee_a, insurance_fee_a) = self.protocol_authority.insurance_split(fee_a - referral_fee_a)?;
+            if protocol_fee_a > 0 {
+                anchor_spl::token::transfer(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        anchor_spl::token::Transfer {
+                            from: self.user_token_a.to_account_info(),
+                            to: self.fee_vault_a.to_account_info(),
+                            authority: self.owner.to_account_info(),
+                        },
+                    ),
+                    protocol_fee_a,
+                )?;
+                self.protocol_authority.record_fee(&self.token_a_mint.key(), protocol_fee_a)?;
+            }
+            
+            if insurance_fee_a > 0 {
+                let insurance_vault = self.insurance_vault_a.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
                 anchor_spl::token::transfer(
                     CpiContext::new(
                         self.token_program.to_account_info(),
                         anchor_spl::token::Transfer {
                             from: self.user_token_a.to_account_info(),
-                            to: self.fee_token_a.to_account_info(),
+                            to: insurance_vault.to_account_info(),
                             authority: self.owner.to_account_info(),
                         },
                     ),
-                    fee_a,
+                    insurance_fee_a,
                 )?;
+                self.protocol_authority.record_insurance(&self.token_a_mint.key(), insurance_fee_a)?;
             }
             
-            self.position.token_a_vault_balance = self.position.token_a_vault_balance
+            if referral_fee_a > 0 {
+                let referrer_token = self.referrer_token_a.as_ref().ok_or(ErrorCode::InvalidReferralAccounts)?;
+                anchor_spl::token::transfer(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        anchor_spl::token::Transfer {
+                            from: self.user_token_a.to_account_info(),
+                            to: referrer_token.to_account_info(),
+                            authority: self.owner.to_account_info(),
+                        },
+                    ),
+                    referral_fee_a,
+                )?;
+            }
+            
+            let mut position = self.position.load_mut()?;
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(deposit_a)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.net_deposit_a = position.net_deposit_a
                 .checked_add(deposit_a)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
@@ -337,41 +538,138 @@ impl<'info> DepositToPosition<'info> {
                 deposit_b,
             )?;
             
-            // Transfer fee
-            if fee_b > 0 {
+            // Transfer fee, less the referrer's share and the insurance share
+            let (protocol_fee_b, insurance_fee_b) = self.protocol_authority.insurance_split(fee_b - referral_fee_b)?;
+            if protocol_fee_b > 0 {
+                anchor_spl::token::transfer(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        anchor_spl::token::Transfer {
+                            from: self.user_token_b.to_account_info(),
+                            to: self.fee_vault_b.to_account_info(),
+                            authority: self.owner.to_account_info(),
+                        },
+                    ),
+                    protocol_fee_b,
+                )?;
+                self.protocol_authority.record_fee(&self.token_b_mint.key(), protocol_fee_b)?;
+            }
+            
+            if insurance_fee_b > 0 {
+                let insurance_vault = self.insurance_vault_b.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+                anchor_spl::token::transfer(
+                    CpiContext::new(
+                        self.token_program.to_account_info(),
+                        anchor_spl::token::Transfer {
+                            from: self.user_token_b.to_account_info(),
+                            to: insurance_vault.to_account_info(),
+                            authority: self.owner.to_account_info(),
+                        },
+                    ),
+                    insurance_fee_b,
+                )?;
+                self.protocol_authority.record_insurance(&self.token_b_mint.key(), insurance_fee_b)?;
+            }
+            
+            if referral_fee_b > 0 {
+                let referrer_token = self.referrer_token_b.as_ref().ok_or(ErrorCode::InvalidReferralAccounts)?;
                 anchor_spl::token::transfer(
                     CpiContext::new(
                         self.token_program.to_account_info(),
                         anchor_spl::token::Transfer {
                             from: self.user_token_b.to_account_info(),
-                            to: self.fee_token_b.to_account_info(),
+                            to: referrer_token.to_account_info(),
                             authority: self.owner.to_account_info(),
                         },
                     ),
-                    fee_b,
+                    referral_fee_b,
                 )?;
             }
             
-            self.position.token_b_vault_balance = self.position.token_b_vault_balance
+            let mut position = self.position.load_mut()?;
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(deposit_b)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.net_deposit_b = position.net_deposit_b
                 .checked_add(deposit_b)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         
+        // Volume credit towards fee tiers
+        let price = match self.price_update.as_ref() {
+            Some(price_update) => Some(get_pair_price(price_update, &self.pair_config, PRICE_MAX_AGE)?.0),
+            None => None,
+        };
+        let volume = self.pair_config.deposit_volume(amount_a, amount_b, price)?;
+        self.user_main_account.record_deposit_volume(volume);
+        
+        let position = self.position.load()?;
         emit!(DepositEvent {
-            position_id: self.position.position_id,
-            owner: self.position.owner,
+            position_id: position.position_id,
+            owner: position.owner,
             amount_a: deposit_a,
             amount_b: deposit_b,
             fee_a,
             fee_b,
+            fee_tier: applied_fee.tier,
+            fee_bps: applied_fee.deposit_fee_bps,
         });
         
+        if let (Some(referrer), Some(tier)) = (self.referrer.as_ref(), referral_tier) {
+            if referral_fee_a > 0 || referral_fee_b > 0 {
+                emit!(ReferralFeeEvent {
+                    referrer: referrer.key(),
+                    user: self.owner.key(),
+                    position_id: position.position_id,
+                    tier: tier.tier,
+                    amount_a: referral_fee_a,
+                    amount_b: referral_fee_b,
+                });
+            }
+        }
+        
         msg!(
             "Deposited {} token A and {} token B (fees: {}, {})",
             deposit_a, deposit_b, fee_a, fee_b
         );
         Ok(())
     }
+    
+    // Record the referrer on the user's first deposit and check later
+    // deposits against it. Returns the tier paying out on this deposit.
+    fn record_referral(&mut self) -> Result<Option<ReferralTier>> {
+        let now = Clock::get()?.unix_timestamp;
+        let user = &mut self.user_main_account;
+        
+        let first_deposit = user.first_deposit_at == 0;
+        if first_deposit {
+            user.first_deposit_at = now;
+        }
+        
+        let Some(referrer) = self.referrer.as_mut() else {
+            // A recorded referrer is owed its share of every later deposit
+            require!(user.referrer.is_none(), ErrorCode::ReferrerMismatch);
+            return Ok(None);
+        };
+        require_keys_neq!(referrer.owner, self.owner.key(), ErrorCode::SelfReferral);
+        
+        if first_deposit {
+            user.referrer = Some(referrer.key());
+            referrer.referred_users += 1;
+            msg!("Referrer {} recorded", referrer.owner);
+        }
+        require!(user.referrer == Some(referrer.key()), ErrorCode::ReferrerMismatch);
+        
+        let tier = self.referral_tier.as_ref().ok_or(ErrorCode::InvalidReferralAccounts)?;
+        require!(tier.tier == referrer.tier, ErrorCode::InvalidReferralAccounts);
+        
+        // Fee shares accrue in the referrer PDA's associated token accounts
+        for token_account in [&self.referrer_token_a, &self.referrer_token_b].into_iter().flatten() {
+            check_referrer_fee_account(&referrer.key(), token_account)?;
+        }
+        
+        Ok(Some((***tier).clone()))
+    }
 }
 
 // Modify Position (Pause/Resume)
@@ -379,25 +677,191 @@ impl<'info> DepositToPosition<'info> {
 pub struct ModifyPosition<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump,
-        has_one = owner
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        constraint = position.load()?.is_authorized(&authority.key()) @ ErrorCode::UnauthorizedOperator
     )]
-    pub position: Box<Account<'info, Position>>, 
+    pub position: AccountLoader<'info, Position>, 
     
-    pub owner: Signer<'info>,
+    // Position owner or operator
+    pub authority: Signer<'info>,
 }
 
 impl<'info> ModifyPosition<'info> {
     pub fn pause(&mut self) -> Result<()> {
-        self.position.pause_flag = true;
-        msg!("Position {} paused", self.position.position_id);
+        let mut position = self.position.load_mut()?;
+        position.set_paused(true);
+        msg!("Position {} paused", position.position_id);
         Ok(())
     }
     
     pub fn resume(&mut self) -> Result<()> {
-        self.position.pause_flag = false;
-        msg!("Position {} resumed", self.position.position_id);
+        let mut position = self.position.load_mut()?;
+        position.set_paused(false);
+        msg!("Position {} resumed", position.position_id);
         Ok(())
     }
-}
\ No newline at end of file
+    
+    pub fn update_range(&mut self, lp_range_min: u64, lp_range_max: u64) -> Result<()> {
+        require!(
+            lp_range_min < lp_range_max,
+            ErrorCode::InvalidPriceRange
+        );
+        
+        // Laddered positions derive their envelope from the rungs
+        let mut position = self.position.load_mut()?;
+        require!(!position.has_ladder(), ErrorCode::RangeSetByLadder);
+        
+        position.lp_range_min = lp_range_min;
+        position.lp_range_max = lp_range_max;
+        
+        msg!(
+            "Position {} range updated: {} - {}",
+            position.position_id, lp_range_min, lp_range_max
+        );
+        Ok(())
+    }
+    
+    pub fn set_out_of_range_policy(&mut self, policy: OutOfRangePolicy) -> Result<()> {
+        // Funds already converted are rebalanced back on re-entry whatever the new policy
+        let mut position = self.position.load_mut()?;
+        position.set_out_of_range_policy(policy);
+        
+        msg!("Position {} out-of-range policy: {:?}", position.position_id, policy);
+        Ok(())
+    }
+}
+
+// Appoint or remove the position operator
+#[derive(Accounts)]
+pub struct SetPositionOperator<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        has_one = owner
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    pub owner: Signer<'info>,
+}
+
+impl<'info> SetPositionOperator<'info> {
+    pub fn set_operator(&mut self, operator: Option<Pubkey>) -> Result<()> {
+        let mut position = self.position.load_mut()?;
+        position.set_operator(operator);
+        
+        match operator {
+            Some(operator) => msg!("Position {} operator set to {}", position.position_id, operator),
+            None => msg!("Position {} operator removed", position.position_id),
+        }
+        Ok(())
+    }
+}
+
+// Turn the delta-neutral hedge leg on or off (owner only, it adds leverage)
+#[derive(Accounts)]
+pub struct SetHedgeMode<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        has_one = owner
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    pub owner: Signer<'info>,
+}
+
+impl<'info> SetHedgeMode<'info> {
+    pub fn set_hedge(&mut self, enabled: bool) -> Result<()> {
+        // Disabling leaves any open borrow to be unwound by the next rebalance
+        let mut position = self.position.load_mut()?;
+        position.set_hedge_enabled(enabled);
+        
+        msg!(
+            "Position {} hedge {}",
+            position.position_id,
+            if enabled { "enabled" } else { "disabled" }
+        );
+        Ok(())
+    }
+}
+// Configure laddered LP rungs
+#[derive(Accounts)]
+pub struct SetLpLadder<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        has_one = owner
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    pub owner: Signer<'info>,
+}
+
+impl<'info> SetLpLadder<'info> {
+    pub fn set_ladder(&mut self, rungs: Vec<LpRungConfig>) -> Result<()> {
+        require!(
+            rungs.len() <= MAX_LP_RUNGS,
+            ErrorCode::InvalidLadderConfig
+        );
+        
+        // Rungs can only be swapped out while nothing is deployed in Meteora
+        let mut position = self.position.load_mut()?;
+        require!(
+            position.token_a_in_lp == 0 && position.token_b_in_lp == 0,
+            ErrorCode::LiquidityInLP
+        );
+        
+        let mut total_weight: u32 = 0;
+        for rung in rungs.iter() {
+            require!(
+                rung.range_min < rung.range_max && rung.weight_bps > 0,
+                ErrorCode::InvalidLadderConfig
+            );
+            total_weight += rung.weight_bps as u32;
+        }
+        
+        require!(
+            rungs.is_empty() || total_weight == 10_000,
+            ErrorCode::InvalidLadderConfig
+        );
+        
+        let mut lp_rungs = [LpRung::default(); MAX_LP_RUNGS];
+        for (slot, rung) in lp_rungs.iter_mut().zip(rungs.iter()) {
+            *slot = LpRung {
+                range_min: rung.range_min,
+                range_max: rung.range_max,
+                weight_bps: rung.weight_bps,
+                ..LpRung::default()
+            };
+        }
+        
+        // The outer range becomes the envelope of all rungs so in/out of range
+        // decisions keep working on lp_range_min/lp_range_max
+        if let (Some(range_min), Some(range_max)) = (
+            rungs.iter().map(|r| r.range_min).min(),
+            rungs.iter().map(|r| r.range_max).max(),
+        ) {
+            position.lp_range_min = range_min;
+            position.lp_range_max = range_max;
+        }
+        
+        position.lp_rungs = lp_rungs;
+        position.lp_rung_count = rungs.len() as u8;
+        
+        msg!(
+            "Position {} ladder set with {} rungs, range: {}-{}",
+            position.position_id,
+            rungs.len(),
+            position.lp_range_min,
+            position.lp_range_max
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+// instructions/insurance.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::InsuranceCompensationEvent;
+use crate::constants::*;
+
+// Create the insurance vault for a mint (protocol admin)
+#[derive(Accounts)]
+pub struct CreateInsuranceVault<'info> {
+    #[account(
+        init,
+        payer = admin,
+        seeds = [INSURANCE_VAULT_SEED, mint.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = protocol_authority
+    )]
+    pub insurance_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreateInsuranceVault<'info> {
+    pub fn create_vault(&mut self, bumps: &CreateInsuranceVaultBumps) -> Result<()> {
+        self.protocol_authority.register_insurance_vault(self.mint.key(), bumps.insurance_vault)?;
+        
+        msg!("Insurance vault {} created for mint {}", self.insurance_vault.key(), self.mint.key());
+        Ok(())
+    }
+}
+
+// Set the share of each deposit and withdraw fee that goes to insurance (protocol admin)
+#[derive(Accounts)]
+pub struct SetInsuranceShare<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetInsuranceShare<'info> {
+    /// With a non-zero share, deposits and withdrawals must pass the insurance
+    /// vaults of both pair mints
+    pub fn set_share(&mut self, share_bps: u16) -> Result<()> {
+        require!(share_bps <= MAX_INSURANCE_SHARE_BPS, ErrorCode::InvalidInsuranceShare);
+        
+        self.protocol_authority.insurance_share_bps = share_bps;
+        
+        msg!("Insurance share set to {} bps of protocol fees", share_bps);
+        Ok(())
+    }
+}
+
+// Pay a position back out of the insurance vault (protocol admin), e.g.
+// after a venue loss or a bad rebalance. The tokens land in the position
+// vault and count towards its tracked balance.
+#[derive(Accounts)]
+pub struct CompensatePosition<'info> {
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&mint.key())?.bump
+    )]
+    pub insurance_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = position
+    )]
+    pub position_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub mint: Account<'info, Mint>,
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CompensatePosition<'info> {
+    /// `reason` is a free-form reference (incident id, audit note) recorded in
+    /// the event for later review
+    pub fn compensate(&mut self, amount: u64, reason: [u8; 32]) -> Result<()> {
+        let mint = self.mint.key();
+        let is_token_a = {
+            let position = self.position.load()?;
+            require!(
+                mint == position.token_a_mint || mint == position.token_b_mint,
+                ErrorCode::InvalidCompensation
+            );
+            mint == position.token_a_mint
+        };
+        require!(amount > 0, ErrorCode::InvalidCompensation);
+        require!(amount <= self.insurance_vault.amount, ErrorCode::InsufficientInsuranceFunds);
+        
+        let signer_seeds: &[&[&[u8]]] = &[&[PROTOCOL_SEED, &[self.protocol_authority.bump]]];
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.insurance_vault.to_account_info(),
+                    to: self.position_vault.to_account_info(),
+                    authority: self.protocol_authority.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+        
+        let mut position = self.position.load_mut()?;
+        if is_token_a {
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(amount)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        let vault = self.protocol_authority.insurance_vault_mut(&mint)?;
+        vault.compensated = vault.compensated.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        let total_compensated = vault.compensated;
+        
+        emit!(InsuranceCompensationEvent {
+            position: self.position.key(),
+            position_id: position.position_id,
+            owner: position.owner,
+            mint,
+            amount,
+            reason,
+            admin: self.admin.key(),
+            insurance_remaining: self.insurance_vault.amount - amount,
+            total_compensated,
+        });
+        
+        msg!("Compensated position {} with {} from insurance", position.position_id, amount);
+        Ok(())
+    }
+}
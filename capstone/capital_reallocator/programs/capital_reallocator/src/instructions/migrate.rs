@@ -0,0 +1,226 @@
+// instructions/migrate.rs
+use anchor_lang::prelude::*;
+use anchor_lang::system_program::{self, Transfer};
+use crate::state::*;
+use crate::state::legacy::{PositionLayout, PositionV8, Versioned};
+use crate::errors::ErrorCode;
+use crate::constants::*;
+use crate::program::CapitalReallocator;
+
+// Rewrite a Borsh-encoded position (any layout before v9) in the zero-copy
+// layout, seeding the fields added since its version
+#[derive(Accounts)]
+pub struct MigratePosition<'info> {
+    /// CHECK: Decoded by version in the handler, PDA re-derived from its contents
+    #[account(mut, owner = crate::ID)]
+    pub position: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigratePosition<'info> {
+    pub fn migrate(&mut self) -> Result<()> {
+        let position = match Position::decode_versioned(&self.position.try_borrow_data()?)? {
+            PositionLayout::Borsh(borsh) => match *borsh {
+                Versioned::Current(v8) => Position::from(v8),
+                Versioned::V0(v0) => Position::from(PositionV8::from(v0)),
+            },
+            PositionLayout::ZeroCopy(position) => {
+                msg!("Position {} is already at version {}", position.position_id, position.version);
+                return Ok(());
+            }
+        };
+        
+        let position_id_bytes = position.position_id.to_le_bytes();
+        verify_pda(
+            &self.position,
+            &[POSITION_SEED, position.owner.as_ref(), position_id_bytes.as_ref(), &[position.bump]],
+        )?;
+        
+        resize_for_migration(&self.position, &self.payer, &self.system_program, 8 + Position::INIT_SPACE)?;
+        
+        let mut data = self.position.try_borrow_mut_data()?;
+        data[..8].copy_from_slice(POSITION_DISCRIMINATOR);
+        data[8..].copy_from_slice(bytemuck::bytes_of(&position));
+        
+        msg!("Position {} migrated to version {}", position.position_id, POSITION_VERSION);
+        Ok(())
+    }
+}
+
+// Migrate a user account created before account versioning. Accounts from
+// before v4 also pass all of the owner's open positions as remaining
+// accounts to seed the open position index.
+#[derive(Accounts)]
+pub struct MigrateUserAccount<'info> {
+    /// CHECK: Decoded by version in the handler, PDA re-derived from its contents
+    #[account(mut, owner = crate::ID)]
+    pub user_main_account: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateUserAccount<'info> {
+    pub fn migrate(&mut self, positions: &[AccountInfo<'info>]) -> Result<()> {
+        let (mut user_account, from_version) = match UserMainAccount::decode_versioned(&self.user_main_account.try_borrow_data()?)? {
+            Versioned::Current(user_account) if user_account.version < USER_ACCOUNT_VERSION => {
+                let version = user_account.version;
+                (user_account, version)
+            }
+            Versioned::Current(user_account) => {
+                msg!("User account is already at version {}", user_account.version);
+                return Ok(());
+            }
+            Versioned::V0(v0) => (UserMainAccount::from(v0), 0),
+        };
+        user_account.upgrade_from(from_version, Clock::get()?.unix_timestamp);
+        if from_version < 4 {
+            index_positions(&mut user_account, positions)?;
+        }
+        
+        verify_pda(
+            &self.user_main_account,
+            &[USER_SEED, user_account.owner.as_ref(), &[user_account.bump]],
+        )?;
+        
+        write_migrated(
+            &self.user_main_account,
+            &self.payer,
+            &self.system_program,
+            &user_account,
+            8 + UserMainAccount::INIT_SPACE,
+        )?;
+        
+        msg!("User account {} migrated to version {}", user_account.owner, USER_ACCOUNT_VERSION);
+        Ok(())
+    }
+}
+
+// Migrate the protocol account; the upgrade authority becomes protocol admin
+#[derive(Accounts)]
+pub struct MigrateProtocol<'info> {
+    /// CHECK: Decoded by version in the handler
+    #[account(
+        mut,
+        owner = crate::ID,
+        seeds = [PROTOCOL_SEED],
+        bump
+    )]
+    pub protocol_authority: UncheckedAccount<'info>,
+    
+    #[account(constraint = program.programdata_address()? == Some(program_data.key()))]
+    pub program: Program<'info, CapitalReallocator>,
+    
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ ErrorCode::UnauthorizedAdmin)]
+    pub program_data: Account<'info, ProgramData>,
+    
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> MigrateProtocol<'info> {
+    pub fn migrate(&mut self) -> Result<()> {
+        let protocol = match ProtocolAuthority::decode_versioned(&self.protocol_authority.try_borrow_data()?)? {
+            Versioned::Current(mut protocol) if protocol.version < PROTOCOL_VERSION => {
+                protocol.version = PROTOCOL_VERSION;
+                protocol.upgrade_external_programs();
+                protocol
+            }
+            Versioned::Current(protocol) => {
+                msg!("Protocol is already at version {}", protocol.version);
+                return Ok(());
+            }
+            Versioned::V0(v0) => v0.upgrade(self.authority.key()),
+        };
+        
+        write_migrated(
+            &self.protocol_authority,
+            &self.authority,
+            &self.system_program,
+            &protocol,
+            8 + ProtocolAuthority::INIT_SPACE,
+        )?;
+        
+        msg!("Protocol migrated to version {} with admin {}", PROTOCOL_VERSION, protocol.admin);
+        Ok(())
+    }
+}
+
+// Positions may themselves still be on an older layout, so they are decoded by version
+fn index_positions(user_account: &mut UserMainAccount, positions: &[AccountInfo]) -> Result<()> {
+    for info in positions {
+        require_keys_eq!(*info.owner, crate::ID, ErrorCode::InvalidAccountData);
+        let (owner, position_id) = match Position::decode_versioned(&info.try_borrow_data()?)? {
+            PositionLayout::ZeroCopy(position) => (position.owner, position.position_id),
+            PositionLayout::Borsh(borsh) => match *borsh {
+                Versioned::Current(v8) => (v8.owner, v8.position_id),
+                Versioned::V0(v0) => (v0.owner, v0.position_id),
+            },
+        };
+        require_keys_eq!(owner, user_account.owner, ErrorCode::PositionNotIndexed);
+        user_account.index_position(position_id)?;
+    }
+    
+    require!(
+        user_account.open_position_count as u64 == user_account.position_count,
+        ErrorCode::InvalidPortfolioAccounts
+    );
+    Ok(())
+}
+
+fn verify_pda(account: &AccountInfo, seeds: &[&[u8]]) -> Result<()> {
+    let expected = Pubkey::create_program_address(seeds, &crate::ID)
+        .map_err(|_| ErrorCode::InvalidAccountData)?;
+    require_keys_eq!(account.key(), expected, ErrorCode::InvalidAccountData);
+    Ok(())
+}
+
+// Top up rent, grow the account and rewrite it in the current layout
+fn write_migrated<'info, T: AccountSerialize>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    value: &T,
+    space: usize,
+) -> Result<()> {
+    resize_for_migration(account, payer, system_program, space)?;
+    
+    let mut data = account.try_borrow_mut_data()?;
+    value.try_serialize(&mut &mut data[..])?;
+    Ok(())
+}
+
+// Top up rent and resize the account for its current layout
+fn resize_for_migration<'info>(
+    account: &AccountInfo<'info>,
+    payer: &Signer<'info>,
+    system_program: &Program<'info, System>,
+    space: usize,
+) -> Result<()> {
+    let required_lamports = Rent::get()?.minimum_balance(space);
+    let current_lamports = account.lamports();
+    
+    if required_lamports > current_lamports {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.to_account_info(),
+                Transfer {
+                    from: payer.to_account_info(),
+                    to: account.clone(),
+                },
+            ),
+            required_lamports - current_lamports,
+        )?;
+    }
+    
+    account.realloc(space, true)?;
+    Ok(())
+}
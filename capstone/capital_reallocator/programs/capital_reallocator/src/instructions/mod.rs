@@ -1,7 +1,35 @@
 pub mod initialize;
 pub mod withdraw;
 pub mod rebalance;
+pub mod compound;
+pub mod harvest;
+pub mod schedule;
+pub mod audit;
+pub mod migrate;
+pub mod pair;
+pub mod valuation;
+pub mod referral;
+pub mod fees;
+pub mod insurance;
+pub mod programs;
+pub mod portfolio;
+pub mod preset;
+pub mod session;
 
 pub use initialize::*;
 pub use withdraw::*;
 pub use rebalance::*;
+pub use compound::*;
+pub use harvest::*;
+pub use schedule::*;
+pub use audit::*;
+pub use migrate::*;
+pub use pair::*;
+pub use valuation::*;
+pub use referral::*;
+pub use fees::*;
+pub use insurance::*;
+pub use programs::*;
+pub use portfolio::*;
+pub use preset::*;
+pub use session::*;
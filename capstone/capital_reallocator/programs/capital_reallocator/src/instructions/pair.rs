@@ -0,0 +1,274 @@
+// instructions/pair.rs
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::sysvar;
+use anchor_spl::token::{self, Mint};
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::constants::*;
+use crate::protocols::meteora::{read_lb_pair, meteora_event_authority};
+use crate::protocols::kamino::{kamino_market_authority, kamino_reserve_pdas};
+use crate::protocols::lookup_table::{
+    create_lookup_table_signed, extend_lookup_table_signed, read_lookup_table_addresses,
+    ADDRESS_LOOKUP_TABLE_PROGRAM_ID,
+};
+
+// Register or update a supported pair (protocol admin)
+#[derive(Accounts)]
+pub struct SetPairConfig<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + PairConfig::INIT_SPACE,
+        seeds = [PAIR_SEED, token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetPairConfig<'info> {
+    pub fn set_pair(&mut self, params: PairConfigParams, bumps: &SetPairConfigBumps) -> Result<()> {
+        require_keys_neq!(self.token_a_mint.key(), self.token_b_mint.key(), ErrorCode::PairNotSupported);
+        require!(
+            (params.max_position_a == 0 || params.min_position_a <= params.max_position_a) &&
+            (params.max_position_b == 0 || params.min_position_b <= params.max_position_b),
+            ErrorCode::PositionSizeOutOfBounds
+        );
+        
+        // Decimals come from the mints, never from the caller
+        self.pair_config.set_inner(PairConfig {
+            version: PAIR_CONFIG_VERSION,
+            token_a_mint: self.token_a_mint.key(),
+            token_b_mint: self.token_b_mint.key(),
+            token_a_decimals: self.token_a_mint.decimals,
+            token_b_decimals: self.token_b_mint.decimals,
+            price_feed_id: params.price_feed_id,
+            meteora_lb_pair: params.meteora_lb_pair,
+            kamino_lending_market: params.kamino_lending_market,
+            kamino_reserve_a: params.kamino_reserve_a,
+            kamino_reserve_b: params.kamino_reserve_b,
+            min_position_a: params.min_position_a,
+            min_position_b: params.min_position_b,
+            max_position_a: params.max_position_a,
+            max_position_b: params.max_position_b,
+            enabled: params.enabled,
+            bump: bumps.pair_config,
+            yield_check_enabled: params.yield_check_enabled,
+            yield_margin_bps: params.yield_margin_bps,
+            // Survives venue updates; extend_pair_lookup_table adds new venues
+            lookup_table: self.pair_config.lookup_table,
+            reserved: [0; PAIR_RESERVED_BYTES],
+        });
+        
+        msg!(
+            "Pair {}/{} configured (enabled: {})",
+            self.token_a_mint.key(), self.token_b_mint.key(), params.enabled
+        );
+        Ok(())
+    }
+}
+
+// Create the pair's address lookup table with the pair config PDA as its
+// authority (protocol admin)
+#[derive(Accounts)]
+pub struct CreatePairLookupTable<'info> {
+    #[account(
+        mut,
+        seeds = [PAIR_SEED, pair_config.token_a_mint.as_ref(), pair_config.token_b_mint.as_ref()],
+        bump = pair_config.bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    /// CHECK: Derived from the pair config and recent slot, created by the lookup table program
+    #[account(mut)]
+    pub lookup_table: UncheckedAccount<'info>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    /// CHECK: Address lookup table program
+    #[account(address = ADDRESS_LOOKUP_TABLE_PROGRAM_ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> CreatePairLookupTable<'info> {
+    pub fn create_table(&mut self, recent_slot: u64) -> Result<()> {
+        require!(self.pair_config.lookup_table.is_none(), ErrorCode::LookupTableExists);
+        
+        let pair = &self.pair_config;
+        let seeds = &[PAIR_SEED, pair.token_a_mint.as_ref(), pair.token_b_mint.as_ref(), &[pair.bump]];
+        
+        create_lookup_table_signed(
+            &self.lookup_table.to_account_info(),
+            &self.pair_config.to_account_info(),
+            &self.admin.to_account_info(),
+            &self.system_program.to_account_info(),
+            &self.address_lookup_table_program.to_account_info(),
+            recent_slot,
+            &[seeds],
+        )?;
+        
+        self.pair_config.lookup_table = Some(self.lookup_table.key());
+        
+        msg!("Lookup table {} created for pair {}", self.lookup_table.key(), self.pair_config.key());
+        Ok(())
+    }
+}
+
+// Add the pair's venue accounts, plus any extra addresses such as busy bin
+// arrays, to its lookup table. Addresses already in the table are skipped,
+// so it's safe to rerun after the pair's venues change (protocol admin).
+#[derive(Accounts)]
+pub struct ExtendPairLookupTable<'info> {
+    #[account(
+        seeds = [PAIR_SEED, pair_config.token_a_mint.as_ref(), pair_config.token_b_mint.as_ref()],
+        bump = pair_config.bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    /// CHECK: The pair's lookup table
+    #[account(
+        mut,
+        constraint = pair_config.lookup_table == Some(lookup_table.key()) @ ErrorCode::InvalidLookupTable
+    )]
+    pub lookup_table: UncheckedAccount<'info>,
+    
+    /// CHECK: The pair's Meteora LB pair, read for its reserve addresses
+    #[account(address = pair_config.meteora_lb_pair @ ErrorCode::VenueMismatch)]
+    pub meteora_lb_pair: Option<UncheckedAccount<'info>>,
+    
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    
+    /// CHECK: Address lookup table program
+    #[account(address = ADDRESS_LOOKUP_TABLE_PROGRAM_ID)]
+    pub address_lookup_table_program: UncheckedAccount<'info>,
+    
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ExtendPairLookupTable<'info> {
+    pub fn extend_table(&mut self, extra_addresses: Vec<Pubkey>) -> Result<()> {
+        let existing = read_lookup_table_addresses(&self.lookup_table, &self.pair_config.key())?;
+        
+        let mut new_addresses: Vec<Pubkey> = Vec::new();
+        for address in self.venue_addresses()?.into_iter().chain(extra_addresses) {
+            if address != Pubkey::default() && !existing.contains(&address) && !new_addresses.contains(&address) {
+                new_addresses.push(address);
+            }
+        }
+        
+        if new_addresses.is_empty() {
+            msg!("Lookup table {} is up to date", self.lookup_table.key());
+            return Ok(());
+        }
+        require!(
+            existing.len() + new_addresses.len() <= LOOKUP_TABLE_MAX_ADDRESSES,
+            ErrorCode::LookupTableFull
+        );
+        
+        let added = new_addresses.len();
+        let pair = &self.pair_config;
+        let seeds = &[PAIR_SEED, pair.token_a_mint.as_ref(), pair.token_b_mint.as_ref(), &[pair.bump]];
+        
+        extend_lookup_table_signed(
+            &self.lookup_table.to_account_info(),
+            &self.pair_config.to_account_info(),
+            &self.admin.to_account_info(),
+            &self.system_program.to_account_info(),
+            &self.address_lookup_table_program.to_account_info(),
+            new_addresses,
+            &[seeds],
+        )?;
+        
+        msg!("Added {} addresses to lookup table {}", added, self.lookup_table.key());
+        Ok(())
+    }
+    
+    // Static accounts of position and rebalance transactions for this pair.
+    // Position-specific accounts (vaults, obligation, LP positions) are not
+    // included; they go in the transaction itself.
+    fn venue_addresses(&self) -> Result<Vec<Pubkey>> {
+        let pair = &self.pair_config;
+        let programs = self.protocol_authority.external_programs;
+        
+        let mut addresses = vec![
+            crate::ID, // also stands in for omitted optional accounts
+            self.pair_config.key(),
+            self.protocol_authority.key(),
+            pair.token_a_mint,
+            pair.token_b_mint,
+            token::ID,
+            System::id(),
+            sysvar::rent::ID,
+            sysvar::clock::ID,
+            sysvar::instructions::ID,
+            programs.jupiter,
+        ];
+        
+        if pair.meteora_lb_pair != Pubkey::default() {
+            addresses.extend([
+                programs.meteora_dlmm,
+                meteora_event_authority(&programs.meteora_dlmm),
+                pair.meteora_lb_pair,
+            ]);
+            if let Some(lb_pair) = self.meteora_lb_pair.as_ref() {
+                let lb_pair = read_lb_pair(lb_pair, &programs.meteora_dlmm)?;
+                addresses.extend([lb_pair.reserve_x, lb_pair.reserve_y]);
+            }
+        }
+        
+        if pair.kamino_lending_market != Pubkey::default() {
+            addresses.extend([
+                programs.kamino_lending,
+                pair.kamino_lending_market,
+                kamino_market_authority(&pair.kamino_lending_market, &programs.kamino_lending),
+            ]);
+            for reserve in [pair.kamino_reserve_a, pair.kamino_reserve_b] {
+                if reserve == Pubkey::default() {
+                    continue;
+                }
+                let pdas = kamino_reserve_pdas(&reserve, &programs.kamino_lending);
+                addresses.extend([
+                    reserve,
+                    pdas.liquidity_supply,
+                    pdas.collateral_mint,
+                    pdas.collateral_supply,
+                    pdas.fee_receiver,
+                ]);
+            }
+        }
+        
+        Ok(addresses)
+    }
+}
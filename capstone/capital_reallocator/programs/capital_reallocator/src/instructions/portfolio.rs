@@ -0,0 +1,276 @@
+// instructions/portfolio.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Token, TokenAccount};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::*;
+use crate::constants::*;
+use crate::instructions::rebalance::get_pair_price;
+use crate::instructions::valuation::position_value;
+use crate::instructions::withdraw::{pay_from_vault, transfer_from_vault};
+
+// Pause or resume several of the owner's positions at once. Positions are
+// passed as writable remaining accounts.
+#[derive(Accounts)]
+pub struct ModifyPortfolio<'info> {
+    #[account(
+        seeds = [USER_SEED, owner.key().as_ref()],
+        bump = user_main_account.bump,
+        has_one = owner
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+
+    pub owner: Signer<'info>,
+}
+
+impl<'info> ModifyPortfolio<'info> {
+    pub fn set_paused(&self, paused: bool, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let mut seen = Vec::new();
+
+        for info in portfolio_groups(accounts, 1)?.flatten() {
+            let position = load_position(&self.user_main_account, info, &mut seen, true)?;
+            position.load_mut()?.set_paused(paused);
+        }
+
+        msg!(
+            "{} {} positions",
+            if paused { "Paused" } else { "Resumed" },
+            seen.len()
+        );
+        Ok(())
+    }
+}
+
+// Withdraw the same percentage from several of the owner's positions. Each
+// position takes PORTFOLIO_WITHDRAW_ACCOUNTS remaining accounts:
+// [position, vault A, vault B, user token A, user token B, fee vault A, fee vault B,
+//  insurance vault A, insurance vault B]. The insurance vaults are only read
+// while the protocol routes a share of fees to insurance.
+#[derive(Accounts)]
+pub struct WithdrawFromPortfolio<'info> {
+    #[account(
+        seeds = [USER_SEED, owner.key().as_ref()],
+        bump = user_main_account.bump,
+        has_one = owner
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> WithdrawFromPortfolio<'info> {
+    /// Only pays out of the position vaults. A position whose withdrawal would
+    /// need queuing fails the whole batch; withdraw it on its own instead.
+    pub fn withdraw(&mut self, withdraw_percentage: u8, accounts: &'info [AccountInfo<'info>]) -> Result<()> {
+        let applied_fee = self.protocol_authority.fee_for_volume(self.user_main_account.cumulative_deposit_value);
+        let fee_bps = applied_fee.withdraw_fee_bps;
+        let mut seen = Vec::new();
+
+        for group in portfolio_groups(accounts, PORTFOLIO_WITHDRAW_ACCOUNTS)? {
+            let position = load_position(&self.user_main_account, &group[0], &mut seen, true)?;
+            let (position_id, owner, token_a_mint, token_b_mint) = {
+                let position = position.load()?;
+                (position.position_id, position.owner, position.token_a_mint, position.token_b_mint)
+            };
+            let vault_a = Account::<TokenAccount>::try_from(&group[1])?;
+            let vault_b = Account::<TokenAccount>::try_from(&group[2])?;
+            let user_token_a = Account::<TokenAccount>::try_from(&group[3])?;
+            let user_token_b = Account::<TokenAccount>::try_from(&group[4])?;
+            let fee_vault_a = Account::<TokenAccount>::try_from(&group[5])?;
+            let fee_vault_b = Account::<TokenAccount>::try_from(&group[6])?;
+            require!(
+                fee_vault_a.key() == self.protocol_authority.fee_vault_address(&token_a_mint)? &&
+                fee_vault_b.key() == self.protocol_authority.fee_vault_address(&token_b_mint)?,
+                ErrorCode::InvalidPortfolioAccounts
+            );
+
+            for (account, owner, mint) in [
+                (&vault_a, position.key(), token_a_mint),
+                (&vault_b, position.key(), token_b_mint),
+                (&user_token_a, self.owner.key(), token_a_mint),
+                (&user_token_b, self.owner.key(), token_b_mint),
+            ] {
+                require!(
+                    account.owner == owner && account.mint == mint,
+                    ErrorCode::InvalidPortfolioAccounts
+                );
+            }
+
+            let split = position.load_mut()?.split_withdrawal(withdraw_percentage, vault_a.amount, vault_b.amount)?;
+            require!(
+                split.queued_a == 0 && split.queued_b == 0,
+                ErrorCode::PortfolioWithdrawShortfall
+            );
+
+            let fee_a = ProtocolAuthority::fee_at(split.paid_a, fee_bps)?;
+            let fee_b = ProtocolAuthority::fee_at(split.paid_b, fee_bps)?;
+            let net_a = split.paid_a - fee_a;
+            let net_b = split.paid_b - fee_b;
+            let (protocol_fee_a, insurance_fee_a) = self.protocol_authority.insurance_split(fee_a)?;
+            let (protocol_fee_b, insurance_fee_b) = self.protocol_authority.insurance_split(fee_b)?;
+
+            pay_from_vault(&position, &vault_a, &user_token_a, &fee_vault_a, &self.token_program, net_a, protocol_fee_a)?;
+            pay_from_vault(&position, &vault_b, &user_token_b, &fee_vault_b, &self.token_program, net_b, protocol_fee_b)?;
+            self.protocol_authority.record_fee(&token_a_mint, protocol_fee_a)?;
+            self.protocol_authority.record_fee(&token_b_mint, protocol_fee_b)?;
+
+            for (vault, insurance_info, mint, insurance_fee) in [
+                (&vault_a, &group[7], token_a_mint, insurance_fee_a),
+                (&vault_b, &group[8], token_b_mint, insurance_fee_b),
+            ] {
+                if insurance_fee == 0 {
+                    continue;
+                }
+                require_keys_eq!(
+                    insurance_info.key(),
+                    self.protocol_authority.insurance_vault_address(&mint)?,
+                    ErrorCode::InvalidPortfolioAccounts
+                );
+                let insurance_vault = Account::<TokenAccount>::try_from(insurance_info)?;
+                transfer_from_vault(&position, vault, &insurance_vault, &self.token_program, insurance_fee)?;
+                self.protocol_authority.record_insurance(&mint, insurance_fee)?;
+            }
+
+            emit!(WithdrawEvent {
+                position_id,
+                owner,
+                amount_a: net_a,
+                amount_b: net_b,
+                fee_a,
+                fee_b,
+                percentage: withdraw_percentage,
+                fee_tier: applied_fee.tier,
+                fee_bps,
+                queued_a: 0,
+                queued_b: 0,
+            });
+        }
+
+        msg!("Withdrew {}% from {} positions", withdraw_percentage, seen.len());
+        Ok(())
+    }
+}
+
+// Aggregated valuation of several of a user's positions (read-only, meant to
+// be simulated). Each position takes PORTFOLIO_VALUE_ACCOUNTS remaining
+// accounts: [position, pair config, price update]. Lending uses the tracked
+// amounts; get_position_value reads the obligation for a single position.
+#[derive(Accounts)]
+pub struct GetPortfolioValue<'info> {
+    #[account(
+        seeds = [USER_SEED, user_main_account.owner.as_ref()],
+        bump = user_main_account.bump,
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+}
+
+impl<'info> GetPortfolioValue<'info> {
+    pub fn value(&self, accounts: &'info [AccountInfo<'info>]) -> Result<PortfolioValue> {
+        let mut seen = Vec::new();
+        let mut summaries = Vec::new();
+        let mut token_a_mint = None;
+        let mut total_value: u64 = 0;
+        let mut net_deposit_value: u64 = 0;
+
+        for group in portfolio_groups(accounts, PORTFOLIO_VALUE_ACCOUNTS)? {
+            let position = load_position(&self.user_main_account, &group[0], &mut seen, false)?;
+            let position = position.load()?;
+            let pair = Account::<PairConfig>::try_from(&group[1])?;
+            let price_update = Account::<PriceUpdateV2>::try_from(&group[2])?;
+
+            let expected_pair = Pubkey::create_program_address(
+                &[PAIR_SEED, position.token_a_mint.as_ref(), position.token_b_mint.as_ref(), &[pair.bump]],
+                &crate::ID,
+            ).map_err(|_| ErrorCode::InvalidPortfolioAccounts)?;
+            require_keys_eq!(pair.key(), expected_pair, ErrorCode::InvalidPortfolioAccounts);
+
+            // Values are in token A units, so they only add up over one token A
+            let mint = *token_a_mint.get_or_insert(position.token_a_mint);
+            require_keys_eq!(position.token_a_mint, mint, ErrorCode::PortfolioQuoteMismatch);
+
+            let (price, confidence) = get_pair_price(&price_update, &pair, PRICE_MAX_AGE)?;
+            let value = position_value(
+                &position,
+                &pair,
+                price,
+                confidence,
+                (position.token_a_in_lending, position.token_b_in_lending, false),
+            )?;
+
+            total_value = total_value.checked_add(value.total_value).ok_or(ErrorCode::MathOverflow)?;
+            net_deposit_value = net_deposit_value
+                .checked_add(value.net_deposit_value)
+                .ok_or(ErrorCode::MathOverflow)?;
+            summaries.push(PositionValueSummary {
+                position_id: value.position_id,
+                total_value: value.total_value,
+                unrealized_pnl: value.unrealized_pnl,
+            });
+        }
+
+        let unrealized_pnl = i64::try_from(total_value as i128 - net_deposit_value as i128)
+            .map_err(|_| ErrorCode::MathOverflow)?;
+
+        msg!(
+            "Portfolio of {} positions value: {} (net deposits {}, PnL {})",
+            summaries.len(), total_value, net_deposit_value, unrealized_pnl
+        );
+
+        Ok(PortfolioValue {
+            owner: self.user_main_account.owner,
+            token_a_mint: token_a_mint.unwrap_or_default(),
+            total_value,
+            net_deposit_value,
+            unrealized_pnl,
+            positions: summaries,
+        })
+    }
+}
+
+// Split remaining accounts into one group per position
+fn portfolio_groups<'a, 'info>(
+    accounts: &'a [AccountInfo<'info>],
+    group_len: usize,
+) -> Result<std::slice::ChunksExact<'a, AccountInfo<'info>>> {
+    require!(
+        !accounts.is_empty()
+            && accounts.len() % group_len == 0
+            && accounts.len() / group_len <= MAX_BATCH_SIZE,
+        ErrorCode::InvalidPortfolioAccounts
+    );
+    Ok(accounts.chunks_exact(group_len))
+}
+
+// A position of this user that is in its open index, each at most once.
+// Positions to modify must not be mid-rebalance.
+fn load_position<'info>(
+    user: &UserMainAccount,
+    info: &'info AccountInfo<'info>,
+    seen: &mut Vec<u64>,
+    writable: bool,
+) -> Result<AccountLoader<'info, Position>> {
+    require!(!writable || info.is_writable, ErrorCode::InvalidPortfolioAccounts);
+
+    let loader = AccountLoader::<Position>::try_from(info)?;
+    let position = loader.load()?;
+    require_keys_eq!(position.owner, user.owner, ErrorCode::PositionNotIndexed);
+    require!(
+        user.open_positions().contains(&position.position_id),
+        ErrorCode::PositionNotIndexed
+    );
+    require!(!seen.contains(&position.position_id), ErrorCode::InvalidPortfolioAccounts);
+    require!(!writable || !position.is_rebalancing(), ErrorCode::PositionRebalancing);
+
+    seen.push(position.position_id);
+    drop(position);
+    Ok(loader)
+}
@@ -0,0 +1,223 @@
+// instructions/preset.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token, TokenAccount};
+use anchor_spl::associated_token::AssociatedToken;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::constants::*;
+use crate::instructions::initialize::{new_position, register_position};
+use crate::instructions::rebalance::get_pair_price;
+use crate::strategy::range_around;
+
+// Publish or update a named strategy preset (protocol admin)
+#[derive(Accounts)]
+#[instruction(name: [u8; 32])]
+pub struct SetStrategyPreset<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + StrategyPreset::INIT_SPACE,
+        seeds = [PRESET_SEED, name.as_ref()],
+        bump
+    )]
+    pub preset: Box<Account<'info, StrategyPreset>>,
+
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetStrategyPreset<'info> {
+    pub fn set_preset(
+        &mut self,
+        name: [u8; 32],
+        params: StrategyPresetParams,
+        bumps: &SetStrategyPresetBumps,
+    ) -> Result<()> {
+        require!(
+            params.rebalance_threshold_bps <= 10_000 && params.rungs.len() <= MAX_LP_RUNGS,
+            ErrorCode::InvalidStrategyPreset
+        );
+
+        // The single range is only used without a ladder
+        if params.rungs.is_empty() {
+            require!(
+                params.range_width_bps > 0 && params.range_width_bps < 20_000,
+                ErrorCode::InvalidStrategyPreset
+            );
+        } else {
+            let mut total_weight: u32 = 0;
+            for rung in params.rungs.iter() {
+                require!(
+                    rung.lower_bps < 10_000 && rung.lower_bps as u32 + rung.upper_bps as u32 > 0 && rung.weight_bps > 0,
+                    ErrorCode::InvalidStrategyPreset
+                );
+                total_weight += rung.weight_bps as u32;
+            }
+            require!(total_weight == 10_000, ErrorCode::InvalidStrategyPreset);
+        }
+
+        let mut rungs = [PresetRung::default(); MAX_LP_RUNGS];
+        rungs[..params.rungs.len()].copy_from_slice(&params.rungs);
+
+        self.preset.set_inner(StrategyPreset {
+            version: STRATEGY_PRESET_VERSION,
+            name,
+            range_width_bps: params.range_width_bps,
+            rebalance_threshold_bps: params.rebalance_threshold_bps,
+            min_rebalance_slots: params.min_rebalance_slots,
+            rung_count: params.rungs.len() as u8,
+            rungs,
+            out_of_range_policy: params.out_of_range_policy,
+            hedge_enabled: params.hedge_enabled,
+            enabled: params.enabled,
+            bump: bumps.preset,
+            reserved: [0; STRATEGY_PRESET_RESERVED_BYTES],
+        });
+
+        msg!(
+            "Strategy preset {} configured (enabled: {})",
+            String::from_utf8_lossy(&name).trim_end_matches('\0'),
+            params.enabled
+        );
+        Ok(())
+    }
+}
+
+// Create a position whose range is computed from a preset at the current Pyth price
+#[derive(Accounts)]
+#[instruction(position_id: u64)]
+pub struct CreatePositionFromPreset<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Position::INIT_SPACE,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub position: AccountLoader<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [USER_SEED, owner.key().as_ref()],
+        bump = user_main_account.bump,
+        constraint = user_main_account.owner == owner.key()
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+
+    #[account(
+        seeds = [PAIR_SEED, token_a_mint.key().as_ref(), token_b_mint.key().as_ref()],
+        bump = pair_config.bump,
+        constraint = pair_config.enabled @ ErrorCode::PairNotSupported
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+
+    #[account(
+        seeds = [PRESET_SEED, preset.name.as_ref()],
+        bump = preset.bump,
+        constraint = preset.enabled @ ErrorCode::PresetDisabled
+    )]
+    pub preset: Box<Account<'info, StrategyPreset>>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> CreatePositionFromPreset<'info> {
+    pub fn init_from_preset(&mut self, position_id: u64, bumps: &CreatePositionFromPresetBumps) -> Result<()> {
+        let (price, _) = get_pair_price(&self.price_update, &self.pair_config, PRICE_MAX_AGE)?;
+        let preset = &self.preset;
+
+        // Concrete rung ranges at today's price
+        let mut lp_rungs = [LpRung::default(); MAX_LP_RUNGS];
+        for (slot, rung) in lp_rungs.iter_mut().zip(preset.active_rungs()) {
+            let (range_min, range_max) = range_around(price, rung.lower_bps, rung.upper_bps)?;
+            *slot = LpRung {
+                range_min,
+                range_max,
+                weight_bps: rung.weight_bps,
+                ..LpRung::default()
+            };
+        }
+        let rungs = &lp_rungs[..preset.rung_count as usize];
+
+        // A ladder's outer range is the envelope of its rungs, as in set_lp_ladder
+        let (lp_range_min, lp_range_max) = match (
+            rungs.iter().map(|r| r.range_min).min(),
+            rungs.iter().map(|r| r.range_max).max(),
+        ) {
+            (Some(range_min), Some(range_max)) => (range_min, range_max),
+            _ => {
+                let half_width = preset.range_width_bps / 2;
+                range_around(price, half_width, preset.range_width_bps - half_width)?
+            }
+        };
+
+        let mut position = new_position(
+            self.owner.key(),
+            position_id,
+            self.token_a_mint.key(),
+            self.token_b_mint.key(),
+            lp_range_min,
+            lp_range_max,
+            bumps.position,
+        )?;
+        position.lp_rung_count = preset.rung_count;
+        position.lp_rungs = lp_rungs;
+        position.set_out_of_range_policy(preset.out_of_range_policy);
+        position.set_hedge_enabled(preset.hedge_enabled);
+        position.rebalance_threshold_bps = preset.rebalance_threshold_bps;
+        position.min_rebalance_slots = preset.min_rebalance_slots;
+        position.preset = preset.key();
+
+        register_position(&mut self.user_main_account, &mut self.protocol_authority, position_id)?;
+        *self.position.load_init()? = position;
+
+        msg!(
+            "Position {} created from preset at price {} with range: {}-{} ({} rungs)",
+            position_id, price, lp_range_min, lp_range_max, preset.rung_count
+        );
+        Ok(())
+    }
+}
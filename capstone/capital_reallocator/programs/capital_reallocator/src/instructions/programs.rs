@@ -0,0 +1,91 @@
+// instructions/programs.rs
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::{ExternalProgramsProposedEvent, ExternalProgramsUpdatedEvent};
+use crate::constants::*;
+
+// Propose, apply or cancel new venue program IDs (protocol admin). Changes
+// wait out PROGRAM_UPDATE_TIMELOCK so depositors can exit before the
+// protocol starts routing funds through different programs.
+#[derive(Accounts)]
+pub struct UpdateExternalPrograms<'info> {
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    pub admin: Signer<'info>,
+}
+
+impl<'info> UpdateExternalPrograms<'info> {
+    /// Takes effect right away while no positions are open, which is how
+    /// localnet and devnet deployments point the protocol at mock programs
+    pub fn propose(&mut self, programs: ExternalPrograms) -> Result<()> {
+        require!(programs.is_complete(), ErrorCode::InvalidExternalPrograms);
+        
+        if self.protocol_authority.total_positions == 0 {
+            return self.set_programs(programs);
+        }
+        
+        let eta = Clock::get()?.unix_timestamp
+            .checked_add(PROGRAM_UPDATE_TIMELOCK)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let protocol = &mut self.protocol_authority;
+        protocol.pending_external_programs = programs;
+        protocol.pending_external_programs_eta = eta;
+        
+        emit!(ExternalProgramsProposedEvent {
+            programs,
+            eta,
+            admin: self.admin.key(),
+        });
+        
+        msg!("External program update proposed, can be applied at {}", eta);
+        Ok(())
+    }
+    
+    pub fn apply(&mut self) -> Result<()> {
+        let eta = self.protocol_authority.pending_external_programs_eta;
+        require!(eta != 0, ErrorCode::NoPendingProgramUpdate);
+        require!(Clock::get()?.unix_timestamp >= eta, ErrorCode::ProgramUpdateTimelocked);
+        
+        let programs = self.protocol_authority.pending_external_programs;
+        self.set_programs(programs)
+    }
+    
+    pub fn cancel(&mut self) -> Result<()> {
+        require!(self.protocol_authority.pending_external_programs_eta != 0, ErrorCode::NoPendingProgramUpdate);
+        
+        self.clear_pending();
+        
+        msg!("External program update cancelled");
+        Ok(())
+    }
+    
+    fn set_programs(&mut self, programs: ExternalPrograms) -> Result<()> {
+        let previous = self.protocol_authority.external_programs;
+        self.protocol_authority.external_programs = programs;
+        self.clear_pending();
+        
+        emit!(ExternalProgramsUpdatedEvent {
+            previous,
+            programs,
+            admin: self.admin.key(),
+        });
+        
+        msg!(
+            "External programs set: meteora {}, kamino {}, farms {}, jupiter {}",
+            programs.meteora_dlmm, programs.kamino_lending, programs.kamino_farms, programs.jupiter
+        );
+        Ok(())
+    }
+    
+    fn clear_pending(&mut self) {
+        self.protocol_authority.pending_external_programs = ExternalPrograms::default();
+        self.protocol_authority.pending_external_programs_eta = 0;
+    }
+}
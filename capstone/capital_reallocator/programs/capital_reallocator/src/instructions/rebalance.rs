@@ -1,28 +1,37 @@
 // instructions/rebalance.rs
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint};
-use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, get_feed_id_from_hex};
+use anchor_spl::token::{accessor, Token, TokenAccount, Mint};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
 use crate::state::*;
 use crate::errors::ErrorCode;
-use crate::events::{PositionStatusEvent, RebalanceEvent, RebalanceAction};
+use crate::events::{HedgeEvent, PositionStatusEvent, RangeConversionEvent, RebalanceEvent, RebalanceAction, VenueYieldEvent};
 use crate::constants::*;
-
-pub const SOL_USD_FEED_ID: &str = "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
-
-// Real Meteora DLMM Program ID
-pub const METEORA_DLMM_PROGRAM: &str = "LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo";
-// Real Kamino Lending Program ID  
-pub const KAMINO_LENDING_PROGRAM: &str = "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD";
-// Real Jupiter Aggregator Program ID
-pub const JUPITER_PROGRAM: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+use crate::strategy::{
+    classify_price, hedge_collateral_required, hedge_debt_capacity, hedge_ltv_bps, lending_outyields_lp,
+    lp_fee_apr_bps, rebalance_gate, Allocation, PriceZone, RebalanceGate, RebalanceParams,
+};
+use crate::protocols::meteora::{
+    read_lb_pair, read_lb_pair_fees, validate_bin_array, validate_meteora_position, meteora_event_authority,
+};
+use crate::protocols::kamino::{
+    kamino_market_authority, kamino_reserve_pdas, read_obligation_borrow, read_reserve_available_liquidity,
+    read_reserve_ltv_limits, read_reserve_supply_apy_bps,
+    validate_kamino_lending_market, validate_kamino_obligation, validate_kamino_reserve,
+};
 
 #[derive(Accounts)]
 pub struct CheckPositionStatus<'info> {
     #[account(
-        seeds = [POSITION_SEED, position.owner.as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump
     )]
-    pub position: Account<'info, Position>,
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        seeds = [PAIR_SEED, position.load()?.token_a_mint.as_ref(), position.load()?.token_b_mint.as_ref()],
+        bump = pair_config.bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
     
     /// CHECK: Pyth price account - validated in instruction logic
     pub price_update: UncheckedAccount<'info>,
@@ -33,79 +42,118 @@ impl<'info> CheckPositionStatus<'info> {
         let price_update = PriceUpdateV2::try_deserialize(
             &mut &self.price_update.data.borrow()[..]
         ).map_err(|_| ErrorCode::InvalidAccountData)?;
-        // Get the price feed ID (using SOL/USD for SOL positions)
-        let feed_id = get_feed_id_from_hex(SOL_USD_FEED_ID)?;
-        // Get price with maximum age of 30 seconds
-        let maximum_age: u64 = 30;
-        let price_data = price_update.get_price_no_older_than(
-            &Clock::get()?,
-            maximum_age,
-            &feed_id
-        )?;
-
-        // Convert price to 6 decimals (USD standard)
-        let current_price = normalize_pyth_price(
-            price_data.price,
-            price_data.exponent,
-            6
-        )?;
+        // Get price from the pair's feed with maximum age of 30 seconds
+        let (current_price, _) = get_pair_price(&price_update, &self.pair_config, 30)?;
+        let position = self.position.load()?;
         
         // Check if price is in range
-        let in_range = current_price >= self.position.lp_range_min && 
-                       current_price <= self.position.lp_range_max;
+        let in_range = current_price >= position.lp_range_min && 
+                       current_price <= position.lp_range_max;
         
         msg!(
             "Position {} - Current price: {}, Range: {}-{}, In range: {}",
-            self.position.position_id,
+            position.position_id,
             current_price,
-            self.position.lp_range_min,
-            self.position.lp_range_max,
+            position.lp_range_min,
+            position.lp_range_max,
             in_range
         );
         
         emit!(PositionStatusEvent {
-            position_id: self.position.position_id,
-            owner: self.position.owner,
+            position_id: position.position_id,
+            owner: position.owner,
             current_price,
             in_range,
-            has_lp: self.position.token_a_in_lp > 0 || self.position.token_b_in_lp > 0,
-            has_lending: self.position.token_a_in_lending > 0 || self.position.token_b_in_lending > 0,
+            has_lp: position.token_a_in_lp > 0 || position.token_b_in_lp > 0,
+            has_lending: position.token_a_in_lending > 0 || position.token_b_in_lending > 0,
         });
         
         Ok(())
     }
 }
 
+// Keeper-supplied Jupiter route for a conversion or hedge swap. A route
+// is good for one swap per instruction.
+pub struct ConversionRoute<'a, 'info> {
+    pub swap_data: Vec<u8>,
+    pub min_swap_out: u64,
+    pub accounts: &'a [AccountInfo<'info>],
+    used: std::cell::Cell<bool>,
+}
+
+impl<'a, 'info> ConversionRoute<'a, 'info> {
+    /// Split remaining accounts into the ladder rung accounts and the route
+    pub fn split(
+        rung_count: u8,
+        swap_data: Vec<u8>,
+        min_swap_out: u64,
+        remaining_accounts: &'a [AccountInfo<'info>],
+    ) -> (&'a [AccountInfo<'info>], Self) {
+        let rung_account_count = (rung_count as usize * LP_RUNG_ACCOUNTS)
+            .min(remaining_accounts.len());
+        let (rung_accounts, route_accounts) = remaining_accounts.split_at(rung_account_count);
+        let route = ConversionRoute { swap_data, min_swap_out, accounts: route_accounts, used: std::cell::Cell::new(false) };
+        (rung_accounts, route)
+    }
+    
+    // Supplied and not yet swapped through
+    pub fn is_available(&self) -> bool {
+        !self.swap_data.is_empty() && !self.used.get()
+    }
+}
+
+// Outcome of the pre-rebalance checks. `in_range` is where the price is;
+// `lp_target` is whether the position belongs in LP (in range, and LP fees
+// keep up with lending).
+pub struct RebalancePlan {
+    pub current_price: u64,
+    pub in_range: bool,
+    pub lp_target: bool,
+    pub proceed: bool,
+}
+
 // Rebalance Position with Meteora Integration
 #[derive(Accounts)]
 pub struct RebalancePosition<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, position.owner.as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump
     )]
-    pub position: Account<'info, Position>,
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        seeds = [PAIR_SEED, position.load()?.token_a_mint.as_ref(), position.load()?.token_b_mint.as_ref()],
+        bump = pair_config.bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
     
     pub price_update: Account<'info, PriceUpdateV2>,
+    
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
 
     // Position token vaults
     #[account(
         mut,
         constraint = position_token_a_vault.owner == position.key(),
-        constraint = position_token_a_vault.mint == position.token_a_mint
+        constraint = position_token_a_vault.mint == position.load()?.token_a_mint
     )]
     pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
         constraint = position_token_b_vault.owner == position.key(),
-        constraint = position_token_b_vault.mint == position.token_b_mint
+        constraint = position_token_b_vault.mint == position.load()?.token_b_mint
     )]
     pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
 
     // Meteora DLMM Accounts
     /// CHECK: Meteora DLMM program
-    #[account(constraint = meteora_program.key() == METEORA_DLMM_PROGRAM.parse::<Pubkey>().unwrap())]
+    #[account(address = protocol_authority.external_programs.meteora_dlmm @ ErrorCode::ExternalProgramMismatch)]
     pub meteora_program: UncheckedAccount<'info>,
 
     /// CHECK: Meteora LB pair account
@@ -142,21 +190,55 @@ pub struct RebalancePosition<'info> {
     
     // Kamino Lending Accounts (for future implementation)
     /// CHECK: Kamino lending program
-    #[account(constraint = kamino_program.key() == KAMINO_LENDING_PROGRAM.parse::<Pubkey>().unwrap())]
+    #[account(address = protocol_authority.external_programs.kamino_lending @ ErrorCode::ExternalProgramMismatch)]
     pub kamino_program: UncheckedAccount<'info>,
 
     /// CHECK: Kamino lending market
     pub kamino_lending_market: Option<UncheckedAccount<'info>>,
     
+    /// CHECK: Kamino lending market authority - derived from the market
+    pub kamino_lending_market_authority: Option<UncheckedAccount<'info>>,
+    
     /// CHECK: Kamino obligation account
+    #[account(mut)]
     pub kamino_obligation: Option<UncheckedAccount<'info>>,
     
     /// CHECK: Kamino reserve accounts
+    #[account(mut)]
     pub kamino_reserve_a: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
     pub kamino_reserve_b: Option<UncheckedAccount<'info>>,
     
+    /// CHECK: Reserve vaults and collateral mints - derived from each reserve
+    #[account(mut)]
+    pub kamino_reserve_a_liquidity_supply: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub kamino_reserve_a_collateral_mint: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub kamino_reserve_a_collateral_supply: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub kamino_reserve_b_liquidity_supply: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub kamino_reserve_b_collateral_mint: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub kamino_reserve_b_collateral_supply: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Position collateral (cToken) accounts - checked against the reserve collateral mints
+    #[account(mut)]
+    pub position_collateral_a: Option<UncheckedAccount<'info>>,
+    #[account(mut)]
+    pub position_collateral_b: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Token B reserve fee receiver for hedge borrows - derived from the reserve
+    #[account(mut)]
+    pub kamino_reserve_b_fee_receiver: Option<UncheckedAccount<'info>>,
+    
+    /// CHECK: Instructions sysvar, read by klend borrow and repay
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instruction_sysvar: Option<UncheckedAccount<'info>>,
+    
     /// CHECK: Jupiter aggregator program
-    #[account(constraint = jupiter_program.key() == JUPITER_PROGRAM.parse::<Pubkey>().unwrap())]
+    #[account(address = protocol_authority.external_programs.jupiter @ ErrorCode::ExternalProgramMismatch)]
     pub jupiter_program: UncheckedAccount<'info>,
     
     // Required system accounts
@@ -167,177 +249,620 @@ pub struct RebalancePosition<'info> {
 }
 
 impl<'info> RebalancePosition<'info> {
-    pub fn rebalance(&mut self) -> Result<()> {
-        // Check if position is paused
-        require!(!self.position.pause_flag, ErrorCode::PositionPaused);
-
-        // Get price from Pyth
-        let feed_id = get_feed_id_from_hex(SOL_USD_FEED_ID)?;
-        let maximum_age: u64 = 60; // 60 seconds max staleness
-
-        let price_data = self.price_update.get_price_no_older_than(
-            &Clock::get()?,
-            maximum_age,
-            &feed_id
-        )?;
-
-        // Normalize price to 6 decimals (USD standard)
-        let current_price = normalize_pyth_price(
-            price_data.price,
-            price_data.exponent,
-            6
-        )?;
+    /// Remaining accounts: ladder rung accounts (LP_RUNG_ACCOUNTS per rung)
+    /// followed by the conversion route's accounts, if any
+    pub fn rebalance(
+        &mut self,
+        swap_data: Vec<u8>,
+        min_swap_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let (rung_count, position_id, owner) = {
+            let position = self.position.load()?;
+            
+            // A multi-step session owns the position until it ends
+            require!(!position.is_rebalancing(), ErrorCode::PositionRebalancing);
+            (position.lp_rung_count, position.position_id, position.owner)
+        };
+        
+        let (rung_accounts, route) = ConversionRoute::split(rung_count, swap_data, min_swap_out, remaining_accounts);
+        
+        let plan = self.plan_rebalance()?;
+        if !plan.proceed {
+            emit!(RebalanceEvent {
+                position_id,
+                owner,
+                current_price: plan.current_price,
+                in_range: plan.in_range,
+                action: RebalanceAction::NoAction,
+            });
+            return self.resize_hedge(plan.current_price, &route);
+        }
+        
+        // Execute rebalancing logic
+        let action = self.execute_rebalance(plan.lp_target, plan.in_range, plan.current_price, rung_accounts, &route)?;
+        
+        self.finish_rebalance(plan.current_price, plan.in_range, action, &route)
+    }
 
-        let confidence = normalize_pyth_price(
-            price_data.conf as i64,
-            price_data.exponent,
-            6
-        )?;
+    // Price, target venue and whether the position should rebalance now
+    pub(crate) fn plan_rebalance(&mut self) -> Result<RebalancePlan> {
+        let (range_min, range_max) = {
+            let position = self.position.load()?;
+            
+            // Check if position is paused
+            require!(!position.is_paused(), ErrorCode::PositionPaused);
+            require!(!position.is_flagged(), ErrorCode::PositionFlagged);
+            (position.lp_range_min, position.lp_range_max)
+        };
         
-        // Calculate price bounds with confidence interval
-        let price_lower = current_price.saturating_sub(confidence);
-        let price_upper = current_price.saturating_add(confidence);
+        self.validate_venues()?;
+
+        // Get price from Pyth (60 seconds max staleness)
+        let (current_price, confidence) = get_pair_price(&self.price_update, &self.pair_config, PRICE_MAX_AGE)?;
         
-        // Check if price is definitively in or out of range
-        let definitely_in_range = price_lower >= self.position.lp_range_min && 
-                                  price_upper <= self.position.lp_range_max;
-        let definitely_out_of_range = price_upper < self.position.lp_range_min || 
-                                      price_lower > self.position.lp_range_max;
+        // Check if price is definitively in or out of range given the confidence interval
+        let zone = classify_price(current_price, confidence, range_min, range_max);
         
         // If price is in the uncertain zone (overlapping range boundary), don't rebalance
-        if !definitely_in_range && !definitely_out_of_range {
+        if zone == PriceZone::Uncertain {
             msg!("Price uncertain at range boundary, skipping rebalance");
-            emit!(RebalanceEvent {
-                position_id: self.position.position_id,
-                owner: self.position.owner,
-                current_price,
-                in_range: false,
-                action: RebalanceAction::NoAction,
-            });
-            return Ok(());
+            return Ok(RebalancePlan { current_price, in_range: false, lp_target: false, proceed: false });
         }
         
-        let in_range = definitely_in_range;
+        let in_range = zone == PriceZone::InRange;
+        
+        // In range, LP is only the target venue while its fees keep up with lending
+        let lp_target = in_range && !self.lending_outyields_lp(current_price)?;
 
         // Check rebalance threshold
-        if !self.should_rebalance(current_price, in_range)? {
+        let proceed = self.should_rebalance(current_price, lp_target)?;
+        if !proceed {
             msg!("Rebalance threshold not met, skipping");
+        }
+        
+        Ok(RebalancePlan { current_price, in_range, lp_target, proceed })
+    }
+
+    // Record a completed rebalance and resize the hedge leg
+    pub(crate) fn finish_rebalance(
+        &mut self,
+        current_price: u64,
+        in_range: bool,
+        action: RebalanceAction,
+        route: &ConversionRoute<'_, 'info>,
+    ) -> Result<()> {
+        {
+            // Update tracking
+            let mut position = self.position.load_mut()?;
+            position.last_rebalance_price = current_price;
+            position.last_rebalance_slot = Clock::get()?.slot;
+            position.total_rebalances = position.total_rebalances.saturating_add(1);
+            
             emit!(RebalanceEvent {
-                position_id: self.position.position_id,
-                owner: self.position.owner,
+                position_id: position.position_id,
+                owner: position.owner,
                 current_price,
                 in_range,
-                action: RebalanceAction::NoAction,
+                action,
             });
-            return Ok(());
         }
         
-        // Execute rebalancing logic
-        let action = self.execute_rebalance(in_range, current_price)?;
+        self.resize_hedge(current_price, route)
+    }
+
+    /// Run the current phase of a rebalance session on the rung accounts
+    /// and route passed for it. Returns the price the phase ran at.
+    pub(crate) fn run_session_phase(
+        &mut self,
+        session: &RebalanceSession,
+        rung_accounts: &[AccountInfo<'info>],
+        route: &ConversionRoute<'_, 'info>,
+    ) -> Result<u64> {
+        let (rung_count, range_min, range_max, allocation) = {
+            let position = self.position.load()?;
+            require!(!position.is_flagged(), ErrorCode::PositionFlagged);
+            (position.lp_rung_count, position.lp_range_min, position.lp_range_max, Allocation::of(&position))
+        };
+        
+        self.validate_venues()?;
+        
+        let (current_price, confidence) = get_pair_price(&self.price_update, &self.pair_config, PRICE_MAX_AGE)?;
+        let target_lp = session.target_lp;
+        
+        match session.phase {
+            RebalancePhase::Unwind => {
+                if rung_count > 0 {
+                    let plan = plan_ladder(&*self.position.load()?, &self.pair_config, target_lp, current_price)?;
+                    for rung_index in 0..rung_count as usize {
+                        if plan.close[rung_index] {
+                            self.close_meteora_rung(rung_index, rung_accounts)?;
+                        }
+                    }
+                } else if !target_lp && allocation.has_lp {
+                    self.close_meteora_position()?;
+                }
+                if target_lp && allocation.has_lending {
+                    self.withdraw_from_kamino()?;
+                }
+            }
+            RebalancePhase::Swap => {
+                if target_lp {
+                    self.convert_for_reentry(current_price, route)?;
+                } else if !session.price_in_range {
+                    self.convert_out_of_range(current_price, route)?;
+                }
+            }
+            RebalancePhase::Deploy => {
+                if target_lp {
+                    // Bins are placed around the current price, so it must still be in range
+                    require!(
+                        classify_price(current_price, confidence, range_min, range_max) == PriceZone::InRange,
+                        ErrorCode::RebalanceSessionStale
+                    );
+                    if rung_count > 0 {
+                        let plan = plan_ladder(&*self.position.load()?, &self.pair_config, true, current_price)?;
+                        self.deploy_to_rungs(&plan.deploy, current_price, rung_accounts)?;
+                    } else {
+                        self.open_meteora_position(current_price)?;
+                    }
+                } else {
+                    self.deposit_to_kamino()?;
+                }
+            }
+        }
         
-        // Update tracking
-        self.position.last_rebalance_price = current_price;
-        self.position.last_rebalance_slot = Clock::get()?.slot;
-        self.position.total_rebalances = self.position.total_rebalances.saturating_add(1);
+        Ok(current_price)
+    }
+
+    // Venue accounts must be the pair's canonical ones, linked to each other,
+    // and anything holding position funds must be owned by the position PDA
+    fn validate_venues(&self) -> Result<()> {
+        let pair = &self.pair_config;
+        let position_key = self.position.key();
+        let meteora_program = self.meteora_program.key();
+        let kamino_program = self.kamino_program.key();
+        let position = self.position.load()?;
+        
+        if let Some(lb_pair) = self.meteora_lb_pair.as_ref() {
+            require_keys_eq!(lb_pair.key(), pair.meteora_lb_pair, ErrorCode::VenueMismatch);
+            verify_meteora_pair(
+                lb_pair,
+                self.meteora_reserve_x.as_deref(),
+                self.meteora_reserve_y.as_deref(),
+                self.meteora_event_authority.as_deref(),
+                &position,
+                &meteora_program,
+            )?;
+        }
+        if let Some(meteora_position) = self.meteora_position.as_ref() {
+            if let Some(expected) = position.meteora_position() {
+                require_keys_eq!(meteora_position.key(), expected, ErrorCode::VenueMismatch);
+            }
+            validate_meteora_position(meteora_position, &pair.meteora_lb_pair, &position_key, &meteora_program)?;
+        }
+        for bin_array in [&self.meteora_bin_array_lower, &self.meteora_bin_array_upper].into_iter().flatten() {
+            validate_bin_array(bin_array, &pair.meteora_lb_pair, &meteora_program)?;
+        }
         
-        emit!(RebalanceEvent {
-            position_id: self.position.position_id,
-            owner: self.position.owner,
-            current_price,
-            in_range,
-            action,
-        });
+        if let Some(lending_market) = self.kamino_lending_market.as_ref() {
+            require_keys_eq!(lending_market.key(), pair.kamino_lending_market, ErrorCode::VenueMismatch);
+            validate_kamino_lending_market(lending_market, &kamino_program)?;
+        }
+        if let Some(authority) = self.kamino_lending_market_authority.as_ref() {
+            require_keys_eq!(
+                authority.key(),
+                kamino_market_authority(&pair.kamino_lending_market, &kamino_program),
+                ErrorCode::InvalidDerivedAccount
+            );
+        }
+        if let Some(obligation) = self.kamino_obligation.as_ref() {
+            verify_kamino_obligation(obligation, &position, &position_key, &pair.kamino_lending_market, &kamino_program)?;
+        }
+        if let Some(reserve_a) = self.kamino_reserve_a.as_ref() {
+            require_keys_eq!(reserve_a.key(), pair.kamino_reserve_a, ErrorCode::VenueMismatch);
+            verify_kamino_reserve(
+                reserve_a,
+                &pair.kamino_lending_market,
+                &position.token_a_mint,
+                self.kamino_reserve_a_liquidity_supply.as_deref(),
+                self.kamino_reserve_a_collateral_mint.as_deref(),
+                self.kamino_reserve_a_collateral_supply.as_deref(),
+                None,
+                self.position_collateral_a.as_deref(),
+                &position_key,
+                &kamino_program,
+            )?;
+        }
+        if let Some(reserve_b) = self.kamino_reserve_b.as_ref() {
+            require_keys_eq!(reserve_b.key(), pair.kamino_reserve_b, ErrorCode::VenueMismatch);
+            verify_kamino_reserve(
+                reserve_b,
+                &pair.kamino_lending_market,
+                &position.token_b_mint,
+                self.kamino_reserve_b_liquidity_supply.as_deref(),
+                self.kamino_reserve_b_collateral_mint.as_deref(),
+                self.kamino_reserve_b_collateral_supply.as_deref(),
+                self.kamino_reserve_b_fee_receiver.as_deref(),
+                self.position_collateral_b.as_deref(),
+                &position_key,
+                &kamino_program,
+            )?;
+        }
         
         Ok(())
     }
 
-    fn should_rebalance(&self, current_price: u64, in_range: bool) -> Result<bool> {
-        // Check if enough time has passed since last rebalance
-        let current_slot = Clock::get()?.slot;
-        let slots_since_rebalance = current_slot.saturating_sub(self.position.last_rebalance_slot);
+    // Compare the pair's recent LP fee APR with what the position would earn
+    // supplied to Kamino. Without a usable fee sample the position keeps its
+    // current venue.
+    fn lending_outyields_lp(&mut self, current_price: u64) -> Result<bool> {
+        if !self.pair_config.yield_check_enabled {
+            return Ok(false);
+        }
         
-        // Minimum ~10 seconds between rebalances (25 slots)
-        const MIN_SLOTS_BETWEEN_REBALANCES: u64 = 25;
+        let (Some(lb_pair), Some(reserve_x), Some(reserve_y), Some(kamino_reserve_a), Some(kamino_reserve_b)) = (
+            self.meteora_lb_pair.as_ref(),
+            self.meteora_reserve_x.as_ref(),
+            self.meteora_reserve_y.as_ref(),
+            self.kamino_reserve_a.as_ref(),
+            self.kamino_reserve_b.as_ref(),
+        ) else {
+            msg!("Venue accounts missing, skipping yield check");
+            return Ok(false);
+        };
+        
+        let mut position = self.position.load_mut()?;
+        let allocation = Allocation::of(&position);
+        let in_lending = allocation.has_lending && !allocation.has_lp;
+        
+        let fees = read_lb_pair_fees(lb_pair, &self.meteora_program.key())?;
+        let now = Clock::get()?.unix_timestamp;
+        let elapsed = now.saturating_sub(position.lp_fee_snapshot_at);
+        
+        // The accumulators drop when the protocol claims its fees; start over
+        let claimed = fees.protocol_fee_x < position.lp_fee_snapshot_x
+            || fees.protocol_fee_y < position.lp_fee_snapshot_y;
+        if position.lp_fee_snapshot_at == 0 || claimed {
+            position.lp_fee_snapshot_x = fees.protocol_fee_x;
+            position.lp_fee_snapshot_y = fees.protocol_fee_y;
+            position.lp_fee_snapshot_at = now;
+            return Ok(in_lending);
+        }
+        if elapsed < YIELD_SAMPLE_MIN_SECONDS || fees.protocol_share_bps == 0 || fees.protocol_share_bps >= 10_000 {
+            return Ok(in_lending);
+        }
+        
+        // LPs earn everything the protocol share leaves over
+        let pair = &self.pair_config;
+        let protocol_fee_value = (fees.protocol_fee_x - position.lp_fee_snapshot_x)
+            .checked_add(pair.value_b_in_a(fees.protocol_fee_y - position.lp_fee_snapshot_y, current_price)?)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let lp_fee_value = (protocol_fee_value as u128 * (10_000 - fees.protocol_share_bps) as u128
+            / fees.protocol_share_bps as u128)
+            .min(u64::MAX as u128) as u64;
+        let tvl_value = accessor::amount(reserve_x)?
+            .checked_add(pair.value_b_in_a(accessor::amount(reserve_y)?, current_price)?)
+            .ok_or(ErrorCode::MathOverflow)?;
+        let lp_apr_bps = lp_fee_apr_bps(lp_fee_value, tvl_value, elapsed);
+        
+        // Supply APY of each reserve, weighted by the position's value in each token
+        let kamino_program = self.kamino_program.key();
+        let apy_a = read_reserve_supply_apy_bps(kamino_reserve_a, &kamino_program)? as u128;
+        let apy_b = read_reserve_supply_apy_bps(kamino_reserve_b, &kamino_program)? as u128;
+        let value_a = position.total_token_a() as u128;
+        let value_b = pair.value_b_in_a(position.total_token_b(), current_price)? as u128;
+        let lending_apy_bps = (apy_a * value_a + apy_b * value_b)
+            .checked_div(value_a + value_b)
+            .unwrap_or((apy_a + apy_b) / 2) as u64;
+        
+        let prefer_lending = lending_outyields_lp(lp_apr_bps, lending_apy_bps, pair.yield_margin_bps);
+        
+        // Roll the sample so it tracks recent fees rather than all-time ones
+        if elapsed >= YIELD_SAMPLE_WINDOW_SECONDS {
+            position.lp_fee_snapshot_x = fees.protocol_fee_x;
+            position.lp_fee_snapshot_y = fees.protocol_fee_y;
+            position.lp_fee_snapshot_at = now;
+        }
+        
+        msg!(
+            "LP fee APR {}bps vs lending APY {}bps (margin {}bps)",
+            lp_apr_bps, lending_apy_bps, pair.yield_margin_bps
+        );
+        emit!(VenueYieldEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            lp_fee_apr_bps: lp_apr_bps,
+            lending_apy_bps,
+            margin_bps: pair.yield_margin_bps,
+            prefer_lending,
+        });
         
-        if slots_since_rebalance < MIN_SLOTS_BETWEEN_REBALANCES {
-            msg!("Too soon since last rebalance: {} slots", slots_since_rebalance);
+        Ok(prefer_lending)
+    }
+
+    fn should_rebalance(&self, current_price: u64, in_range: bool) -> Result<bool> {
+        let position = self.position.load()?;
+        
+        // Freed liquidity is earmarked for the owner until the queue is filled
+        if position.has_queued_withdrawal() {
+            msg!("Withdrawal queued, skipping rebalance");
             return Ok(false);
         }
         
-        // Check price movement threshold (1% minimum)
-        if self.position.last_rebalance_price > 0 {
-            let price_change = if current_price > self.position.last_rebalance_price {
-                current_price - self.position.last_rebalance_price
-            } else {
-                self.position.last_rebalance_price - current_price
-            };
-            
-            let price_change_bps = price_change
-                .checked_mul(10000)
-                .ok_or(ErrorCode::MathOverflow)?
-                / self.position.last_rebalance_price;
-            
-            if price_change_bps < REBALANCE_THRESHOLD_BPS as u64 {
-                msg!("Price change {}bps below threshold {}bps", price_change_bps, REBALANCE_THRESHOLD_BPS);
+        // Check if enough time has passed and the price moved enough since last rebalance
+        let current_slot = Clock::get()?.slot;
+        let slots_since_rebalance = current_slot.saturating_sub(position.last_rebalance_slot);
+        
+        let params = RebalanceParams::for_position(&position);
+        match rebalance_gate(
+            &params,
+            slots_since_rebalance,
+            position.last_rebalance_price,
+            current_price,
+        )? {
+            RebalanceGate::TooSoon { slots_since } => {
+                msg!("Too soon since last rebalance: {} slots", slots_since);
                 return Ok(false);
             }
+            RebalanceGate::BelowThreshold { change_bps } => {
+                msg!("Price change {}bps below threshold {}bps", change_bps, params.threshold_bps);
+                return Ok(false);
+            }
+            RebalanceGate::Ready => {}
         }
         
         // Check if position state actually needs rebalancing
-        let has_lp = self.position.token_a_in_lp > 0 || self.position.token_b_in_lp > 0;
-        let has_lending = self.position.token_a_in_lending > 0 || self.position.token_b_in_lending > 0;
-        let has_idle = self.position.token_a_vault_balance > 0 || self.position.token_b_vault_balance > 0;
+        let needs_rebalance = Allocation::of(&position).needs_rebalance(in_range);
         
-        let needs_rebalance = (in_range && has_lending) ||  // Should be in LP but in lending
-                             (!in_range && has_lp) ||        // Should be in lending but in LP
-                             has_idle;                        // Has idle funds to deploy
+        // Laddered positions also rebalance when individual rungs need work
+        if !needs_rebalance && position.has_ladder() {
+            return Ok(!plan_ladder(&position, &self.pair_config, in_range, current_price)?.is_empty());
+        }
         
         Ok(needs_rebalance)
     }
 
-    fn execute_rebalance(&mut self, in_range: bool, current_price: u64) -> Result<RebalanceAction> {
-        let has_lp = self.position.token_a_in_lp > 0 || self.position.token_b_in_lp > 0;
-        let has_lending = self.position.token_a_in_lending > 0 || self.position.token_b_in_lending > 0;
-        let has_idle = self.position.token_a_vault_balance > 0 || self.position.token_b_vault_balance > 0;
+    // `in_range` picks the target venue; `price_in_range` says whether the
+    // price actually left the range, which is when conversion applies
+    fn execute_rebalance(
+        &mut self,
+        in_range: bool,
+        price_in_range: bool,
+        current_price: u64,
+        rung_accounts: &[AccountInfo<'info>],
+        route: &ConversionRoute<'_, 'info>,
+    ) -> Result<RebalanceAction> {
+        if self.position.load()?.has_ladder() {
+            return self.execute_ladder_rebalance(in_range, price_in_range, current_price, rung_accounts, route);
+        }
+        
+        let allocation = Allocation::of(&*self.position.load()?);
         
         msg!(
             "Executing rebalance - Price: ${}, In range: {}, LP: {}, Lending: {}, Idle: {}",
-            current_price / 10u64.pow(6), in_range, has_lp, has_lending, has_idle
+            current_price / 10u64.pow(6), in_range, allocation.has_lp, allocation.has_lending, allocation.has_idle
+        );
+        
+        let action = allocation.plan(in_range);
+        match action {
+            RebalanceAction::MoveToLP => {
+                if allocation.has_lending {
+                    msg!("Moving from lending to LP");
+                    self.withdraw_from_kamino()?;
+                }
+                self.convert_for_reentry(current_price, route)?;
+                self.open_meteora_position(current_price)?;
+            }
+            RebalanceAction::MoveToLending => {
+                if allocation.has_lp {
+                    msg!("Moving from LP to lending");
+                    self.close_meteora_position()?;
+                }
+                if !price_in_range {
+                    self.convert_out_of_range(current_price, route)?;
+                }
+                self.deposit_to_kamino()?;
+            }
+            RebalanceAction::NoAction => {}
+        }
+        
+        Ok(action)
+    }
+    
+    fn execute_ladder_rebalance(
+        &mut self,
+        in_range: bool,
+        price_in_range: bool,
+        current_price: u64,
+        rung_accounts: &[AccountInfo<'info>],
+        route: &ConversionRoute<'_, 'info>,
+    ) -> Result<RebalanceAction> {
+        let (plan, has_lending, rung_count) = {
+            let position = self.position.load()?;
+            let plan = plan_ladder(&position, &self.pair_config, in_range, current_price)?;
+            let has_lending = position.token_a_in_lending > 0 || position.token_b_in_lending > 0;
+            (plan, has_lending, position.lp_rung_count)
+        };
+        
+        msg!(
+            "Executing ladder rebalance - Price: ${}, In range: {}, Rungs: {}",
+            current_price / 10u64.pow(6), in_range, rung_count
         );
         
+        // Close rungs that left their range or drifted away from their weight
+        let mut closed_any = false;
+        for rung_index in 0..rung_count as usize {
+            if plan.close[rung_index] {
+                self.close_meteora_rung(rung_index, rung_accounts)?;
+                closed_any = true;
+            }
+        }
+        
+        let has_idle = {
+            let position = self.position.load()?;
+            position.token_a_vault_balance > 0 || position.token_b_vault_balance > 0
+        };
+        
         if in_range {
             if has_lending {
-                msg!("Moving from lending to LP");
+                msg!("Moving from lending to LP rungs");
                 self.withdraw_from_kamino()?;
             }
             if has_idle || has_lending {
-                // Balance tokens first (before borrowing accounts)
-                self.balance_tokens_for_lp(current_price)?;
-                self.open_meteora_position(current_price)?;
+                self.convert_for_reentry(current_price, route)?;
+                self.deploy_to_rungs(&plan.deploy, current_price, rung_accounts)?;
                 Ok(RebalanceAction::MoveToLP)
             } else {
                 Ok(RebalanceAction::NoAction)
             }
+        } else if has_idle || closed_any {
+            msg!("Moving from LP rungs to lending");
+            if !price_in_range {
+                self.convert_out_of_range(current_price, route)?;
+            }
+            self.deposit_to_kamino()?;
+            Ok(RebalanceAction::MoveToLending)
         } else {
-            if has_lp {
-                msg!("Moving from LP to lending");
-                self.close_meteora_position()?;
+            Ok(RebalanceAction::NoAction)
+        }
+    }
+    
+    fn close_meteora_rung(&mut self, rung_index: usize, rung_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let meteora_lb_pair = self.meteora_lb_pair.as_ref()
+            .ok_or(ErrorCode::LPPositionNotFound)?;
+        let meteora_reserve_x = self.meteora_reserve_x.as_ref()
+            .ok_or(ErrorCode::LPPositionNotFound)?;
+        let meteora_reserve_y = self.meteora_reserve_y.as_ref()
+            .ok_or(ErrorCode::LPPositionNotFound)?;
+        let meteora_event_authority = self.meteora_event_authority.as_ref()
+            .ok_or(ErrorCode::LPPositionNotFound)?;
+        let (meteora_position, meteora_bin_array_lower, meteora_bin_array_upper) =
+            rung_accounts_for(&self.position, &self.pair_config.meteora_lb_pair, rung_accounts, rung_index, &self.meteora_program.key())?;
+        
+        Position::close_meteora_rung_cpi(
+            &self.position,
+            rung_index,
+            &self.meteora_program,
+            meteora_lb_pair,
+            meteora_position,
+            &self.position_token_a_vault,
+            &self.position_token_b_vault,
+            meteora_reserve_x,
+            meteora_reserve_y,
+            &self.token_a_mint,
+            &self.token_b_mint,
+            meteora_bin_array_lower,
+            meteora_bin_array_upper,
+            &self.token_program,
+            meteora_event_authority,
+        )
+    }
+    
+    // Split idle vault funds across the rungs selected for deployment by weight
+    fn deploy_to_rungs(
+        &mut self,
+        deploy: &[bool; MAX_LP_RUNGS],
+        current_price: u64,
+        rung_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        let (vault_a, vault_b, rung_count, weights) = {
+            let position = self.position.load()?;
+            let weights = position.lp_rungs.map(|rung| rung.weight_bps as u64);
+            (position.token_a_vault_balance, position.token_b_vault_balance, position.lp_rung_count as usize, weights)
+        };
+        
+        let total_weight: u64 = (0..rung_count)
+            .filter(|&i| deploy[i])
+            .map(|i| weights[i])
+            .sum();
+        
+        if total_weight == 0 || (vault_a == 0 && vault_b == 0) {
+            msg!("No rungs to deploy idle funds into");
+            return Ok(());
+        }
+        
+        let meteora_lb_pair = self.meteora_lb_pair.as_ref()
+            .ok_or(ErrorCode::ExternalProtocolError)?;
+        let meteora_reserve_x = self.meteora_reserve_x.as_ref()
+            .ok_or(ErrorCode::ExternalProtocolError)?;
+        let meteora_reserve_y = self.meteora_reserve_y.as_ref()
+            .ok_or(ErrorCode::ExternalProtocolError)?;
+        let meteora_event_authority = self.meteora_event_authority.as_ref()
+            .ok_or(ErrorCode::ExternalProtocolError)?;
+        
+        let last_rung = (0..rung_count).rev().find(|&i| deploy[i]).unwrap_or(0);
+        let mut remaining_a = vault_a;
+        let mut remaining_b = vault_b;
+        
+        for rung_index in 0..rung_count {
+            if !deploy[rung_index] {
+                continue;
             }
-            if has_idle || has_lp {
-                self.deposit_to_kamino()?;
-                Ok(RebalanceAction::MoveToLending)
+            
+            // Last rung takes the remainder so no dust is left behind
+            let (amount_a, amount_b) = if rung_index == last_rung {
+                (remaining_a, remaining_b)
             } else {
-                Ok(RebalanceAction::NoAction)
+                let weight = weights[rung_index] as u128;
+                let amount_a = (vault_a as u128)
+                    .checked_mul(weight)
+                    .ok_or(ErrorCode::MathOverflow)?
+                    .checked_div(total_weight as u128)
+                    .ok_or(ErrorCode::MathOverflow)? as u64;
+                let amount_b = (vault_b as u128)
+                    .checked_mul(weight)
+                    .ok_or(ErrorCode::MathOverflow)?
+                    .checked_div(total_weight as u128)
+                    .ok_or(ErrorCode::MathOverflow)? as u64;
+                (amount_a, amount_b)
+            };
+            
+            remaining_a = remaining_a.checked_sub(amount_a).ok_or(ErrorCode::MathOverflow)?;
+            remaining_b = remaining_b.checked_sub(amount_b).ok_or(ErrorCode::MathOverflow)?;
+            
+            if amount_a == 0 && amount_b == 0 {
+                continue;
             }
+            
+            let (meteora_position, meteora_bin_array_lower, meteora_bin_array_upper) =
+                rung_accounts_for(&self.position, &self.pair_config.meteora_lb_pair, rung_accounts, rung_index, &self.meteora_program.key())?;
+            
+            Position::open_meteora_rung_cpi(
+                &self.position,
+                rung_index,
+                &self.meteora_program,
+                meteora_lb_pair,
+                meteora_position,
+                &self.position_token_a_vault,
+                &self.position_token_b_vault,
+                meteora_reserve_x,
+                meteora_reserve_y,
+                &self.token_a_mint,
+                &self.token_b_mint,
+                meteora_bin_array_lower,
+                meteora_bin_array_upper,
+                &self.token_program,
+                &self.system_program,
+                &self.rent,
+                meteora_event_authority,
+                amount_a,
+                amount_b,
+                current_price,
+            )?;
         }
+        
+        Ok(())
     }
     
     fn withdraw_from_kamino(&mut self) -> Result<()> {
         msg!("Withdrawing from Kamino lending...");
         
         // Get current balances
-        let lending_a = self.position.token_a_in_lending;
-        let lending_b = self.position.token_b_in_lending;
+        let (lending_a, lending_b) = {
+            let position = self.position.load()?;
+            (position.token_a_in_lending, position.token_b_in_lending)
+        };
         
         if lending_a == 0 && lending_b == 0 {
             msg!("No funds in Kamino lending");
@@ -347,30 +872,37 @@ impl<'info> RebalancePosition<'info> {
         // Check if we have lending accounts provided
         let kamino_lending_market = self.kamino_lending_market.as_ref()
             .ok_or(ErrorCode::LendingPositionNotFound)?;
-        let kamino_obligation = self.kamino_obligation.as_ref()
+        let kamino_lending_market_authority = self.kamino_lending_market_authority.as_ref()
             .ok_or(ErrorCode::LendingPositionNotFound)?;
-        let kamino_reserve_a = self.kamino_reserve_a.as_ref()
-            .ok_or(ErrorCode::LendingPositionNotFound)?;
-        let kamino_reserve_b = self.kamino_reserve_b.as_ref()
+        let kamino_obligation = self.kamino_obligation.as_ref()
             .ok_or(ErrorCode::LendingPositionNotFound)?;
         
         // Execute Kamino withdrawal CPI
         let position_account_info = self.position.to_account_info();
         
         if lending_a > 0 {
-            // Create temporary collateral account reference
-            let source_collateral_a = kamino_reserve_a.to_account_info();
+            let kamino_reserve_a = self.kamino_reserve_a.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let liquidity_supply = self.kamino_reserve_a_liquidity_supply.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let collateral_mint = self.kamino_reserve_a_collateral_mint.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let collateral_supply = self.kamino_reserve_a_collateral_supply.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let position_collateral = self.position_collateral_a.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
             
-            self.position.withdraw_from_kamino_cpi(
-                &position_account_info,
+            let received_a = Position::withdraw_from_kamino_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
-                &source_collateral_a,
+                position_collateral,
                 &self.position_token_a_vault,
                 kamino_reserve_a,
-                kamino_reserve_a, // Reserve liquidity supply
-                kamino_reserve_a, // Reserve collateral mint  
+                liquidity_supply,
+                collateral_mint,
+                collateral_supply,
                 kamino_lending_market,
-                kamino_lending_market, // Market authority (derived)
+                kamino_lending_market_authority,
                 kamino_obligation,
                 &position_account_info, // Owner is the position PDA
                 &self.clock,
@@ -379,26 +911,36 @@ impl<'info> RebalancePosition<'info> {
             )?;
             
             // Update position state
-            self.position.token_a_in_lending = 0;
-            self.position.token_a_vault_balance = self.position.token_a_vault_balance
-                .checked_add(lending_a)
+            let mut position = self.position.load_mut()?;
+            position.token_a_in_lending = 0;
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(received_a)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         
         if lending_b > 0 {
-            // Create temporary collateral account reference
-            let source_collateral_b = kamino_reserve_b.to_account_info();
+            let kamino_reserve_b = self.kamino_reserve_b.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let liquidity_supply = self.kamino_reserve_b_liquidity_supply.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let collateral_mint = self.kamino_reserve_b_collateral_mint.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let collateral_supply = self.kamino_reserve_b_collateral_supply.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
+            let position_collateral = self.position_collateral_b.as_ref()
+                .ok_or(ErrorCode::LendingPositionNotFound)?;
             
-            self.position.withdraw_from_kamino_cpi(
-                &position_account_info,
+            let received_b = Position::withdraw_from_kamino_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
-                &source_collateral_b,
+                position_collateral,
                 &self.position_token_b_vault,
                 kamino_reserve_b,
-                kamino_reserve_b, // Reserve liquidity supply
-                kamino_reserve_b, // Reserve collateral mint
+                liquidity_supply,
+                collateral_mint,
+                collateral_supply,
                 kamino_lending_market,
-                kamino_lending_market, // Market authority (derived)
+                kamino_lending_market_authority,
                 kamino_obligation,
                 &position_account_info, // Owner is the position PDA
                 &self.clock,
@@ -407,9 +949,10 @@ impl<'info> RebalancePosition<'info> {
             )?;
             
             // Update position state
-            self.position.token_b_in_lending = 0;
-            self.position.token_b_vault_balance = self.position.token_b_vault_balance
-                .checked_add(lending_b)
+            let mut position = self.position.load_mut()?;
+            position.token_b_in_lending = 0;
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(received_b)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         
@@ -420,8 +963,10 @@ impl<'info> RebalancePosition<'info> {
     fn deposit_to_kamino(&mut self) -> Result<()> {
         msg!("Depositing to Kamino lending...");
         
-        let vault_a = self.position.token_a_vault_balance;
-        let vault_b = self.position.token_b_vault_balance;
+        let (vault_a, vault_b) = {
+            let position = self.position.load()?;
+            (position.token_a_vault_balance, position.token_b_vault_balance)
+        };
         
         if vault_a == 0 && vault_b == 0 {
             msg!("No idle funds to deposit to Kamino");
@@ -431,18 +976,16 @@ impl<'info> RebalancePosition<'info> {
         // Check if we have lending accounts provided
         let kamino_lending_market = self.kamino_lending_market.as_ref()
             .ok_or(ErrorCode::ExternalProtocolError)?;
-        let kamino_obligation = self.kamino_obligation.as_ref()
+        let kamino_lending_market_authority = self.kamino_lending_market_authority.as_ref()
             .ok_or(ErrorCode::ExternalProtocolError)?;
-        let kamino_reserve_a = self.kamino_reserve_a.as_ref()
-            .ok_or(ErrorCode::ExternalProtocolError)?;
-        let kamino_reserve_b = self.kamino_reserve_b.as_ref()
+        let kamino_obligation = self.kamino_obligation.as_ref()
             .ok_or(ErrorCode::ExternalProtocolError)?;
         
-        // Initialize obligation if needed
-        if self.position.kamino_obligation.is_none() {
-            let position_account_info = self.position.to_account_info();
-            self.position.init_kamino_obligation_cpi(
-                &position_account_info,
+        // Initialize the obligation unless it already exists (validated as ours)
+        let position_account_info = self.position.to_account_info();
+        if kamino_obligation.data_is_empty() {
+            Position::init_kamino_obligation_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
                 kamino_lending_market,
                 kamino_obligation,
@@ -451,25 +994,35 @@ impl<'info> RebalancePosition<'info> {
                 &self.rent,
                 &self.token_program,
             )?;
+        } else {
+            self.position.load_mut()?.set_kamino_obligation(Some(kamino_obligation.key()));
         }
         
         // Execute Kamino deposit CPI
-        let position_account_info = self.position.to_account_info();
         
         if vault_a > 0 {
-            // Create temporary collateral destination
-            let destination_collateral_a = kamino_reserve_a.to_account_info();
+            let kamino_reserve_a = self.kamino_reserve_a.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let liquidity_supply = self.kamino_reserve_a_liquidity_supply.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let collateral_mint = self.kamino_reserve_a_collateral_mint.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let collateral_supply = self.kamino_reserve_a_collateral_supply.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let position_collateral = self.position_collateral_a.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
             
-            self.position.deposit_to_kamino_cpi(
-                &position_account_info,
+            Position::deposit_to_kamino_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
                 &self.position_token_a_vault,
-                &destination_collateral_a,
+                position_collateral,
                 kamino_reserve_a,
-                kamino_reserve_a, // Reserve liquidity supply
-                kamino_reserve_a, // Reserve collateral mint
+                liquidity_supply,
+                collateral_mint,
+                collateral_supply,
                 kamino_lending_market,
-                kamino_lending_market, // Market authority (derived)
+                kamino_lending_market_authority,
                 kamino_obligation,
                 &position_account_info,
                 &self.clock,
@@ -478,26 +1031,36 @@ impl<'info> RebalancePosition<'info> {
             )?;
             
             // Update position state
-            self.position.token_a_vault_balance = 0;
-            self.position.token_a_in_lending = self.position.token_a_in_lending
+            let mut position = self.position.load_mut()?;
+            position.token_a_vault_balance = 0;
+            position.token_a_in_lending = position.token_a_in_lending
                 .checked_add(vault_a)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         
         if vault_b > 0 {
-            // Create temporary collateral destination
-            let destination_collateral_b = kamino_reserve_b.to_account_info();
+            let kamino_reserve_b = self.kamino_reserve_b.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let liquidity_supply = self.kamino_reserve_b_liquidity_supply.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let collateral_mint = self.kamino_reserve_b_collateral_mint.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let collateral_supply = self.kamino_reserve_b_collateral_supply.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
+            let position_collateral = self.position_collateral_b.as_ref()
+                .ok_or(ErrorCode::ExternalProtocolError)?;
             
-            self.position.deposit_to_kamino_cpi(
-                &position_account_info,
+            Position::deposit_to_kamino_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
                 &self.position_token_b_vault,
-                &destination_collateral_b,
+                position_collateral,
                 kamino_reserve_b,
-                kamino_reserve_b, // Reserve liquidity supply
-                kamino_reserve_b, // Reserve collateral mint
+                liquidity_supply,
+                collateral_mint,
+                collateral_supply,
                 kamino_lending_market,
-                kamino_lending_market, // Market authority (derived)
+                kamino_lending_market_authority,
                 kamino_obligation,
                 &position_account_info,
                 &self.clock,
@@ -506,8 +1069,9 @@ impl<'info> RebalancePosition<'info> {
             )?;
             
             // Update position state
-            self.position.token_b_vault_balance = 0;
-            self.position.token_b_in_lending = self.position.token_b_in_lending
+            let mut position = self.position.load_mut()?;
+            position.token_b_vault_balance = 0;
+            position.token_b_in_lending = position.token_b_in_lending
                 .checked_add(vault_b)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
@@ -516,11 +1080,274 @@ impl<'info> RebalancePosition<'info> {
         Ok(())
     }
     
+    // Keep the hedge borrow sized to the LP's token B exposure, posting idle
+    // token A as collateral, and pay it down when the obligation's LTV nears
+    // the collateral reserve's liquidation threshold. Borrowed token B is
+    // sold for A through the keeper's route, so the debt offsets the LP's B.
+    fn resize_hedge(&mut self, current_price: u64, route: &ConversionRoute<'_, 'info>) -> Result<()> {
+        // Skip when the borrow already matches the target, so rebalances that
+        // leave the hedge alone don't need the Kamino accounts
+        if !self.position.load()?.hedge_needs_resize() {
+            return Ok(());
+        }
+        
+        let kamino_lending_market = self.kamino_lending_market.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let kamino_lending_market_authority = self.kamino_lending_market_authority.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let kamino_obligation = self.kamino_obligation.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let kamino_reserve_a = self.kamino_reserve_a.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let reserve_a_liquidity_supply = self.kamino_reserve_a_liquidity_supply.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let reserve_a_collateral_mint = self.kamino_reserve_a_collateral_mint.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let reserve_a_collateral_supply = self.kamino_reserve_a_collateral_supply.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let position_collateral_a = self.position_collateral_a.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let kamino_reserve_b = self.kamino_reserve_b.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let reserve_b_liquidity_supply = self.kamino_reserve_b_liquidity_supply.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let reserve_b_fee_receiver = self.kamino_reserve_b_fee_receiver.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        let instruction_sysvar = self.instruction_sysvar.as_ref()
+            .ok_or(ErrorCode::HedgeAccountsMissing)?;
+        
+        let position_account_info = self.position.to_account_info();
+        if kamino_obligation.data_is_empty() {
+            Position::init_kamino_obligation_cpi(
+                &self.position,
+                &self.kamino_program.to_account_info(),
+                kamino_lending_market,
+                kamino_obligation,
+                &position_account_info,
+                &self.clock,
+                &self.rent,
+                &self.token_program,
+            )?;
+        } else {
+            self.position.load_mut()?.set_kamino_obligation(Some(kamino_obligation.key()));
+        }
+        
+        let (max_ltv_bps, liquidation_threshold_bps) = read_reserve_ltv_limits(kamino_reserve_a, &self.kamino_program.key())?;
+        let target_ltv_bps = HEDGE_TARGET_LTV_BPS.min(max_ltv_bps);
+        let deleverage_ltv_bps = liquidation_threshold_bps.saturating_sub(HEDGE_DELEVERAGE_BUFFER_BPS);
+        
+        let (ltv_before, target_borrow_b, repay_b) = {
+            let mut position = self.position.load_mut()?;
+            
+            // Interest accrues on the borrow, so start from what the obligation owes
+            if position.hedge_borrowed_b > 0 {
+                position.hedge_borrowed_b = read_obligation_borrow(kamino_obligation, &kamino_reserve_b.key(), &self.kamino_program.key())?;
+            }
+            
+            let ltv_before = hedge_ltv_bps(
+                self.pair_config.value_b_in_a(position.hedge_borrowed_b, current_price)?,
+                position.hedge_collateral_a,
+            );
+            
+            // Hedge the LP's token B, capped at what posted plus idle token A can carry
+            let exposure_b = position.hedge_target_b();
+            let max_collateral_a = position.hedge_collateral_a
+                .checked_add(position.token_a_vault_balance)
+                .ok_or(ErrorCode::MathOverflow)?;
+            let target_borrow_b = exposure_b.min(self.pair_config.amount_b_for_value(
+                hedge_debt_capacity(max_collateral_a, target_ltv_bps),
+                current_price,
+            )?);
+            
+            // Pay the borrow down from idle token B, when over the target
+            let repay_b = position.hedge_borrowed_b
+                .saturating_sub(target_borrow_b)
+                .min(position.token_b_vault_balance);
+            
+            (ltv_before, target_borrow_b, repay_b)
+        };
+        
+        if repay_b > 0 {
+            let obligation_reserves = hedge_obligation_reserves(&*self.position.load()?, kamino_reserve_a, kamino_reserve_b);
+            Position::repay_to_kamino_cpi(
+                &self.position,
+                &self.kamino_program.to_account_info(),
+                kamino_obligation,
+                kamino_lending_market,
+                kamino_reserve_b,
+                &self.token_b_mint,
+                reserve_b_liquidity_supply,
+                &self.position_token_b_vault,
+                &obligation_reserves,
+                instruction_sysvar,
+                &self.clock,
+                &self.token_program,
+                repay_b,
+            )?;
+            
+            let mut position = self.position.load_mut()?;
+            position.hedge_borrowed_b -= repay_b;
+            position.token_b_vault_balance -= repay_b;
+        }
+        
+        let (debt_b, collateral_a, vault_a) = {
+            let position = self.position.load()?;
+            (target_borrow_b.max(position.hedge_borrowed_b), position.hedge_collateral_a, position.token_a_vault_balance)
+        };
+        if debt_b == 0 {
+            // Fully unwound, release the collateral
+            if collateral_a > 0 {
+                let received_a = Position::withdraw_from_kamino_cpi(
+                    &self.position,
+                    &self.kamino_program.to_account_info(),
+                    position_collateral_a,
+                    &self.position_token_a_vault,
+                    kamino_reserve_a,
+                    reserve_a_liquidity_supply,
+                    reserve_a_collateral_mint,
+                    reserve_a_collateral_supply,
+                    kamino_lending_market,
+                    kamino_lending_market_authority,
+                    kamino_obligation,
+                    &position_account_info,
+                    &self.clock,
+                    &self.token_program,
+                    collateral_a,
+                )?;
+                
+                let mut position = self.position.load_mut()?;
+                position.hedge_collateral_a = 0;
+                position.token_a_vault_balance = position.token_a_vault_balance
+                    .checked_add(received_a)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
+        } else {
+            // Post enough collateral to carry the debt at the target LTV
+            let required_a = hedge_collateral_required(
+                self.pair_config.value_b_in_a(debt_b, current_price)?,
+                target_ltv_bps,
+            )?;
+            let top_up_a = required_a
+                .saturating_sub(collateral_a)
+                .min(vault_a);
+            
+            if top_up_a > 0 {
+                Position::deposit_to_kamino_cpi(
+                    &self.position,
+                    &self.kamino_program.to_account_info(),
+                    &self.position_token_a_vault,
+                    position_collateral_a,
+                    kamino_reserve_a,
+                    reserve_a_liquidity_supply,
+                    reserve_a_collateral_mint,
+                    reserve_a_collateral_supply,
+                    kamino_lending_market,
+                    kamino_lending_market_authority,
+                    kamino_obligation,
+                    &position_account_info,
+                    &self.clock,
+                    &self.token_program,
+                    top_up_a,
+                )?;
+                
+                let mut position = self.position.load_mut()?;
+                position.token_a_vault_balance -= top_up_a;
+                position.hedge_collateral_a = position.hedge_collateral_a
+                    .checked_add(top_up_a)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
+        }
+        
+        // Borrow up to the target, never past what the posted collateral carries
+        let borrow_b = {
+            let position = self.position.load()?;
+            let capacity_b = self.pair_config.amount_b_for_value(
+                hedge_debt_capacity(position.hedge_collateral_a, target_ltv_bps),
+                current_price,
+            )?;
+            target_borrow_b.min(capacity_b).saturating_sub(position.hedge_borrowed_b)
+        };
+        
+        // Without a route to sell it, borrowed B would just be redeployed long
+        let borrow_b = if borrow_b > 0 && !route.is_available() {
+            msg!("Hedge borrow of {} B deferred until a route to sell it is supplied", borrow_b);
+            0
+        } else {
+            borrow_b
+        };
+        
+        if borrow_b > 0 {
+            let obligation_reserves = hedge_obligation_reserves(&*self.position.load()?, kamino_reserve_a, kamino_reserve_b);
+            Position::borrow_from_kamino_cpi(
+                &self.position,
+                &self.kamino_program.to_account_info(),
+                kamino_obligation,
+                kamino_lending_market,
+                kamino_lending_market_authority,
+                kamino_reserve_b,
+                &self.token_b_mint,
+                reserve_b_liquidity_supply,
+                reserve_b_fee_receiver,
+                &self.position_token_b_vault,
+                &obligation_reserves,
+                instruction_sysvar,
+                &self.clock,
+                &self.token_program,
+                borrow_b,
+            )?;
+            
+            {
+                let mut position = self.position.load_mut()?;
+                position.hedge_borrowed_b = position.hedge_borrowed_b
+                    .checked_add(borrow_b)
+                    .ok_or(ErrorCode::MathOverflow)?;
+                position.token_b_vault_balance = position.token_b_vault_balance
+                    .checked_add(borrow_b)
+                    .ok_or(ErrorCode::MathOverflow)?;
+            }
+            
+            let sold_b = self.swap_vault_tokens(false, borrow_b, current_price, route, false)?;
+            require!(sold_b == borrow_b, ErrorCode::InvalidSwapRoute);
+        }
+        
+        let position = self.position.load()?;
+        let ltv_after = hedge_ltv_bps(
+            self.pair_config.value_b_in_a(position.hedge_borrowed_b, current_price)?,
+            position.hedge_collateral_a,
+        );
+        
+        // New borrows must leave headroom; an existing one we couldn't pay down is flagged
+        if borrow_b > 0 {
+            require!(ltv_after < deleverage_ltv_bps, ErrorCode::HedgeUnhealthy);
+        } else if ltv_after >= deleverage_ltv_bps {
+            msg!(
+                "Hedge LTV {}bps near liquidation threshold {}bps with no idle funds to deleverage",
+                ltv_after, liquidation_threshold_bps
+            );
+        }
+        
+        emit!(HedgeEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            current_price,
+            collateral_a: position.hedge_collateral_a,
+            borrowed_b: position.hedge_borrowed_b,
+            target_borrow_b,
+            ltv_bps: ltv_after,
+            liquidation_threshold_bps,
+            deleveraged: ltv_before >= deleverage_ltv_bps && ltv_after < ltv_before,
+        });
+        
+        Ok(())
+    }
+    
     fn close_meteora_position(&mut self) -> Result<()> {
         msg!("Closing Meteora DLMM position...");
         
-        let lp_a = self.position.token_a_in_lp;
-        let lp_b = self.position.token_b_in_lp;
+        let (lp_a, lp_b) = {
+            let position = self.position.load()?;
+            (position.token_a_in_lp, position.token_b_in_lp)
+        };
         
         if lp_a == 0 && lp_b == 0 {
             msg!("No Meteora liquidity to close");
@@ -544,9 +1371,8 @@ impl<'info> RebalancePosition<'info> {
             .ok_or(ErrorCode::LPPositionNotFound)?;
         
         // Execute Meteora remove liquidity CPI
-        let position_account_info = self.position.to_account_info();
-        self.position.close_meteora_position_cpi(
-            &position_account_info,
+        Position::close_meteora_position_cpi(
+            &self.position,
             &self.meteora_program,
             meteora_lb_pair,
             meteora_position,
@@ -568,8 +1394,10 @@ impl<'info> RebalancePosition<'info> {
     fn open_meteora_position(&mut self, current_price: u64) -> Result<()> {
         msg!("Opening Meteora DLMM position...");
         
-        let vault_a = self.position.token_a_vault_balance;
-        let vault_b = self.position.token_b_vault_balance;
+        let (vault_a, vault_b) = {
+            let position = self.position.load()?;
+            (position.token_a_vault_balance, position.token_b_vault_balance)
+        };
         
         if vault_a == 0 && vault_b == 0 {
             msg!("No idle funds to open Meteora position");
@@ -593,9 +1421,8 @@ impl<'info> RebalancePosition<'info> {
             .ok_or(ErrorCode::ExternalProtocolError)?;
         
         // Execute Meteora add liquidity CPI
-        let position_account_info = self.position.to_account_info();
-        self.position.open_meteora_position_cpi(
-            &position_account_info.to_account_info(),
+        Position::open_meteora_position_cpi(
+            &self.position,
             &self.meteora_program,
             meteora_lb_pair,
             meteora_position,
@@ -619,47 +1446,136 @@ impl<'info> RebalancePosition<'info> {
         Ok(())
     }
 
-    // Jupiter Integration for token balancing (placeholder for now)
-    fn balance_tokens_for_lp(&mut self, current_price: u64) -> Result<()> {
-        msg!("Balancing tokens using Jupiter...");
-        
-        let vault_a = self.position.token_a_vault_balance;
-        let vault_b = self.position.token_b_vault_balance;
-        
-        // Calculate optimal ratio based on current price
-        // For a 50/50 LP position: value_a should equal value_b
-        let total_value_a = vault_a; // Already in USD terms
-        let total_value_b = vault_b.checked_mul(current_price)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10u64.pow(9)) // Convert from 9 decimals to 6 decimals
-            .ok_or(ErrorCode::MathOverflow)?;
-        
-        let total_value = total_value_a.checked_add(total_value_b)
-            .ok_or(ErrorCode::MathOverflow)?;
+    // Sell the side the out-of-range policy doesn't want to hold
+    fn convert_out_of_range(&mut self, current_price: u64, route: &ConversionRoute<'_, 'info>) -> Result<()> {
+        let (policy, vault_a, vault_b) = {
+            let position = self.position.load()?;
+            (position.out_of_range_policy(), position.token_a_vault_balance, position.token_b_vault_balance)
+        };
+        let sell_a = match policy {
+            OutOfRangePolicy::Hold => return Ok(()),
+            OutOfRangePolicy::ConvertToQuote => false,
+            OutOfRangePolicy::ConvertToBase => true,
+        };
+        
+        let amount_in = if sell_a { vault_a } else { vault_b };
+        
+        if amount_in > 0 {
+            msg!("Converting out-of-range funds per {:?}", policy);
+            self.swap_vault_tokens(sell_a, amount_in, current_price, route, false)?;
+        }
         
-        if total_value == 0 {
-            return Ok(());
+        // Even with nothing to sell, the funds are one-sided until re-entry
+        self.position.load_mut()?.set_out_of_range_converted(true);
+        Ok(())
+    }
+    
+    // Swap a converted position back to equal value of each token
+    fn convert_for_reentry(&mut self, current_price: u64, route: &ConversionRoute<'_, 'info>) -> Result<()> {
+        let (value_a, vault_b) = {
+            let position = self.position.load()?;
+            if !position.out_of_range_converted() {
+                return Ok(());
+            }
+            (position.token_a_vault_balance, position.token_b_vault_balance)
+        };
+        
+        let value_b = self.pair_config.value_b_in_a(vault_b, current_price)?;
+        let target_value = value_a.checked_add(value_b).ok_or(ErrorCode::MathOverflow)? / 2;
+        
+        if value_a > target_value {
+            self.swap_vault_tokens(true, value_a - target_value, current_price, route, true)?;
+        } else if value_b > target_value {
+            let excess_b = self.pair_config.amount_b_for_value(value_b - target_value, current_price)?;
+            if excess_b > 0 {
+                self.swap_vault_tokens(false, excess_b, current_price, route, true)?;
+            }
         }
         
-        let target_value_each = total_value / 2;
+        self.position.load_mut()?.set_out_of_range_converted(false);
+        Ok(())
+    }
+    
+    // Run the keeper's Jupiter route between the position vaults. Rebalancing is
+    // permissionless, so the route is held to the vault deltas: it may spend at
+    // most `amount_in` and must return at least the oracle value less slippage.
+    // Returns the amount spent.
+    fn swap_vault_tokens(
+        &mut self,
+        sell_a: bool,
+        amount_in: u64,
+        current_price: u64,
+        route: &ConversionRoute<'_, 'info>,
+        reentry: bool,
+    ) -> Result<u64> {
+        require!(route.is_available(), ErrorCode::SwapRouteRequired);
+        route.used.set(true);
+        
+        // Venue CPIs earlier in the rebalance moved tokens in and out
+        self.position_token_a_vault.reload()?;
+        self.position_token_b_vault.reload()?;
+        let pre_a = self.position_token_a_vault.amount;
+        let pre_b = self.position_token_b_vault.amount;
+        
+        Position::jupiter_swap_cpi(
+            &self.position,
+            &self.jupiter_program,
+            route.accounts,
+            route.swap_data.clone(),
+        )?;
+        
+        self.position_token_a_vault.reload()?;
+        self.position_token_b_vault.reload()?;
+        let post_a = self.position_token_a_vault.amount;
+        let post_b = self.position_token_b_vault.amount;
         
-        // Determine swap direction and amount
-        if total_value_a > target_value_each {
-            // Too much token A, swap some A for B
-            let excess_a = total_value_a - target_value_each;
-            msg!("Would swap {} A for B using Jupiter", excess_a);
-        } else if total_value_b > target_value_each {
-            // Too much token B, swap some B for A
-            let excess_value_b = total_value_b - target_value_each;
-            let excess_b = excess_value_b.checked_mul(10u64.pow(9))
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(current_price)
+        let (spent, received) = if sell_a {
+            (pre_a.checked_sub(post_a), post_b.checked_sub(pre_b))
+        } else {
+            (pre_b.checked_sub(post_b), post_a.checked_sub(pre_a))
+        };
+        let spent = spent.ok_or(ErrorCode::InvalidSwapRoute)?;
+        let received = received.ok_or(ErrorCode::SlippageExceeded)?;
+        require!(spent <= amount_in, ErrorCode::InvalidSwapRoute);
+        
+        let oracle_out = if sell_a {
+            self.pair_config.amount_b_for_value(spent, current_price)?
+        } else {
+            self.pair_config.value_b_in_a(spent, current_price)?
+        };
+        let min_out = ((oracle_out as u128 * (10_000 - MAX_SLIPPAGE_BPS) as u128 / 10_000) as u64)
+            .max(route.min_swap_out);
+        require!(received >= min_out, ErrorCode::SlippageExceeded);
+        
+        let mut position = self.position.load_mut()?;
+        if sell_a {
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_sub(spent)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(received)
+                .ok_or(ErrorCode::MathOverflow)?;
+        } else {
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_sub(spent)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(received)
                 .ok_or(ErrorCode::MathOverflow)?;
-            
-            msg!("Would swap {} B for A using Jupiter", excess_b);
         }
         
-        Ok(())
+        emit!(RangeConversionEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            current_price,
+            sold_token_a: sell_a,
+            amount_in: spent,
+            amount_out: received,
+            reentry,
+        });
+        
+        msg!("Swapped {} {} for {} {}", spent, if sell_a { "A" } else { "B" }, received, if sell_a { "B" } else { "A" });
+        Ok(spent)
     }
 }
 
@@ -667,28 +1583,36 @@ impl<'info> RebalancePosition<'info> {
 pub struct WithdrawFromMeteora<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump,
-        has_one = owner
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        constraint = position.load()?.is_authorized(&authority.key()) || position.load()?.has_queued_withdrawal() @ ErrorCode::UnauthorizedOperator
     )]
-    pub position: Account<'info, Position>,
+    pub position: AccountLoader<'info, Position>,
     
     #[account(
         mut,
         constraint = position_token_a_vault.owner == position.key(),
-        constraint = position_token_a_vault.mint == position.token_a_mint
+        constraint = position_token_a_vault.mint == position.load()?.token_a_mint
     )]
     pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
         constraint = position_token_b_vault.owner == position.key(),
-        constraint = position_token_b_vault.mint == position.token_b_mint
+        constraint = position_token_b_vault.mint == position.load()?.token_b_mint
     )]
     pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
     
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
     // Meteora accounts
     /// CHECK: Meteora program
+    #[account(address = protocol_authority.external_programs.meteora_dlmm @ ErrorCode::ExternalProgramMismatch)]
     pub meteora_program: UncheckedAccount<'info>,
     /// CHECK: Meteora LB pair
     pub meteora_lb_pair: UncheckedAccount<'info>,
@@ -712,14 +1636,17 @@ pub struct WithdrawFromMeteora<'info> {
     
     pub token_a_mint: Account<'info, Mint>,
     pub token_b_mint: Account<'info, Mint>,
-    pub owner: Signer<'info>,
+    // Position owner or operator - funds only ever return to the position vaults
+    pub authority: Signer<'info>,
     pub token_program: Program<'info, Token>,
 }
 
 impl<'info> WithdrawFromMeteora<'info> {
-    pub fn withdraw_from_lp(&mut self) -> Result<()> {
-        let lp_a = self.position.token_a_in_lp;
-        let lp_b = self.position.token_b_in_lp;
+    pub fn withdraw_from_lp(&mut self, rung_accounts: &[AccountInfo<'info>]) -> Result<()> {
+        let (lp_a, lp_b, rung_count) = {
+            let position = self.position.load()?;
+            (position.token_a_in_lp, position.token_b_in_lp, position.lp_rung_count)
+        };
         
         if lp_a == 0 && lp_b == 0 {
             msg!("No funds in Meteora LP to withdraw");
@@ -728,10 +1655,45 @@ impl<'info> WithdrawFromMeteora<'info> {
         
         msg!("Withdrawing {} A and {} B from Meteora LP", lp_a, lp_b);
         
+        self.validate_venues()?;
+        let lb_pair = self.meteora_lb_pair.key();
+        
+        // Laddered positions unwind every rung using the per-rung remaining accounts
+        if rung_count > 0 {
+            for rung_index in 0..rung_count as usize {
+                if !self.position.load()?.lp_rungs[rung_index].has_liquidity() {
+                    continue;
+                }
+                
+                let (meteora_position, meteora_bin_array_lower, meteora_bin_array_upper) =
+                    rung_accounts_for(&self.position, &lb_pair, rung_accounts, rung_index, &self.meteora_program.key())?;
+                
+                Position::close_meteora_rung_cpi(
+                    &self.position,
+                    rung_index,
+                    &self.meteora_program,
+                    &self.meteora_lb_pair,
+                    meteora_position,
+                    &self.position_token_a_vault,
+                    &self.position_token_b_vault,
+                    &self.meteora_reserve_x,
+                    &self.meteora_reserve_y,
+                    &self.token_a_mint,
+                    &self.token_b_mint,
+                    meteora_bin_array_lower,
+                    meteora_bin_array_upper,
+                    &self.token_program,
+                    &self.meteora_event_authority,
+                )?;
+            }
+            
+            msg!("Successfully withdrew {} A and {} B from Meteora rungs", lp_a, lp_b);
+            return Ok(());
+        }
+        
         // Execute Meteora withdrawal
-        let position_account_info = self.position.to_account_info();
-        self.position.close_meteora_position_cpi(
-            &position_account_info,
+        Position::close_meteora_position_cpi(
+            &self.position,
             &self.meteora_program,
             &self.meteora_lb_pair,
             &self.meteora_position,
@@ -747,22 +1709,36 @@ impl<'info> WithdrawFromMeteora<'info> {
             &self.meteora_event_authority,
         )?;
         
-        // Update position state
-        let withdrawn_a = self.position.token_a_in_lp;
-        let withdrawn_b = self.position.token_b_in_lp;
-        
-        self.position.token_a_in_lp = 0;
-        self.position.token_b_in_lp = 0;
-        self.position.token_a_vault_balance = self.position.token_a_vault_balance
-            .checked_add(withdrawn_a)
-            .ok_or(ErrorCode::MathOverflow)?;
-        self.position.token_b_vault_balance = self.position.token_b_vault_balance
-            .checked_add(withdrawn_b)
-            .ok_or(ErrorCode::MathOverflow)?;
+        // The CPI helper moves the LP amounts back to the vault balances
+        msg!("Successfully withdrew {} A and {} B from Meteora", lp_a, lp_b);
+        Ok(())
+    }
+    
+    // The DLMM accounts must be the pair holding the position's liquidity
+    fn validate_venues(&self) -> Result<()> {
+        let meteora_program = self.meteora_program.key();
+        let position = self.position.load()?;
+        verify_meteora_pair(
+            &self.meteora_lb_pair,
+            Some(&self.meteora_reserve_x),
+            Some(&self.meteora_reserve_y),
+            Some(&self.meteora_event_authority),
+            &position,
+            &meteora_program,
+        )?;
         
-        self.position.meteora_position = None;
+        // Laddered positions use per-rung accounts instead
+        if !position.has_ladder() {
+            let lb_pair = self.meteora_lb_pair.key();
+            
+            if let Some(expected) = position.meteora_position() {
+                require_keys_eq!(self.meteora_position.key(), expected, ErrorCode::VenueMismatch);
+            }
+            validate_meteora_position(&self.meteora_position, &lb_pair, &self.position.key(), &meteora_program)?;
+            validate_bin_array(&self.meteora_bin_array_lower, &lb_pair, &meteora_program)?;
+            validate_bin_array(&self.meteora_bin_array_upper, &lb_pair, &meteora_program)?;
+        }
         
-        msg!("Successfully withdrew {} A and {} B from Meteora", withdrawn_a, withdrawn_b);
         Ok(())
     }
 }
@@ -771,74 +1747,120 @@ impl<'info> WithdrawFromMeteora<'info> {
 pub struct WithdrawFromKamino<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump,
-        has_one = owner
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        constraint = position.load()?.is_authorized(&authority.key()) || position.load()?.has_queued_withdrawal() @ ErrorCode::UnauthorizedOperator
     )]
-    pub position: Account<'info, Position>,
+    pub position: AccountLoader<'info, Position>,
     
     #[account(
         mut,
         constraint = position_token_a_vault.owner == position.key(),
-        constraint = position_token_a_vault.mint == position.token_a_mint
+        constraint = position_token_a_vault.mint == position.load()?.token_a_mint
     )]
     pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
         constraint = position_token_b_vault.owner == position.key(),
-        constraint = position_token_b_vault.mint == position.token_b_mint
+        constraint = position_token_b_vault.mint == position.load()?.token_b_mint
     )]
     pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
     
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
     // Kamino accounts
     /// CHECK: Kamino program
+    #[account(address = protocol_authority.external_programs.kamino_lending @ ErrorCode::ExternalProgramMismatch)]
     pub kamino_program: UncheckedAccount<'info>,
-    /// CHECK: Kamino lending market
+    /// CHECK: Kamino lending market - checked against the obligation
     pub kamino_lending_market: UncheckedAccount<'info>,
-    /// CHECK: Kamino obligation
+    /// CHECK: Kamino lending market authority - derived from the market
+    pub kamino_lending_market_authority: UncheckedAccount<'info>,
+    /// CHECK: Kamino obligation - must be the position's recorded obligation
     #[account(mut)]
     pub kamino_obligation: UncheckedAccount<'info>,
-    /// CHECK: Kamino reserves
+    /// CHECK: Kamino reserves - checked against the market and position mints
     #[account(mut)]
     pub kamino_reserve_a: UncheckedAccount<'info>,
-    /// CHECK: Kamino reserves
+    /// CHECK: Kamino reserves - checked against the market and position mints
     #[account(mut)]
     pub kamino_reserve_b: UncheckedAccount<'info>,
+    /// CHECK: Reserve vaults and collateral mints - derived from each reserve
+    #[account(mut)]
+    pub kamino_reserve_a_liquidity_supply: UncheckedAccount<'info>,
+    /// CHECK: Derived from reserve A
+    #[account(mut)]
+    pub kamino_reserve_a_collateral_mint: UncheckedAccount<'info>,
+    /// CHECK: Derived from reserve A
+    #[account(mut)]
+    pub kamino_reserve_a_collateral_supply: UncheckedAccount<'info>,
+    /// CHECK: Derived from reserve B
+    #[account(mut)]
+    pub kamino_reserve_b_liquidity_supply: UncheckedAccount<'info>,
+    /// CHECK: Derived from reserve B
+    #[account(mut)]
+    pub kamino_reserve_b_collateral_mint: UncheckedAccount<'info>,
+    /// CHECK: Derived from reserve B
+    #[account(mut)]
+    pub kamino_reserve_b_collateral_supply: UncheckedAccount<'info>,
+    /// CHECK: Position collateral (cToken) accounts - checked against the reserve collateral mints
+    #[account(mut)]
+    pub position_collateral_a: UncheckedAccount<'info>,
+    /// CHECK: Position collateral (cToken) accounts - checked against the reserve collateral mints
+    #[account(mut)]
+    pub position_collateral_b: UncheckedAccount<'info>,
     
-    pub owner: Signer<'info>,
+    // Position owner or operator, or any keeper while a withdrawal is
+    // queued - funds only ever return to the position vaults
+    pub authority: Signer<'info>,
     pub clock: Sysvar<'info, Clock>,
     pub token_program: Program<'info, Token>,
 }
 
 impl<'info> WithdrawFromKamino<'info> {
     pub fn withdraw_from_lending(&mut self) -> Result<()> {
-        let lending_a = self.position.token_a_in_lending;
-        let lending_b = self.position.token_b_in_lending;
-        
-        if lending_a == 0 && lending_b == 0 {
+        let (in_lending_a, in_lending_b) = {
+            let position = self.position.load()?;
+            (position.token_a_in_lending, position.token_b_in_lending)
+        };
+        if in_lending_a == 0 && in_lending_b == 0 {
             msg!("No funds in Kamino lending to withdraw");
             return Ok(());
         }
         
+        self.validate_venues()?;
+        
+        // A highly utilized reserve only pays out what borrowers haven't taken;
+        // the rest stays lent and can be pulled on a later call
+        let kamino_program = self.kamino_program.key();
+        let lending_a = in_lending_a
+            .min(read_reserve_available_liquidity(&self.kamino_reserve_a, &kamino_program)?);
+        let lending_b = in_lending_b
+            .min(read_reserve_available_liquidity(&self.kamino_reserve_b, &kamino_program)?);
+        
         msg!("Withdrawing {} A and {} B from Kamino lending", lending_a, lending_b);
         
         let position_account_info = self.position.to_account_info();
         
         // Withdraw token A from Kamino
         if lending_a > 0 {
-            let source_collateral_a = self.kamino_reserve_a.to_account_info();
-            
-            self.position.withdraw_from_kamino_cpi(
-                &position_account_info,
+            let received_a = Position::withdraw_from_kamino_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
-                &source_collateral_a,
+                &self.position_collateral_a,
                 &self.position_token_a_vault,
                 &self.kamino_reserve_a,
-                &self.kamino_reserve_a, // Reserve liquidity supply
-                &self.kamino_reserve_a, // Reserve collateral mint
+                &self.kamino_reserve_a_liquidity_supply,
+                &self.kamino_reserve_a_collateral_mint,
+                &self.kamino_reserve_a_collateral_supply,
                 &self.kamino_lending_market,
-                &self.kamino_lending_market, // Market authority (derived)
+                &self.kamino_lending_market_authority,
                 &self.kamino_obligation,
                 &position_account_info,
                 &self.clock,
@@ -846,26 +1868,26 @@ impl<'info> WithdrawFromKamino<'info> {
                 lending_a,
             )?;
             
-            self.position.token_a_in_lending = 0;
-            self.position.token_a_vault_balance = self.position.token_a_vault_balance
-                .checked_add(lending_a)
+            let mut position = self.position.load_mut()?;
+            position.token_a_in_lending -= lending_a;
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(received_a)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         
         // Withdraw token B from Kamino
         if lending_b > 0 {
-            let source_collateral_b = self.kamino_reserve_b.to_account_info();
-            
-            self.position.withdraw_from_kamino_cpi(
-                &position_account_info,
+            let received_b = Position::withdraw_from_kamino_cpi(
+                &self.position,
                 &self.kamino_program.to_account_info(),
-                &source_collateral_b,
+                &self.position_collateral_b,
                 &self.position_token_b_vault,
                 &self.kamino_reserve_b,
-                &self.kamino_reserve_b, // Reserve liquidity supply
-                &self.kamino_reserve_b, // Reserve collateral mint
+                &self.kamino_reserve_b_liquidity_supply,
+                &self.kamino_reserve_b_collateral_mint,
+                &self.kamino_reserve_b_collateral_supply,
                 &self.kamino_lending_market,
-                &self.kamino_lending_market, // Market authority (derived)
+                &self.kamino_lending_market_authority,
                 &self.kamino_obligation,
                 &position_account_info,
                 &self.clock,
@@ -873,17 +1895,302 @@ impl<'info> WithdrawFromKamino<'info> {
                 lending_b,
             )?;
             
-            self.position.token_b_in_lending = 0;
-            self.position.token_b_vault_balance = self.position.token_b_vault_balance
-                .checked_add(lending_b)
+            let mut position = self.position.load_mut()?;
+            position.token_b_in_lending -= lending_b;
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(received_b)
                 .ok_or(ErrorCode::MathOverflow)?;
         }
         
-        self.position.kamino_obligation = None;
+        let mut position = self.position.load_mut()?;
+        if position.token_a_in_lending == 0 && position.token_b_in_lending == 0 {
+            // The hedge's collateral and debt stay in the obligation
+            if !position.has_open_hedge() {
+                position.set_kamino_obligation(None);
+            }
+        } else {
+            msg!(
+                "Reserve liquidity short, {} A and {} B left in Kamino",
+                position.token_a_in_lending, position.token_b_in_lending
+            );
+        }
         
         msg!("Successfully withdrew {} A and {} B from Kamino", lending_a, lending_b);
         Ok(())
     }
+    
+    // The obligation must be the position's own; reserves and vaults must hang off its market
+    fn validate_venues(&self) -> Result<()> {
+        let position_key = self.position.key();
+        let lending_market = self.kamino_lending_market.key();
+        let kamino_program = self.kamino_program.key();
+        let position = self.position.load()?;
+        
+        let obligation = position.kamino_obligation()
+            .ok_or(ErrorCode::LendingPositionNotFound)?;
+        require_keys_eq!(self.kamino_obligation.key(), obligation, ErrorCode::VenueMismatch);
+        validate_kamino_obligation(&self.kamino_obligation, &lending_market, &position_key, &kamino_program)?;
+        
+        validate_kamino_lending_market(&self.kamino_lending_market, &kamino_program)?;
+        require_keys_eq!(
+            self.kamino_lending_market_authority.key(),
+            kamino_market_authority(&lending_market, &kamino_program),
+            ErrorCode::InvalidDerivedAccount
+        );
+        
+        verify_kamino_reserve(
+            &self.kamino_reserve_a,
+            &lending_market,
+            &position.token_a_mint,
+            Some(&self.kamino_reserve_a_liquidity_supply),
+            Some(&self.kamino_reserve_a_collateral_mint),
+            Some(&self.kamino_reserve_a_collateral_supply),
+            None,
+            Some(&self.position_collateral_a),
+            &position_key,
+            &kamino_program,
+        )?;
+        verify_kamino_reserve(
+            &self.kamino_reserve_b,
+            &lending_market,
+            &position.token_b_mint,
+            Some(&self.kamino_reserve_b_liquidity_supply),
+            Some(&self.kamino_reserve_b_collateral_mint),
+            Some(&self.kamino_reserve_b_collateral_supply),
+            None,
+            Some(&self.position_collateral_b),
+            &position_key,
+            &kamino_program,
+        )
+    }
+}
+
+// Per-rung actions for a laddered rebalance
+pub struct LadderPlan {
+    pub close: [bool; MAX_LP_RUNGS],
+    pub deploy: [bool; MAX_LP_RUNGS],
+}
+
+impl LadderPlan {
+    pub fn is_empty(&self) -> bool {
+        !self.close.iter().any(|c| *c) && !self.deploy.iter().any(|d| *d)
+    }
+}
+
+// Decide which rungs to close and which to (re)deploy at the current price.
+// A rung is live while the price sits inside its range. Live rungs are
+// re-weighted when their share of live LP value drifts past the threshold.
+pub fn plan_ladder(position: &Position, pair: &PairConfig, in_range: bool, current_price: u64) -> Result<LadderPlan> {
+    let mut plan = LadderPlan {
+        close: [false; MAX_LP_RUNGS],
+        deploy: [false; MAX_LP_RUNGS],
+    };
+    let rungs = position.active_rungs();
+    let live = |rung: &LpRung| in_range && rung.contains(current_price);
+    
+    let live_weight: u64 = rungs.iter()
+        .filter(|r| live(r))
+        .map(|r| r.weight_bps as u64)
+        .sum();
+    
+    let mut live_value: u64 = 0;
+    for rung in rungs.iter().filter(|r| live(r)) {
+        live_value = live_value
+            .checked_add(rung_value(rung, pair, current_price)?)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    
+    for (i, rung) in rungs.iter().enumerate() {
+        if !live(rung) {
+            plan.close[i] = rung.has_liquidity();
+            continue;
+        }
+        
+        if !rung.has_liquidity() {
+            plan.deploy[i] = true;
+            continue;
+        }
+        
+        if live_value > 0 && live_weight > 0 {
+            let target_bps = (rung.weight_bps as u64) * 10_000 / live_weight;
+            let actual_bps = ((rung_value(rung, pair, current_price)? as u128) * 10_000
+                / live_value as u128) as u64;
+            
+            if target_bps.abs_diff(actual_bps) > RUNG_REWEIGHT_THRESHOLD_BPS as u64 {
+                plan.close[i] = true;
+                plan.deploy[i] = true;
+            }
+        }
+    }
+    
+    // With no rung to (re)open, idle funds top up every live rung
+    let has_idle = position.token_a_vault_balance > 0 || position.token_b_vault_balance > 0;
+    if has_idle && !plan.deploy.iter().any(|d| *d) {
+        for (i, rung) in rungs.iter().enumerate() {
+            plan.deploy[i] = live(rung);
+        }
+    }
+    
+    Ok(plan)
+}
+
+// Value of a rung's liquidity in token A terms
+fn rung_value(rung: &LpRung, pair: &PairConfig, current_price: u64) -> Result<u64> {
+    let value_b = pair.value_b_in_a(rung.token_b_in_lp, current_price)?;
+    
+    rung.token_a_in_lp.checked_add(value_b).ok_or(ErrorCode::MathOverflow.into())
+}
+
+// Current pair price and confidence, normalized to PRICE_DECIMALS
+pub fn get_pair_price(price_update: &PriceUpdateV2, pair: &PairConfig, maximum_age: u64) -> Result<(u64, u64)> {
+    let price_data = price_update.get_price_no_older_than(
+        &Clock::get()?,
+        maximum_age,
+        &pair.price_feed_id
+    )?;
+    
+    let current_price = normalize_pyth_price(
+        price_data.price,
+        price_data.exponent,
+        PRICE_DECIMALS as u8
+    )?;
+    
+    let confidence = normalize_pyth_price(
+        price_data.conf as i64,
+        price_data.exponent,
+        PRICE_DECIMALS as u8
+    )?;
+    
+    Ok((current_price, confidence))
+}
+
+// Meteora accounts for a rung: [position, bin_array_lower, bin_array_upper]
+fn rung_accounts_for<'a, 'info>(
+    position: &AccountLoader<'info, Position>,
+    lb_pair: &Pubkey,
+    rung_accounts: &'a [AccountInfo<'info>],
+    rung_index: usize,
+    meteora_program: &Pubkey,
+) -> Result<(&'a AccountInfo<'info>, &'a AccountInfo<'info>, &'a AccountInfo<'info>)> {
+    let base = rung_index * LP_RUNG_ACCOUNTS;
+    require!(
+        rung_accounts.len() >= base + LP_RUNG_ACCOUNTS,
+        ErrorCode::RungAccountsMismatch
+    );
+    
+    let meteora_position = &rung_accounts[base];
+    if let Some(expected) = position.load()?.lp_rungs[rung_index].meteora_position() {
+        require_keys_eq!(meteora_position.key(), expected, ErrorCode::RungAccountsMismatch);
+    }
+    validate_meteora_position(meteora_position, lb_pair, &position.key(), meteora_program)?;
+    validate_bin_array(&rung_accounts[base + 1], lb_pair, meteora_program)?;
+    validate_bin_array(&rung_accounts[base + 2], lb_pair, meteora_program)?;
+    
+    Ok((meteora_position, &rung_accounts[base + 1], &rung_accounts[base + 2]))
+}
+
+// DLMM pair must trade the position's mints; reserves and event authority
+// must be the ones the pair and program actually use
+fn verify_meteora_pair(
+    lb_pair: &AccountInfo,
+    reserve_x: Option<&AccountInfo>,
+    reserve_y: Option<&AccountInfo>,
+    event_authority: Option<&AccountInfo>,
+    position: &Position,
+    meteora_program: &Pubkey,
+) -> Result<()> {
+    let pair = read_lb_pair(lb_pair, meteora_program)?;
+    require_keys_eq!(pair.token_x_mint, position.token_a_mint, ErrorCode::VenueMismatch);
+    require_keys_eq!(pair.token_y_mint, position.token_b_mint, ErrorCode::VenueMismatch);
+    
+    if let Some(reserve_x) = reserve_x {
+        require_keys_eq!(reserve_x.key(), pair.reserve_x, ErrorCode::VenueMismatch);
+    }
+    if let Some(reserve_y) = reserve_y {
+        require_keys_eq!(reserve_y.key(), pair.reserve_y, ErrorCode::VenueMismatch);
+    }
+    if let Some(event_authority) = event_authority {
+        require_keys_eq!(event_authority.key(), meteora_event_authority(meteora_program), ErrorCode::InvalidDerivedAccount);
+    }
+    
+    Ok(())
+}
+
+// An obligation recorded on the position must be the one passed. A fresh
+// (empty) account is allowed until the deposit flow initializes it.
+fn verify_kamino_obligation(
+    obligation: &AccountInfo,
+    position: &Position,
+    position_key: &Pubkey,
+    lending_market: &Pubkey,
+    kamino_program: &Pubkey,
+) -> Result<()> {
+    if let Some(expected) = position.kamino_obligation() {
+        require_keys_eq!(obligation.key(), expected, ErrorCode::VenueMismatch);
+    } else if obligation.data_is_empty() {
+        return Ok(());
+    }
+    
+    validate_kamino_obligation(obligation, lending_market, position_key, kamino_program)
+}
+
+// Reserve must sit on the market and lend `mint`. Its vaults and collateral
+// mint are klend PDAs of the reserve, and the position's collateral account
+// must hold that collateral mint.
+fn verify_kamino_reserve<'info>(
+    reserve: &AccountInfo<'info>,
+    lending_market: &Pubkey,
+    mint: &Pubkey,
+    liquidity_supply: Option<&AccountInfo<'info>>,
+    collateral_mint: Option<&AccountInfo<'info>>,
+    collateral_supply: Option<&AccountInfo<'info>>,
+    fee_receiver: Option<&AccountInfo<'info>>,
+    position_collateral: Option<&AccountInfo<'info>>,
+    position_key: &Pubkey,
+    kamino_program: &Pubkey,
+) -> Result<()> {
+    validate_kamino_reserve(reserve, lending_market, mint, kamino_program)?;
+    
+    let pdas = kamino_reserve_pdas(&reserve.key(), kamino_program);
+    for (account, expected) in [
+        (liquidity_supply, pdas.liquidity_supply),
+        (collateral_mint, pdas.collateral_mint),
+        (collateral_supply, pdas.collateral_supply),
+        (fee_receiver, pdas.fee_receiver),
+    ] {
+        if let Some(account) = account {
+            require_keys_eq!(account.key(), expected, ErrorCode::InvalidDerivedAccount);
+        }
+    }
+    
+    if let Some(position_collateral) = position_collateral {
+        require_keys_eq!(*position_collateral.owner, anchor_spl::token::ID, ErrorCode::InvalidAccountData);
+        let collateral = TokenAccount::try_deserialize(&mut &position_collateral.data.borrow()[..])
+            .map_err(|_| ErrorCode::InvalidAccountData)?;
+        require_keys_eq!(collateral.mint, pdas.collateral_mint, ErrorCode::VenueMismatch);
+        require_keys_eq!(collateral.owner, *position_key, ErrorCode::VenueAccountNotOwned);
+    }
+    
+    Ok(())
+}
+
+// Reserves klend expects when refreshing the obligation: deposits, then borrows
+fn hedge_obligation_reserves<'a, 'info>(
+    position: &Position,
+    reserve_a: &'a AccountInfo<'info>,
+    reserve_b: &'a AccountInfo<'info>,
+) -> Vec<&'a AccountInfo<'info>> {
+    let mut reserves = Vec::with_capacity(3);
+    if position.token_a_in_lending > 0 || position.hedge_collateral_a > 0 {
+        reserves.push(reserve_a);
+    }
+    if position.token_b_in_lending > 0 {
+        reserves.push(reserve_b);
+    }
+    if position.hedge_borrowed_b > 0 {
+        reserves.push(reserve_b);
+    }
+    reserves
 }
 
 // Helper function to normalize Pyth prices to target decimals
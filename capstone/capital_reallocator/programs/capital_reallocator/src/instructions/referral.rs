@@ -0,0 +1,198 @@
+// instructions/referral.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Transfer};
+use anchor_spl::associated_token::get_associated_token_address;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::ReferralClaimedEvent;
+use crate::constants::*;
+
+// Create or update a referral tier (protocol admin)
+#[derive(Accounts)]
+#[instruction(tier: u8)]
+pub struct SetReferralTier<'info> {
+    #[account(
+        init_if_needed,
+        payer = admin,
+        space = 8 + ReferralTier::INIT_SPACE,
+        seeds = [REFERRAL_TIER_SEED, &[tier]],
+        bump
+    )]
+    pub referral_tier: Box<Account<'info, ReferralTier>>,
+
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> SetReferralTier<'info> {
+    pub fn set_tier(&mut self, tier: u8, fee_share_bps: u16, bumps: &SetReferralTierBumps) -> Result<()> {
+        require!(fee_share_bps <= MAX_REFERRAL_SHARE_BPS, ErrorCode::InvalidReferralShare);
+
+        self.referral_tier.set_inner(ReferralTier {
+            version: REFERRAL_TIER_VERSION,
+            tier,
+            fee_share_bps,
+            bump: bumps.referral_tier,
+            reserved: [0; REFERRAL_TIER_RESERVED_BYTES],
+        });
+
+        msg!("Referral tier {} pays {} bps of the deposit fee", tier, fee_share_bps);
+        Ok(())
+    }
+}
+
+// Register the signer as a referrer (starts on tier 0)
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + Referrer::INIT_SPACE,
+        seeds = [REFERRER_SEED, owner.key().as_ref()],
+        bump
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> RegisterReferrer<'info> {
+    pub fn register(&mut self, bumps: &RegisterReferrerBumps) -> Result<()> {
+        self.referrer.set_inner(Referrer {
+            version: REFERRER_VERSION,
+            owner: self.owner.key(),
+            tier: 0,
+            referred_users: 0,
+            bump: bumps.referrer,
+            reserved: [0; REFERRER_RESERVED_BYTES],
+        });
+
+        msg!("Referrer registered for: {}", self.owner.key());
+        Ok(())
+    }
+}
+
+// Move a referrer onto another tier (protocol admin)
+#[derive(Accounts)]
+pub struct SetReferrerTier<'info> {
+    #[account(
+        mut,
+        seeds = [REFERRER_SEED, referrer.owner.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+
+    // Must exist, so referrers can only be moved onto configured tiers
+    #[account(
+        seeds = [REFERRAL_TIER_SEED, &[referral_tier.tier]],
+        bump = referral_tier.bump
+    )]
+    pub referral_tier: Box<Account<'info, ReferralTier>>,
+
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump,
+        has_one = admin @ ErrorCode::UnauthorizedAdmin
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+
+    pub admin: Signer<'info>,
+}
+
+impl<'info> SetReferrerTier<'info> {
+    pub fn set_tier(&mut self) -> Result<()> {
+        self.referrer.tier = self.referral_tier.tier;
+
+        msg!("Referrer {} moved to tier {}", self.referrer.owner, self.referrer.tier);
+        Ok(())
+    }
+}
+
+// Claim accrued referral fees for one mint
+#[derive(Accounts)]
+pub struct ClaimReferralFees<'info> {
+    #[account(
+        seeds = [REFERRER_SEED, owner.key().as_ref()],
+        bump = referrer.bump,
+        has_one = owner
+    )]
+    pub referrer: Box<Account<'info, Referrer>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = referrer
+    )]
+    pub referrer_token_account: Box<Account<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = owner_token_account.owner == owner.key(),
+        constraint = owner_token_account.mint == mint.key()
+    )]
+    pub owner_token_account: Box<Account<'info, TokenAccount>>,
+
+    pub mint: Account<'info, Mint>,
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ClaimReferralFees<'info> {
+    pub fn claim(&mut self) -> Result<()> {
+        let amount = self.referrer_token_account.amount;
+        require!(amount > 0, ErrorCode::NothingToClaim);
+
+        let owner_key = self.owner.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            REFERRER_SEED,
+            owner_key.as_ref(),
+            &[self.referrer.bump],
+        ]];
+
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: self.referrer_token_account.to_account_info(),
+                    to: self.owner_token_account.to_account_info(),
+                    authority: self.referrer.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )?;
+
+        emit!(ReferralClaimedEvent {
+            referrer: self.referrer.key(),
+            owner: owner_key,
+            mint: self.mint.key(),
+            amount,
+        });
+
+        msg!("Claimed {} referral fees", amount);
+        Ok(())
+    }
+}
+
+// Fee shares are only claimable from the referrer PDA's associated token
+// accounts, so deposits may only pay into those
+pub(crate) fn check_referrer_fee_account(referrer: &Pubkey, token_account: &Account<TokenAccount>) -> Result<()> {
+    require_keys_eq!(
+        token_account.key(),
+        get_associated_token_address(referrer, &token_account.mint),
+        ErrorCode::InvalidReferralAccounts
+    );
+    Ok(())
+}
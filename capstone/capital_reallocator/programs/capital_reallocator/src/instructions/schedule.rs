@@ -0,0 +1,494 @@
+// instructions/schedule.rs
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, Token, TokenAccount, Mint, Approve, Revoke, Transfer};
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::*;
+use crate::constants::*;
+use crate::instructions::rebalance::get_pair_price;
+use crate::instructions::referral::check_referrer_fee_account;
+
+// Create a recurring deposit schedule
+#[derive(Accounts)]
+pub struct CreateDepositSchedule<'info> {
+    #[account(
+        init,
+        payer = owner,
+        space = 8 + DepositSchedule::INIT_SPACE,
+        seeds = [SCHEDULE_SEED, position.key().as_ref()],
+        bump
+    )]
+    pub deposit_schedule: Box<Account<'info, DepositSchedule>>,
+    
+    #[account(
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        has_one = owner
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        mut,
+        constraint = user_token_a.owner == owner.key(),
+        constraint = user_token_a.mint == position.load()?.token_a_mint
+    )]
+    pub user_token_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = user_token_b.owner == owner.key(),
+        constraint = user_token_b.mint == position.load()?.token_b_mint
+    )]
+    pub user_token_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CreateDepositSchedule<'info> {
+    pub fn create_schedule(
+        &mut self,
+        amount_a: u64,
+        amount_b: u64,
+        interval_seconds: i64,
+        end_ts: i64,
+        bumps: &CreateDepositScheduleBumps,
+    ) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        let next_execution_ts = now
+            .checked_add(interval_seconds)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        require!(
+            (amount_a > 0 || amount_b > 0) &&
+            interval_seconds >= MIN_SCHEDULE_INTERVAL &&
+            end_ts >= next_execution_ts,
+            ErrorCode::InvalidSchedule
+        );
+        
+        // Approve the schedule PDA for every run up to end_ts.
+        // This replaces any existing delegate on the owner's token accounts.
+        let runs = ((end_ts - next_execution_ts) / interval_seconds) as u64 + 1;
+        
+        if amount_a > 0 {
+            self.approve(&self.user_token_a, amount_a.checked_mul(runs).ok_or(ErrorCode::MathOverflow)?)?;
+        }
+        if amount_b > 0 {
+            self.approve(&self.user_token_b, amount_b.checked_mul(runs).ok_or(ErrorCode::MathOverflow)?)?;
+        }
+        
+        self.deposit_schedule.set_inner(DepositSchedule {
+            version: DEPOSIT_SCHEDULE_VERSION,
+            owner: self.owner.key(),
+            position: self.position.key(),
+            amount_a,
+            amount_b,
+            interval_seconds,
+            next_execution_ts,
+            end_ts,
+            executions: 0,
+            bump: bumps.deposit_schedule,
+            reserved: [0; DEPOSIT_SCHEDULE_RESERVED_BYTES],
+        });
+        
+        msg!(
+            "Deposit schedule created for position {}: {} A / {} B every {}s until {}",
+            self.position.load()?.position_id, amount_a, amount_b, interval_seconds, end_ts
+        );
+        Ok(())
+    }
+    
+    fn approve(&self, token_account: &Account<'info, TokenAccount>, amount: u64) -> Result<()> {
+        token::approve(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                Approve {
+                    to: token_account.to_account_info(),
+                    delegate: self.deposit_schedule.to_account_info(),
+                    authority: self.owner.to_account_info(),
+                },
+            ),
+            amount,
+        )
+    }
+}
+
+// Execute a due scheduled deposit (permissionless crank)
+#[derive(Accounts)]
+pub struct ExecuteScheduledDeposit<'info> {
+    #[account(
+        mut,
+        seeds = [SCHEDULE_SEED, position.key().as_ref()],
+        bump = deposit_schedule.bump,
+        has_one = position,
+        has_one = owner
+    )]
+    pub deposit_schedule: Box<Account<'info, DepositSchedule>>,
+    
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_paused() @ ErrorCode::PositionPaused,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        has_one = owner,
+        has_one = token_a_mint,
+        has_one = token_b_mint,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    #[account(
+        seeds = [PAIR_SEED, position.load()?.token_a_mint.as_ref(), position.load()?.token_b_mint.as_ref()],
+        bump = pair_config.bump,
+        constraint = pair_config.enabled @ ErrorCode::PairNotSupported
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+    
+    #[account(
+        mut,
+        constraint = user_token_a.owner == owner.key(),
+        constraint = user_token_a.mint == position.load()?.token_a_mint
+    )]
+    pub user_token_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = user_token_b.owner == owner.key(),
+        constraint = user_token_b.mint == position.load()?.token_b_mint
+    )]
+    pub user_token_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, token_a_mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&token_a_mint.key())?.bump
+    )]
+    pub fee_vault_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, token_b_mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&token_b_mint.key())?.bump
+    )]
+    pub fee_vault_b: Box<Account<'info, TokenAccount>>,
+    
+    // Required while the protocol routes a share of fees to insurance
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, token_a_mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&token_a_mint.key())?.bump
+    )]
+    pub insurance_vault_a: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, token_b_mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&token_b_mint.key())?.bump
+    )]
+    pub insurance_vault_b: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [USER_SEED, owner.key().as_ref()],
+        bump = user_main_account.bump
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+    
+    // Optional oracle price; without it only token A counts toward fee tier volume
+    pub price_update: Option<Account<'info, PriceUpdateV2>>,
+    
+    // Required when the owner has a recorded referrer: the referrer, its
+    // tier and the referrer PDA's token accounts
+    #[account(
+        seeds = [REFERRER_SEED, referrer.owner.as_ref()],
+        bump = referrer.bump
+    )]
+    pub referrer: Option<Box<Account<'info, Referrer>>>,
+    
+    #[account(
+        seeds = [REFERRAL_TIER_SEED, &[referral_tier.tier]],
+        bump = referral_tier.bump
+    )]
+    pub referral_tier: Option<Box<Account<'info, ReferralTier>>>,
+    
+    #[account(mut, constraint = referrer_token_a.mint == position.load()?.token_a_mint)]
+    pub referrer_token_a: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(mut, constraint = referrer_token_b.mint == position.load()?.token_b_mint)]
+    pub referrer_token_b: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// CHECK: Position owner, validated against the schedule and position
+    pub owner: UncheckedAccount<'info>,
+    
+    pub keeper: Signer<'info>,
+    pub token_a_mint: Account<'info, Mint>,
+    pub token_b_mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> ExecuteScheduledDeposit<'info> {
+    pub fn execute(&mut self) -> Result<()> {
+        let now = Clock::get()?.unix_timestamp;
+        
+        require!(now <= self.deposit_schedule.end_ts, ErrorCode::ScheduleExpired);
+        require!(now >= self.deposit_schedule.next_execution_ts, ErrorCode::ScheduleNotDue);
+        
+        let amount_a = self.deposit_schedule.amount_a;
+        let amount_b = self.deposit_schedule.amount_b;
+        
+        // Calculate fees at the owner's volume tier
+        let applied_fee = self.protocol_authority.fee_for_volume(self.user_main_account.cumulative_deposit_value);
+        let fee_a = ProtocolAuthority::fee_at(amount_a, applied_fee.deposit_fee_bps)?;
+        let fee_b = ProtocolAuthority::fee_at(amount_b, applied_fee.deposit_fee_bps)?;
+        
+        let deposit_a = amount_a.checked_sub(fee_a).ok_or(ErrorCode::MathOverflow)?;
+        let deposit_b = amount_b.checked_sub(fee_b).ok_or(ErrorCode::MathOverflow)?;
+        
+        {
+            let position = self.position.load()?;
+            self.pair_config.check_position_size(
+                position.total_token_a().saturating_add(deposit_a),
+                position.total_token_b().saturating_add(deposit_b),
+            )?;
+        }
+        
+        let referral_tier = self.referral_tier()?;
+        let (referral_fee_a, referral_fee_b) = match referral_tier.as_ref() {
+            Some(tier) => (tier.referral_fee(fee_a)?, tier.referral_fee(fee_b)?),
+            None => (0, 0),
+        };
+        
+        if amount_a > 0 {
+            self.transfer_as_delegate(&self.user_token_a, &self.position_token_a_vault, deposit_a)?;
+            let (protocol_fee_a, insurance_fee_a) = self.protocol_authority.insurance_split(fee_a - referral_fee_a)?;
+            self.transfer_as_delegate(&self.user_token_a, &self.fee_vault_a, protocol_fee_a)?;
+            self.protocol_authority.record_fee(&self.token_a_mint.key(), protocol_fee_a)?;
+            if insurance_fee_a > 0 {
+                let insurance_vault = self.insurance_vault_a.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+                self.transfer_as_delegate(&self.user_token_a, insurance_vault, insurance_fee_a)?;
+                self.protocol_authority.record_insurance(&self.token_a_mint.key(), insurance_fee_a)?;
+            }
+            if referral_fee_a > 0 {
+                let referrer_token = self.referrer_token_a.as_ref().ok_or(ErrorCode::InvalidReferralAccounts)?;
+                self.transfer_as_delegate(&self.user_token_a, referrer_token, referral_fee_a)?;
+            }
+            
+            let mut position = self.position.load_mut()?;
+            position.token_a_vault_balance = position.token_a_vault_balance
+                .checked_add(deposit_a)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.net_deposit_a = position.net_deposit_a
+                .checked_add(deposit_a)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        if amount_b > 0 {
+            self.transfer_as_delegate(&self.user_token_b, &self.position_token_b_vault, deposit_b)?;
+            let (protocol_fee_b, insurance_fee_b) = self.protocol_authority.insurance_split(fee_b - referral_fee_b)?;
+            self.transfer_as_delegate(&self.user_token_b, &self.fee_vault_b, protocol_fee_b)?;
+            self.protocol_authority.record_fee(&self.token_b_mint.key(), protocol_fee_b)?;
+            if insurance_fee_b > 0 {
+                let insurance_vault = self.insurance_vault_b.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+                self.transfer_as_delegate(&self.user_token_b, insurance_vault, insurance_fee_b)?;
+                self.protocol_authority.record_insurance(&self.token_b_mint.key(), insurance_fee_b)?;
+            }
+            if referral_fee_b > 0 {
+                let referrer_token = self.referrer_token_b.as_ref().ok_or(ErrorCode::InvalidReferralAccounts)?;
+                self.transfer_as_delegate(&self.user_token_b, referrer_token, referral_fee_b)?;
+            }
+            
+            let mut position = self.position.load_mut()?;
+            position.token_b_vault_balance = position.token_b_vault_balance
+                .checked_add(deposit_b)
+                .ok_or(ErrorCode::MathOverflow)?;
+            position.net_deposit_b = position.net_deposit_b
+                .checked_add(deposit_b)
+                .ok_or(ErrorCode::MathOverflow)?;
+        }
+        
+        // Missed intervals are not made up - the next run is one interval from now
+        self.deposit_schedule.next_execution_ts = now
+            .checked_add(self.deposit_schedule.interval_seconds)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.deposit_schedule.executions += 1;
+        
+        // Volume credit towards fee tiers
+        let price = match self.price_update.as_ref() {
+            Some(price_update) => Some(get_pair_price(price_update, &self.pair_config, PRICE_MAX_AGE)?.0),
+            None => None,
+        };
+        let volume = self.pair_config.deposit_volume(amount_a, amount_b, price)?;
+        self.user_main_account.record_deposit_volume(volume);
+        
+        let position = self.position.load()?;
+        emit!(DepositEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            amount_a: deposit_a,
+            amount_b: deposit_b,
+            fee_a,
+            fee_b,
+            fee_tier: applied_fee.tier,
+            fee_bps: applied_fee.deposit_fee_bps,
+        });
+        
+        emit!(ScheduledDepositEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            schedule: self.deposit_schedule.key(),
+            keeper: self.keeper.key(),
+            amount_a: deposit_a,
+            amount_b: deposit_b,
+            fee_a,
+            fee_b,
+            execution: self.deposit_schedule.executions,
+        });
+        
+        if let (Some(referrer), Some(tier)) = (self.referrer.as_ref(), referral_tier) {
+            if referral_fee_a > 0 || referral_fee_b > 0 {
+                emit!(ReferralFeeEvent {
+                    referrer: referrer.key(),
+                    user: position.owner,
+                    position_id: position.position_id,
+                    tier: tier.tier,
+                    amount_a: referral_fee_a,
+                    amount_b: referral_fee_b,
+                });
+            }
+        }
+        
+        msg!(
+            "Scheduled deposit #{} executed: {} token A and {} token B (fees: {}, {})",
+            self.deposit_schedule.executions, deposit_a, deposit_b, fee_a, fee_b
+        );
+        Ok(())
+    }
+    
+    // Scheduled deposits pay the referrer recorded on the owner's first
+    // deposit; they never record one
+    fn referral_tier(&self) -> Result<Option<ReferralTier>> {
+        let Some(recorded) = self.user_main_account.referrer else {
+            return Ok(None);
+        };
+        let referrer = self.referrer.as_ref().ok_or(ErrorCode::ReferrerMismatch)?;
+        require_keys_eq!(referrer.key(), recorded, ErrorCode::ReferrerMismatch);
+        
+        let tier = self.referral_tier.as_ref().ok_or(ErrorCode::InvalidReferralAccounts)?;
+        require!(tier.tier == referrer.tier, ErrorCode::InvalidReferralAccounts);
+        
+        for token_account in [&self.referrer_token_a, &self.referrer_token_b].into_iter().flatten() {
+            check_referrer_fee_account(&referrer.key(), token_account)?;
+        }
+        
+        Ok(Some((***tier).clone()))
+    }
+    
+    fn transfer_as_delegate(
+        &self,
+        from: &Account<'info, TokenAccount>,
+        to: &Account<'info, TokenAccount>,
+        amount: u64,
+    ) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        
+        let position_key = self.position.key();
+        let signer_seeds: &[&[&[u8]]] = &[&[
+            SCHEDULE_SEED,
+            position_key.as_ref(),
+            &[self.deposit_schedule.bump],
+        ]];
+        
+        token::transfer(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                Transfer {
+                    from: from.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: self.deposit_schedule.to_account_info(),
+                },
+                signer_seeds,
+            ),
+            amount,
+        )
+    }
+}
+
+// Cancel a deposit schedule and revoke its delegation
+#[derive(Accounts)]
+pub struct CancelDepositSchedule<'info> {
+    #[account(
+        mut,
+        close = owner,
+        seeds = [SCHEDULE_SEED, deposit_schedule.position.as_ref()],
+        bump = deposit_schedule.bump,
+        has_one = owner
+    )]
+    pub deposit_schedule: Box<Account<'info, DepositSchedule>>,
+    
+    #[account(
+        mut,
+        constraint = user_token_a.owner == owner.key()
+    )]
+    pub user_token_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        constraint = user_token_b.owner == owner.key()
+    )]
+    pub user_token_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+    
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> CancelDepositSchedule<'info> {
+    pub fn cancel_schedule(&mut self) -> Result<()> {
+        // Only revoke approvals that still belong to this schedule
+        for token_account in [&self.user_token_a, &self.user_token_b] {
+            if token_account.delegate == Some(self.deposit_schedule.key()).into() {
+                token::revoke(CpiContext::new(
+                    self.token_program.to_account_info(),
+                    Revoke {
+                        source: token_account.to_account_info(),
+                        authority: self.owner.to_account_info(),
+                    },
+                ))?;
+            }
+        }
+        
+        msg!(
+            "Deposit schedule cancelled after {} executions",
+            self.deposit_schedule.executions
+        );
+        Ok(())
+    }
+}
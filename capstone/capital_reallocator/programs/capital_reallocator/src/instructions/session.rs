@@ -0,0 +1,178 @@
+// instructions/session.rs
+use anchor_lang::prelude::*;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::events::{RebalanceAction, RebalancePhaseEvent, RebalanceSessionExpiredEvent};
+use crate::constants::*;
+// Glob import so the nested RebalancePosition accounts bring their generated client modules
+use crate::instructions::rebalance::*;
+
+// Open a multi-step rebalance. Takes the rebalance_position accounts, but only
+// the venue accounts the yield check reads are needed (LB pair and reserves,
+// Kamino reserves). The position is locked until the session ends.
+#[derive(Accounts)]
+pub struct StartRebalance<'info> {
+    pub rebalance: RebalancePosition<'info>,
+
+    #[account(
+        init,
+        payer = keeper,
+        space = 8 + RebalanceSession::INIT_SPACE,
+        seeds = [REBALANCE_SESSION_SEED, rebalance.position.key().as_ref()],
+        bump
+    )]
+    pub session: Box<Account<'info, RebalanceSession>>,
+
+    #[account(mut)]
+    pub keeper: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> StartRebalance<'info> {
+    pub fn start(&mut self, bumps: &StartRebalanceBumps) -> Result<()> {
+        require!(!self.rebalance.position.load()?.is_rebalancing(), ErrorCode::PositionRebalancing);
+
+        let plan = self.rebalance.plan_rebalance()?;
+        require!(plan.proceed, ErrorCode::NothingToRebalance);
+
+        let slot = Clock::get()?.slot;
+        self.session.set_inner(RebalanceSession {
+            version: REBALANCE_SESSION_VERSION,
+            position: self.rebalance.position.key(),
+            keeper: self.keeper.key(),
+            phase: RebalancePhase::Unwind,
+            target_lp: plan.lp_target,
+            price_in_range: plan.in_range,
+            start_price: plan.current_price,
+            started_slot: slot,
+            expires_slot: slot.saturating_add(REBALANCE_SESSION_TIMEOUT_SLOTS),
+            bump: bumps.session,
+            reserved: [0; REBALANCE_SESSION_RESERVED_BYTES],
+        });
+        let mut position = self.rebalance.position.load_mut()?;
+        position.set_rebalance_session(Some(self.session.key()));
+
+        msg!(
+            "Rebalance session opened for position {} at price {} (target: {})",
+            position.position_id,
+            plan.current_price,
+            if plan.lp_target { "LP" } else { "lending" }
+        );
+        Ok(())
+    }
+}
+
+// Run the session's next phase. Anyone can crank; each phase only needs the
+// accounts of the venue it touches: the venue being left for Unwind, the
+// Jupiter route for Swap, the target venue (and hedge accounts for hedged
+// positions) for Deploy.
+#[derive(Accounts)]
+pub struct CrankRebalance<'info> {
+    pub rebalance: RebalancePosition<'info>,
+
+    #[account(
+        mut,
+        seeds = [REBALANCE_SESSION_SEED, rebalance.position.key().as_ref()],
+        bump = session.bump,
+        has_one = keeper
+    )]
+    pub session: Box<Account<'info, RebalanceSession>>,
+
+    /// CHECK: Keeper that opened the session, refunded its rent when it completes
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+}
+
+impl<'info> CrankRebalance<'info> {
+    pub fn crank(
+        &mut self,
+        swap_data: Vec<u8>,
+        min_swap_out: u64,
+        remaining_accounts: &[AccountInfo<'info>],
+    ) -> Result<()> {
+        require!(Clock::get()?.slot <= self.session.expires_slot, ErrorCode::RebalanceSessionExpired);
+
+        let phase = self.session.phase;
+        let rung_count = self.rebalance.position.load()?.lp_rung_count;
+        let (rung_accounts, route) = ConversionRoute::split(rung_count, swap_data, min_swap_out, remaining_accounts);
+        let current_price = self.rebalance.run_session_phase(&self.session, rung_accounts, &route)?;
+
+        let (position_id, owner) = {
+            let position = self.rebalance.position.load()?;
+            (position.position_id, position.owner)
+        };
+        emit!(RebalancePhaseEvent {
+            position_id,
+            owner,
+            phase,
+            target_lp: self.session.target_lp,
+            current_price,
+        });
+
+        match phase {
+            RebalancePhase::Unwind => self.session.phase = RebalancePhase::Swap,
+            RebalancePhase::Swap => self.session.phase = RebalancePhase::Deploy,
+            RebalancePhase::Deploy => {
+                let action = if self.session.target_lp {
+                    RebalanceAction::MoveToLP
+                } else {
+                    RebalanceAction::MoveToLending
+                };
+                self.rebalance.position.load_mut()?.set_rebalance_session(None);
+                self.rebalance.finish_rebalance(current_price, self.session.price_in_range, action, &route)?;
+
+                msg!("Rebalance session completed for position {}", position_id);
+                return self.session.close(self.keeper.to_account_info());
+            }
+        }
+
+        msg!("Rebalance phase {:?} done, next: {:?}", phase, self.session.phase);
+        Ok(())
+    }
+}
+
+// Release a session that wasn't completed in time. Tracked balances are
+// consistent after every phase, so the funds stay where the completed
+// phases left them and the next rebalance picks them up.
+#[derive(Accounts)]
+pub struct ExpireRebalanceSession<'info> {
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump
+    )]
+    pub position: AccountLoader<'info, Position>,
+
+    #[account(
+        mut,
+        seeds = [REBALANCE_SESSION_SEED, position.key().as_ref()],
+        bump = session.bump,
+        has_one = keeper,
+        close = keeper
+    )]
+    pub session: Box<Account<'info, RebalanceSession>>,
+
+    /// CHECK: Keeper that opened the session, refunded its rent
+    #[account(mut)]
+    pub keeper: UncheckedAccount<'info>,
+}
+
+impl<'info> ExpireRebalanceSession<'info> {
+    pub fn expire(&mut self) -> Result<()> {
+        require!(Clock::get()?.slot > self.session.expires_slot, ErrorCode::RebalanceSessionActive);
+
+        let mut position = self.position.load_mut()?;
+        position.set_rebalance_session(None);
+
+        emit!(RebalanceSessionExpiredEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            phase: self.session.phase,
+            keeper: self.session.keeper,
+        });
+
+        msg!("Rebalance session for position {} expired in {:?}", position.position_id, self.session.phase);
+        Ok(())
+    }
+}
@@ -0,0 +1,232 @@
+// instructions/valuation.rs
+use anchor_lang::prelude::*;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::state::*;
+use crate::errors::ErrorCode;
+use crate::constants::*;
+use crate::instructions::rebalance::get_pair_price;
+use crate::protocols::kamino::{read_obligation_liquidity, validate_kamino_obligation};
+
+// Canonical position valuation (read-only, meant to be simulated)
+#[derive(Accounts)]
+pub struct GetPositionValue<'info> {
+    #[account(
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+    )]
+    pub position: AccountLoader<'info, Position>,
+
+    #[account(
+        seeds = [PAIR_SEED, position.load()?.token_a_mint.as_ref(), position.load()?.token_b_mint.as_ref()],
+        bump = pair_config.bump
+    )]
+    pub pair_config: Box<Account<'info, PairConfig>>,
+
+    pub price_update: Account<'info, PriceUpdateV2>,
+
+    #[account(
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+
+    /// CHECK: Kamino obligation - must be the one tracked by the position
+    #[account(constraint = position.load()?.kamino_obligation() == Some(kamino_obligation.key()) @ ErrorCode::LendingPositionNotFound)]
+    pub kamino_obligation: Option<UncheckedAccount<'info>>,
+
+    /// CHECK: Kamino reserves - must be the pair's registered reserves
+    #[account(constraint = kamino_reserve_a.key() == pair_config.kamino_reserve_a @ ErrorCode::VenueMismatch)]
+    pub kamino_reserve_a: Option<UncheckedAccount<'info>>,
+    #[account(constraint = kamino_reserve_b.key() == pair_config.kamino_reserve_b @ ErrorCode::VenueMismatch)]
+    pub kamino_reserve_b: Option<UncheckedAccount<'info>>,
+}
+
+impl<'info> GetPositionValue<'info> {
+    /// Value every location at the oracle price. Lending is read from the
+    /// obligation (interest included) when it and both reserves are passed,
+    /// otherwise the tracked amounts are used. LP amounts are the tracked
+    /// deposits since DLMM positions hold bin shares, not token amounts.
+    /// Hedge collateral counts towards token A and the borrow is deducted,
+    /// as is any withdrawal still queued for the owner. The collateral sits
+    /// in the same obligation and reserve as lent token A, so it is taken
+    /// out of the venue reading rather than counted twice.
+    pub fn value(&self) -> Result<PositionValue> {
+        let position = self.position.load()?;
+        let pair = &self.pair_config;
+        let (price, confidence) = get_pair_price(&self.price_update, pair, PRICE_MAX_AGE)?;
+
+        let value = position_value(&position, pair, price, confidence, self.lending_amounts()?)?;
+
+        msg!(
+            "Position {} value: {} (net deposits {}, PnL {}) at price {}",
+            position.position_id, value.total_value, value.net_deposit_value, value.unrealized_pnl, price
+        );
+
+        Ok(value)
+    }
+
+    fn lending_amounts(&self) -> Result<(u64, u64, bool)> {
+        let (Some(obligation), Some(reserve_a), Some(reserve_b)) = (
+            self.kamino_obligation.as_ref(),
+            self.kamino_reserve_a.as_ref(),
+            self.kamino_reserve_b.as_ref(),
+        ) else {
+            let position = self.position.load()?;
+            return Ok((position.token_a_in_lending, position.token_b_in_lending, false));
+        };
+
+        let kamino_program = self.protocol_authority.external_programs.kamino_lending;
+        validate_kamino_obligation(
+            obligation,
+            &self.pair_config.kamino_lending_market,
+            &self.position.key(),
+            &kamino_program,
+        )?;
+
+        Ok((
+            read_obligation_liquidity(obligation, reserve_a, &kamino_program)?,
+            read_obligation_liquidity(obligation, reserve_b, &kamino_program)?,
+            true,
+        ))
+    }
+}
+
+// Value of a position at `price`, with lending amounts as (a, b, read from venue)
+pub(crate) fn position_value(
+    position: &Position,
+    pair: &PairConfig,
+    price: u64,
+    confidence: u64,
+    (lending_a, lending_b, lending_from_venue): (u64, u64, bool),
+) -> Result<PositionValue> {
+    let lending_a = if lending_from_venue {
+        lending_a.saturating_sub(position.hedge_collateral_a)
+    } else {
+        lending_a
+    };
+    let total_a = position.token_a_vault_balance
+        .checked_add(position.token_a_in_lp)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_add(lending_a)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_add(position.hedge_collateral_a)
+        .ok_or(ErrorCode::MathOverflow)?
+        .saturating_sub(position.queued_withdraw_a);
+    let total_b = position.token_b_vault_balance
+        .checked_add(position.token_b_in_lp)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_add(lending_b)
+        .ok_or(ErrorCode::MathOverflow)?
+        .saturating_sub(position.queued_withdraw_b);
+
+    let total_value = total_a
+        .checked_add(pair.value_b_in_a(total_b, price)?)
+        .ok_or(ErrorCode::MathOverflow)?
+        .saturating_sub(pair.value_b_in_a(position.hedge_borrowed_b, price)?);
+
+    // Net deposits valued at today's price, so PnL excludes plain price exposure
+    let net_deposit_value = position.net_deposit_a
+        .checked_add(pair.value_b_in_a(position.net_deposit_b, price)?)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let unrealized_pnl = i64::try_from(total_value as i128 - net_deposit_value as i128)
+        .map_err(|_| ErrorCode::MathOverflow)?;
+
+    Ok(PositionValue {
+        position_id: position.position_id,
+        price,
+        confidence,
+        vault_a: position.token_a_vault_balance,
+        vault_b: position.token_b_vault_balance,
+        lp_a: position.token_a_in_lp,
+        lp_b: position.token_b_in_lp,
+        lending_a,
+        lending_b,
+        lending_from_venue,
+        hedge_collateral_a: position.hedge_collateral_a,
+        hedge_borrowed_b: position.hedge_borrowed_b,
+        queued_withdraw_a: position.queued_withdraw_a,
+        queued_withdraw_b: position.queued_withdraw_b,
+        total_a,
+        total_b,
+        total_value,
+        net_deposit_a: position.net_deposit_a,
+        net_deposit_b: position.net_deposit_b,
+        net_deposit_value,
+        unrealized_pnl,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair() -> PairConfig {
+        PairConfig {
+            version: PAIR_CONFIG_VERSION,
+            token_a_mint: Pubkey::new_unique(),
+            token_b_mint: Pubkey::new_unique(),
+            token_a_decimals: 6,
+            token_b_decimals: 6,
+            price_feed_id: [0; 32],
+            meteora_lb_pair: Pubkey::default(),
+            kamino_lending_market: Pubkey::default(),
+            kamino_reserve_a: Pubkey::default(),
+            kamino_reserve_b: Pubkey::default(),
+            min_position_a: 0,
+            min_position_b: 0,
+            max_position_a: 0,
+            max_position_b: 0,
+            enabled: true,
+            bump: 255,
+            yield_check_enabled: false,
+            yield_margin_bps: 0,
+            lookup_table: None,
+            reserved: [0; PAIR_RESERVED_BYTES],
+        }
+    }
+
+    // 1,000 A lent and 500 A posted as collateral against 100 B borrowed at 2 A per B
+    fn hedged_position() -> Position {
+        let mut position: Position = bytemuck::Zeroable::zeroed();
+        position.token_a_vault_balance = 100;
+        position.token_a_in_lending = 1_000;
+        position.hedge_collateral_a = 500;
+        position.hedge_borrowed_b = 100;
+        position.token_b_vault_balance = 100;
+        position
+    }
+
+    const PRICE: u64 = 2_000_000;
+
+    #[test]
+    fn hedged_position_from_venue_counts_collateral_once() {
+        let position = hedged_position();
+        // The obligation's reserve A deposit holds the lent tokens, the
+        // collateral and 10 of interest
+        let value = position_value(&position, &pair(), PRICE, 0, (1_510, 0, true)).unwrap();
+
+        assert_eq!(value.lending_a, 1_010);
+        assert_eq!(value.total_a, 100 + 1_010 + 500);
+        assert_eq!(value.total_value, 1_610 + 200 - 200);
+    }
+
+    #[test]
+    fn hedged_position_from_bookkeeping_adds_collateral() {
+        let position = hedged_position();
+        let value = position_value(&position, &pair(), PRICE, 0, (1_000, 0, false)).unwrap();
+
+        assert_eq!(value.lending_a, 1_000);
+        assert_eq!(value.total_a, 100 + 1_000 + 500);
+        assert_eq!(value.total_value, 1_600);
+    }
+
+    #[test]
+    fn venue_and_bookkeeping_agree_without_interest() {
+        let position = hedged_position();
+        let from_venue = position_value(&position, &pair(), PRICE, 0, (1_500, 0, true)).unwrap();
+        let tracked = position_value(&position, &pair(), PRICE, 0, (1_000, 0, false)).unwrap();
+
+        assert_eq!(from_venue.total_value, tracked.total_value);
+    }
+}
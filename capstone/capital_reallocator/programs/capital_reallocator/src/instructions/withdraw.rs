@@ -12,15 +12,17 @@ use crate::constants::*;
 pub struct WithdrawFromPosition<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
         has_one = owner,
         has_one = token_a_mint,
         has_one = token_b_mint,
     )]
-    pub position: Box<Account<'info, Position>>,
+    pub position: AccountLoader<'info, Position>,
     
     #[account(
+        mut,
         seeds = [PROTOCOL_SEED],
         bump = protocol_authority.bump
     )]
@@ -29,14 +31,14 @@ pub struct WithdrawFromPosition<'info> {
     #[account(
         mut,
         constraint = user_token_a.owner == owner.key(),
-        constraint = user_token_a.mint == position.token_a_mint
+        constraint = user_token_a.mint == position.load()?.token_a_mint
     )]
     pub user_token_a: Box<Account<'info, TokenAccount>>,
     
     #[account(
         mut,
         constraint = user_token_b.owner == owner.key(),
-        constraint = user_token_b.mint == position.token_b_mint
+        constraint = user_token_b.mint == position.load()?.token_b_mint
     )]
     pub user_token_b: Box<Account<'info, TokenAccount>>,
     
@@ -56,251 +58,472 @@ pub struct WithdrawFromPosition<'info> {
     
     #[account(
         mut,
-        constraint = fee_token_a.owner == protocol_authority.fee_recipient,
-        constraint = fee_token_a.mint == position.token_a_mint
+        seeds = [FEE_VAULT_SEED, token_a_mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&token_a_mint.key())?.bump
+    )]
+    pub fee_vault_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, token_b_mint.key().as_ref()],
+        bump = protocol_authority.fee_vault(&token_b_mint.key())?.bump
     )]
-    pub fee_token_a: Box<Account<'info, TokenAccount>>,
+    pub fee_vault_b: Box<Account<'info, TokenAccount>>,
     
+    // Required while the protocol routes a share of fees to insurance
     #[account(
         mut,
-        constraint = fee_token_b.owner == protocol_authority.fee_recipient,
-        constraint = fee_token_b.mint == position.token_b_mint
+        seeds = [INSURANCE_VAULT_SEED, token_a_mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&token_a_mint.key())?.bump
     )]
-    pub fee_token_b: Box<Account<'info, TokenAccount>>,
+    pub insurance_vault_a: Option<Box<Account<'info, TokenAccount>>>,
     
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, token_b_mint.key().as_ref()],
+        bump = protocol_authority.insurance_vault(&token_b_mint.key())?.bump
+    )]
+    pub insurance_vault_b: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        seeds = [USER_SEED, owner.key().as_ref()],
+        bump = user_main_account.bump
+    )]
+    pub user_main_account: Box<Account<'info, UserMainAccount>>,
+    
+    // Holds whatever part of the withdrawal the vaults can't cover yet
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = 8 + WithdrawalRequest::INIT_SPACE,
+        seeds = [WITHDRAWAL_SEED, position.key().as_ref()],
+        bump
+    )]
+    pub withdrawal_request: Box<Account<'info, WithdrawalRequest>>,
+    
+    #[account(mut)]
     pub owner: Signer<'info>,
     pub token_a_mint: Account<'info, Mint>,
     pub token_b_mint: Account<'info, Mint>,
     pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
 }
 
 impl<'info> WithdrawFromPosition<'info> {
-    pub fn withdraw(&mut self, withdraw_percentage: u8) -> Result<()> {
-        require!(
-            withdraw_percentage > 0 && withdraw_percentage <= 100,
-            ErrorCode::InvalidPercentage
-        );
+    /// Pays the withdrawn share from the idle vaults. Whatever the vaults can't
+    /// cover is still owed in LP or lending and is queued on the position's
+    /// withdrawal request for keepers to fulfill.
+    pub fn withdraw(&mut self, withdraw_percentage: u8, bumps: &WithdrawFromPositionBumps) -> Result<()> {
+        // Pay what the vaults hold now, queue the rest
+        let WithdrawalSplit { paid_a, paid_b, queued_a, queued_b } = self.position.load_mut()?.split_withdrawal(
+            withdraw_percentage,
+            self.position_token_a_vault.amount,
+            self.position_token_b_vault.amount,
+        )?;
         
-        // Calculate total balances across all positions
-        let total_a = self.position.token_a_vault_balance
-            .checked_add(self.position.token_a_in_lp)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_add(self.position.token_a_in_lending)
-            .ok_or(ErrorCode::MathOverflow)?;
-            
-        let total_b = self.position.token_b_vault_balance
-            .checked_add(self.position.token_b_in_lp)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_add(self.position.token_b_in_lending)
-            .ok_or(ErrorCode::MathOverflow)?;
+        // Calculate fees at the owner's volume tier
+        let applied_fee = self.protocol_authority.fee_for_volume(self.user_main_account.cumulative_deposit_value);
+        let fee_a = ProtocolAuthority::fee_at(paid_a, applied_fee.withdraw_fee_bps)?;
+        let fee_b = ProtocolAuthority::fee_at(paid_b, applied_fee.withdraw_fee_bps)?;
         
-        // Calculate withdrawal amounts
-        let withdraw_a = (total_a as u128)
-            .checked_mul(withdraw_percentage as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(100)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
-            
-        let withdraw_b = (total_b as u128)
-            .checked_mul(withdraw_percentage as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(100)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        let net_withdraw_a = paid_a.checked_sub(fee_a).ok_or(ErrorCode::MathOverflow)?;
+        let net_withdraw_b = paid_b.checked_sub(fee_b).ok_or(ErrorCode::MathOverflow)?;
+        let (protocol_fee_a, insurance_fee_a) = self.protocol_authority.insurance_split(fee_a)?;
+        let (protocol_fee_b, insurance_fee_b) = self.protocol_authority.insurance_split(fee_b)?;
         
-        // Calculate fees
-        let fee_a = (withdraw_a as u128)
-            .checked_mul(self.protocol_authority.protocol_fee_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10_000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
-            
-        let fee_b = (withdraw_b as u128)
-            .checked_mul(self.protocol_authority.protocol_fee_bps as u128)
-            .ok_or(ErrorCode::MathOverflow)?
-            .checked_div(10_000)
-            .ok_or(ErrorCode::MathOverflow)? as u64;
+        pay_from_vault(
+            &self.position,
+            &self.position_token_a_vault,
+            &self.user_token_a,
+            &self.fee_vault_a,
+            &self.token_program,
+            net_withdraw_a,
+            protocol_fee_a,
+        )?;
+        pay_from_vault(
+            &self.position,
+            &self.position_token_b_vault,
+            &self.user_token_b,
+            &self.fee_vault_b,
+            &self.token_program,
+            net_withdraw_b,
+            protocol_fee_b,
+        )?;
+        self.protocol_authority.record_fee(&self.token_a_mint.key(), protocol_fee_a)?;
+        self.protocol_authority.record_fee(&self.token_b_mint.key(), protocol_fee_b)?;
         
-        let net_withdraw_a = withdraw_a.checked_sub(fee_a).ok_or(ErrorCode::MathOverflow)?;
-        let net_withdraw_b = withdraw_b.checked_sub(fee_b).ok_or(ErrorCode::MathOverflow)?;
-        
-        // Update position state to reflect withdrawals from LP/lending
-        if withdraw_percentage == 100 {
-            // For 100% withdrawal, clear everything
-            self.position.token_a_in_lp = 0;
-            self.position.token_b_in_lp = 0;
-            self.position.token_a_in_lending = 0;
-            self.position.token_b_in_lending = 0;
-            self.position.token_a_vault_balance = 0;
-            self.position.token_b_vault_balance = 0;
-        } else {
-            // For partial withdrawal, reduce proportionally
-            let remaining_percentage = 100u128.saturating_sub(withdraw_percentage as u128);
-            
-            self.position.token_a_in_lp = ((self.position.token_a_in_lp as u128)
-                .checked_mul(remaining_percentage)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(100)
-                .ok_or(ErrorCode::MathOverflow)?) as u64;
-                
-            self.position.token_b_in_lp = ((self.position.token_b_in_lp as u128)
-                .checked_mul(remaining_percentage)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(100)
-                .ok_or(ErrorCode::MathOverflow)?) as u64;
-                
-            self.position.token_a_in_lending = ((self.position.token_a_in_lending as u128)
-                .checked_mul(remaining_percentage)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(100)
-                .ok_or(ErrorCode::MathOverflow)?) as u64;
-                
-            self.position.token_b_in_lending = ((self.position.token_b_in_lending as u128)
-                .checked_mul(remaining_percentage)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(100)
-                .ok_or(ErrorCode::MathOverflow)?) as u64;
-                
-            self.position.token_a_vault_balance = ((self.position.token_a_vault_balance as u128)
-                .checked_mul(remaining_percentage)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(100)
-                .ok_or(ErrorCode::MathOverflow)?) as u64;
-                
-            self.position.token_b_vault_balance = ((self.position.token_b_vault_balance as u128)
-                .checked_mul(remaining_percentage)
-                .ok_or(ErrorCode::MathOverflow)?
-                .checked_div(100)
-                .ok_or(ErrorCode::MathOverflow)?) as u64;
+        if insurance_fee_a > 0 {
+            let insurance_vault = self.insurance_vault_a.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+            transfer_from_vault(&self.position, &self.position_token_a_vault, insurance_vault, &self.token_program, insurance_fee_a)?;
+            self.protocol_authority.record_insurance(&self.token_a_mint.key(), insurance_fee_a)?;
+        }
+        if insurance_fee_b > 0 {
+            let insurance_vault = self.insurance_vault_b.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+            transfer_from_vault(&self.position, &self.position_token_b_vault, insurance_vault, &self.token_program, insurance_fee_b)?;
+            self.protocol_authority.record_insurance(&self.token_b_mint.key(), insurance_fee_b)?;
         }
         
-        // For testing purposes, we'll simulate the withdrawal by just updating state
-        // In production, you'd actually transfer tokens from vaults
-        // The actual token transfers would happen here after closing LP/lending positions
-        
-        // Extract values for signer seeds
-        let position_owner = self.position.owner;
-        let position_id = self.position.position_id;
-        let position_bump = self.position.bump;
+        self.record_queued(queued_a, queued_b, applied_fee.withdraw_fee_bps, bumps)?;
         
-        // Create signer seeds
-        let position_id_bytes = position_id.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            POSITION_SEED,
-            position_owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[position_bump],
-        ]];
+        let position = self.position.load()?;
+        emit!(WithdrawEvent {
+            position_id: position.position_id,
+            owner: position.owner,
+            amount_a: net_withdraw_a,
+            amount_b: net_withdraw_b,
+            fee_a,
+            fee_b,
+            percentage: withdraw_percentage,
+            fee_tier: applied_fee.tier,
+            fee_bps: applied_fee.withdraw_fee_bps,
+            queued_a,
+            queued_b,
+        });
         
-        // For testing, only transfer what's actually in the vault
-        // In production, you'd first move funds from LP/lending to vault
-        let vault_amount_a = self.position_token_a_vault.amount;
-        let vault_amount_b = self.position_token_b_vault.amount;
+        msg!(
+            "Withdrew {}% - {} token A, {} token B paid, {} A and {} B queued",
+            withdraw_percentage, net_withdraw_a, net_withdraw_b, queued_a, queued_b
+        );
         
-        let actual_withdraw_a = withdraw_a.min(vault_amount_a);
-        let actual_withdraw_b = withdraw_b.min(vault_amount_b);
+        Ok(())
+    }
+    
+    fn record_queued(&mut self, queued_a: u64, queued_b: u64, fee_bps: u16, bumps: &WithdrawFromPositionBumps) -> Result<()> {
+        let request = &mut self.withdrawal_request;
         
-        if actual_withdraw_a > 0 {
-            let actual_net_a = actual_withdraw_a.saturating_sub(fee_a.min(actual_withdraw_a));
-            let actual_fee_a = actual_withdraw_a.saturating_sub(actual_net_a);
-            
-            if actual_net_a > 0 {
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        self.token_program.to_account_info(),
-                        Transfer {
-                            from: self.position_token_a_vault.to_account_info(),
-                            to: self.user_token_a.to_account_info(),
-                            authority: self.position.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
-                    actual_net_a,
-                )?;
+        if request.version == 0 {
+            // Nothing owed and nothing queued before: don't keep the account around
+            if queued_a == 0 && queued_b == 0 {
+                return request.close(self.owner.to_account_info());
             }
             
-            if actual_fee_a > 0 {
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        self.token_program.to_account_info(),
-                        Transfer {
-                            from: self.position_token_a_vault.to_account_info(),
-                            to: self.fee_token_a.to_account_info(),
-                            authority: self.position.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
-                    actual_fee_a,
-                )?;
-            }
+            request.set_inner(WithdrawalRequest {
+                version: WITHDRAWAL_REQUEST_VERSION,
+                owner: self.owner.key(),
+                position: self.position.key(),
+                owed_a: 0,
+                owed_b: 0,
+                filled_a: 0,
+                filled_b: 0,
+                fee_bps,
+                created_at: Clock::get()?.unix_timestamp,
+                bump: bumps.withdrawal_request,
+                reserved: [0; WITHDRAWAL_REQUEST_RESERVED_BYTES],
+            });
         }
         
-        if actual_withdraw_b > 0 {
-            let actual_net_b = actual_withdraw_b.saturating_sub(fee_b.min(actual_withdraw_b));
-            let actual_fee_b = actual_withdraw_b.saturating_sub(actual_net_b);
+        if queued_a > 0 || queued_b > 0 {
+            // Later additions are charged at the rate in force when queued
+            request.fee_bps = fee_bps;
+            request.owed_a = request.owed_a.checked_add(queued_a).ok_or(ErrorCode::MathOverflow)?;
+            request.owed_b = request.owed_b.checked_add(queued_b).ok_or(ErrorCode::MathOverflow)?;
             
-            if actual_net_b > 0 {
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        self.token_program.to_account_info(),
-                        Transfer {
-                            from: self.position_token_b_vault.to_account_info(),
-                            to: self.user_token_b.to_account_info(),
-                            authority: self.position.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
-                    actual_net_b,
-                )?;
-            }
-            
-            if actual_fee_b > 0 {
-                token::transfer(
-                    CpiContext::new_with_signer(
-                        self.token_program.to_account_info(),
-                        Transfer {
-                            from: self.position_token_b_vault.to_account_info(),
-                            to: self.fee_token_b.to_account_info(),
-                            authority: self.position.to_account_info(),
-                        },
-                        signer_seeds,
-                    ),
-                    actual_fee_b,
-                )?;
-            }
+            let position = self.position.load()?;
+            emit!(WithdrawalQueuedEvent {
+                position_id: position.position_id,
+                owner: position.owner,
+                queued_a,
+                queued_b,
+                owed_a: request.owed_a,
+                owed_b: request.owed_b,
+            });
         }
         
-        emit!(WithdrawEvent {
-            position_id,
-            owner: position_owner,
-            amount_a: net_withdraw_a,
-            amount_b: net_withdraw_b,
+        Ok(())
+    }
+}
+
+// Pay a queued withdrawal from whatever the vaults now hold (permissionless)
+#[derive(Accounts)]
+pub struct FulfillWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_SEED, position.key().as_ref()],
+        bump = withdrawal_request.bump,
+        has_one = position,
+        has_one = owner
+    )]
+    pub withdrawal_request: Box<Account<'info, WithdrawalRequest>>,
+    
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, position.load()?.owner.as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(
+        mut,
+        seeds = [PROTOCOL_SEED],
+        bump = protocol_authority.bump
+    )]
+    pub protocol_authority: Box<Account<'info, ProtocolAuthority>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = position.load()?.token_a_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_token_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = position.load()?.token_b_mint,
+        associated_token::authority = owner
+    )]
+    pub owner_token_b: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = position.load()?.token_a_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_a_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        associated_token::mint = position.load()?.token_b_mint,
+        associated_token::authority = position
+    )]
+    pub position_token_b_vault: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, position.load()?.token_a_mint.as_ref()],
+        bump = protocol_authority.fee_vault(&position.load()?.token_a_mint)?.bump
+    )]
+    pub fee_vault_a: Box<Account<'info, TokenAccount>>,
+    
+    #[account(
+        mut,
+        seeds = [FEE_VAULT_SEED, position.load()?.token_b_mint.as_ref()],
+        bump = protocol_authority.fee_vault(&position.load()?.token_b_mint)?.bump
+    )]
+    pub fee_vault_b: Box<Account<'info, TokenAccount>>,
+    
+    // Required while the protocol routes a share of fees to insurance
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, position.load()?.token_a_mint.as_ref()],
+        bump = protocol_authority.insurance_vault(&position.load()?.token_a_mint)?.bump
+    )]
+    pub insurance_vault_a: Option<Box<Account<'info, TokenAccount>>>,
+    
+    #[account(
+        mut,
+        seeds = [INSURANCE_VAULT_SEED, position.load()?.token_b_mint.as_ref()],
+        bump = protocol_authority.insurance_vault(&position.load()?.token_b_mint)?.bump
+    )]
+    pub insurance_vault_b: Option<Box<Account<'info, TokenAccount>>>,
+    
+    /// CHECK: Request owner, receives the rent once the request is filled
+    #[account(mut)]
+    pub owner: UncheckedAccount<'info>,
+    
+    pub keeper: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+impl<'info> FulfillWithdrawal<'info> {
+    pub fn fulfill(&mut self) -> Result<()> {
+        let request = &self.withdrawal_request;
+        let position = self.position.load()?;
+        let (token_a_mint, token_b_mint) = (position.token_a_mint, position.token_b_mint);
+        
+        let pay_a = request.owed_a
+            .min(position.token_a_vault_balance)
+            .min(self.position_token_a_vault.amount);
+        let pay_b = request.owed_b
+            .min(position.token_b_vault_balance)
+            .min(self.position_token_b_vault.amount);
+        drop(position);
+        require!(pay_a > 0 || pay_b > 0, ErrorCode::NothingToFulfill);
+        
+        let fee_a = ProtocolAuthority::fee_at(pay_a, request.fee_bps)?;
+        let fee_b = ProtocolAuthority::fee_at(pay_b, request.fee_bps)?;
+        let (protocol_fee_a, insurance_fee_a) = self.protocol_authority.insurance_split(fee_a)?;
+        let (protocol_fee_b, insurance_fee_b) = self.protocol_authority.insurance_split(fee_b)?;
+        
+        pay_from_vault(
+            &self.position,
+            &self.position_token_a_vault,
+            &self.owner_token_a,
+            &self.fee_vault_a,
+            &self.token_program,
+            pay_a - fee_a,
+            protocol_fee_a,
+        )?;
+        pay_from_vault(
+            &self.position,
+            &self.position_token_b_vault,
+            &self.owner_token_b,
+            &self.fee_vault_b,
+            &self.token_program,
+            pay_b - fee_b,
+            protocol_fee_b,
+        )?;
+        self.protocol_authority.record_fee(&token_a_mint, protocol_fee_a)?;
+        self.protocol_authority.record_fee(&token_b_mint, protocol_fee_b)?;
+        
+        if insurance_fee_a > 0 {
+            let insurance_vault = self.insurance_vault_a.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+            transfer_from_vault(&self.position, &self.position_token_a_vault, insurance_vault, &self.token_program, insurance_fee_a)?;
+            self.protocol_authority.record_insurance(&token_a_mint, insurance_fee_a)?;
+        }
+        if insurance_fee_b > 0 {
+            let insurance_vault = self.insurance_vault_b.as_ref().ok_or(ErrorCode::InsuranceVaultNotFound)?;
+            transfer_from_vault(&self.position, &self.position_token_b_vault, insurance_vault, &self.token_program, insurance_fee_b)?;
+            self.protocol_authority.record_insurance(&token_b_mint, insurance_fee_b)?;
+        }
+        
+        let mut position = self.position.load_mut()?;
+        position.token_a_vault_balance -= pay_a;
+        position.token_b_vault_balance -= pay_b;
+        position.queued_withdraw_a = position.queued_withdraw_a.saturating_sub(pay_a);
+        position.queued_withdraw_b = position.queued_withdraw_b.saturating_sub(pay_b);
+        
+        let request = &mut self.withdrawal_request;
+        request.owed_a -= pay_a;
+        request.owed_b -= pay_b;
+        request.filled_a = request.filled_a.checked_add(pay_a).ok_or(ErrorCode::MathOverflow)?;
+        request.filled_b = request.filled_b.checked_add(pay_b).ok_or(ErrorCode::MathOverflow)?;
+        
+        emit!(WithdrawalFilledEvent {
+            position_id: position.position_id,
+            owner: request.owner,
+            keeper: self.keeper.key(),
+            amount_a: pay_a - fee_a,
+            amount_b: pay_b - fee_b,
             fee_a,
             fee_b,
-            percentage: withdraw_percentage,
+            remaining_a: request.owed_a,
+            remaining_b: request.owed_b,
         });
         
         msg!(
-            "Withdrew {}% - {} token A, {} token B (from LP/lending/vault)",
-            withdraw_percentage, net_withdraw_a, net_withdraw_b
+            "Filled {} A and {} B of queued withdrawal, {} A and {} B still owed",
+            pay_a, pay_b, request.owed_a, request.owed_b
         );
         
+        if request.owed_a == 0 && request.owed_b == 0 {
+            request.close(self.owner.to_account_info())?;
+        }
+        
+        Ok(())
+    }
+}
+
+// Drop a queued withdrawal; the owed share stays in the position (owner only)
+#[derive(Accounts)]
+pub struct CancelWithdrawal<'info> {
+    #[account(
+        mut,
+        seeds = [WITHDRAWAL_SEED, position.key().as_ref()],
+        bump = withdrawal_request.bump,
+        has_one = position,
+        has_one = owner,
+        close = owner
+    )]
+    pub withdrawal_request: Box<Account<'info, WithdrawalRequest>>,
+    
+    #[account(
+        mut,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
+        has_one = owner
+    )]
+    pub position: AccountLoader<'info, Position>,
+    
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+impl<'info> CancelWithdrawal<'info> {
+    pub fn cancel(&mut self) -> Result<()> {
+        let owed_a = self.withdrawal_request.owed_a;
+        let owed_b = self.withdrawal_request.owed_b;
+        
+        // The share comes back into the position, and so does its cost basis
+        let mut position = self.position.load_mut()?;
+        position.queued_withdraw_a = position.queued_withdraw_a.saturating_sub(owed_a);
+        position.queued_withdraw_b = position.queued_withdraw_b.saturating_sub(owed_b);
+        position.net_deposit_a = position.net_deposit_a.saturating_add(owed_a);
+        position.net_deposit_b = position.net_deposit_b.saturating_add(owed_b);
+        
+        emit!(WithdrawalCancelledEvent {
+            position_id: position.position_id,
+            owner: self.owner.key(),
+            owed_a,
+            owed_b,
+            filled_a: self.withdrawal_request.filled_a,
+            filled_b: self.withdrawal_request.filled_b,
+        });
+        
+        msg!("Cancelled queued withdrawal of {} A and {} B", owed_a, owed_b);
         Ok(())
     }
 }
 
+// Send `net` from a position vault to the recipient and `fee` to the fee vault
+pub(crate) fn pay_from_vault<'info>(
+    position: &AccountLoader<'info, Position>,
+    vault: &Account<'info, TokenAccount>,
+    recipient: &Account<'info, TokenAccount>,
+    fee_vault: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    net: u64,
+    fee: u64,
+) -> Result<()> {
+    transfer_from_vault(position, vault, recipient, token_program, net)?;
+    transfer_from_vault(position, vault, fee_vault, token_program, fee)
+}
+
+// Transfer out of a position vault, signed by the position
+pub(crate) fn transfer_from_vault<'info>(
+    position: &AccountLoader<'info, Position>,
+    vault: &Account<'info, TokenAccount>,
+    to: &Account<'info, TokenAccount>,
+    token_program: &Program<'info, Token>,
+    amount: u64,
+) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    
+    let signer = position.load()?.signer();
+    let position_seeds = signer.seeds();
+    let signer_seeds: &[&[&[u8]]] = &[&position_seeds];
+    
+    token::transfer(
+        CpiContext::new_with_signer(
+            token_program.to_account_info(),
+            Transfer {
+                from: vault.to_account_info(),
+                to: to.to_account_info(),
+                authority: position.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        amount,
+    )
+}
+
 // Close Position
 #[derive(Accounts)]
 pub struct ClosePosition<'info> {
     #[account(
         mut,
-        seeds = [POSITION_SEED, owner.key().as_ref(), position.position_id.to_le_bytes().as_ref()],
-        bump = position.bump,
+        seeds = [POSITION_SEED, owner.key().as_ref(), position.load()?.position_id.to_le_bytes().as_ref()],
+        bump = position.load()?.bump,
+        constraint = !position.load()?.is_rebalancing() @ ErrorCode::PositionRebalancing,
         has_one = owner,
         has_one = token_a_mint,
         has_one = token_b_mint,
         close = owner
     )]
-    pub position: Account<'info, Position>,
+    pub position: AccountLoader<'info, Position>,
     
     #[account(
         mut,
@@ -317,6 +540,22 @@ pub struct ClosePosition<'info> {
     )]
     pub protocol_authority: Account<'info, ProtocolAuthority>,
     
+    /// CHECK: must not exist; a queued withdrawal is filled or cancelled first
+    #[account(
+        seeds = [WITHDRAWAL_SEED, position.key().as_ref()],
+        bump,
+        constraint = withdrawal_request.data_is_empty() @ ErrorCode::WithdrawalPending
+    )]
+    pub withdrawal_request: UncheckedAccount<'info>,
+    
+    /// CHECK: must not exist; the schedule is cancelled first to revoke its delegate
+    #[account(
+        seeds = [SCHEDULE_SEED, position.key().as_ref()],
+        bump,
+        constraint = deposit_schedule.data_is_empty() @ ErrorCode::DepositScheduleActive
+    )]
+    pub deposit_schedule: UncheckedAccount<'info>,
+    
     #[account(
         mut,
         associated_token::mint = token_a_mint,
@@ -344,29 +583,27 @@ pub struct ClosePosition<'info> {
 
 impl<'info> ClosePosition<'info> {
     pub fn close(&mut self) -> Result<()> {
-        // Verify position is empty
-        require!(
-            self.position.token_a_vault_balance == 0 &&
-            self.position.token_b_vault_balance == 0 &&
-            self.position.token_a_in_lp == 0 &&
-            self.position.token_b_in_lp == 0 &&
-            self.position.token_a_in_lending == 0 &&
-            self.position.token_b_in_lending == 0,
-            ErrorCode::PositionNotEmpty
-        );
-
-        let position_owner = self.position.owner;
-        let position_id = self.position.position_id;
-        let position_bump = self.position.bump;
+        let (position_id, signer) = {
+            let position = self.position.load()?;
+            
+            // Verify position is empty
+            require!(
+                position.token_a_vault_balance == 0 &&
+                position.token_b_vault_balance == 0 &&
+                position.token_a_in_lp == 0 &&
+                position.token_b_in_lp == 0 &&
+                position.token_a_in_lending == 0 &&
+                position.token_b_in_lending == 0,
+                ErrorCode::PositionNotEmpty
+            );
+            // Collateral posted for the hedge would be orphaned with the obligation
+            require!(!position.has_open_hedge(), ErrorCode::HedgeStillOpen);
+            (position.position_id, position.signer())
+        };
 
         // Create signer seeds
-        let position_id_bytes = position_id.to_le_bytes();
-        let signer_seeds: &[&[&[u8]]] = &[&[
-            POSITION_SEED,
-            position_owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[position_bump],
-        ]];
+        let position_seeds = signer.seeds();
+        let signer_seeds: &[&[&[u8]]] = &[&position_seeds];
 
         // Close token A vault
         let cpi_accounts_a = anchor_spl::token::CloseAccount {
@@ -389,10 +626,11 @@ impl<'info> ClosePosition<'info> {
         anchor_spl::token::close_account(cpi_ctx_b)?;
         
         // Update counters
+        self.user_main_account.unindex_position(position_id);
         self.user_main_account.position_count = self.user_main_account.position_count.saturating_sub(1);
         self.protocol_authority.total_positions = self.protocol_authority.total_positions.saturating_sub(1);
         
-        msg!("Position {} closed", self.position.position_id);
+        msg!("Position {} closed", position_id);
         Ok(())
     }
 }
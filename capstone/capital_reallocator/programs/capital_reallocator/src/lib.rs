@@ -9,8 +9,13 @@ pub mod errors;
 pub mod events;
 pub mod constants;
 pub mod protocols;
+pub mod strategy;
 
 use instructions::*;
+use state::{
+    AuditResolution, ExternalPrograms, FeeTier, LpRungConfig, OutOfRangePolicy, PairConfigParams, PortfolioValue, PositionValue,
+    RewardRoute, StrategyPresetParams,
+};
 
 declare_id!("6CMb7MGxBA7ukm9y6NHx5ghnTuwMrTSr4aeYV5oqNhSh");
 
@@ -23,6 +28,91 @@ pub mod capital_reallocator {
         ctx.accounts.init_protocol(fee_bps, &ctx.bumps)
     }
 
+    // Supported pair registry
+    pub fn set_pair_config(ctx: Context<SetPairConfig>, params: PairConfigParams) -> Result<()> {
+        ctx.accounts.set_pair(params, &ctx.bumps)
+    }
+
+    pub fn create_pair_lookup_table(ctx: Context<CreatePairLookupTable>, recent_slot: u64) -> Result<()> {
+        ctx.accounts.create_table(recent_slot)
+    }
+
+    pub fn extend_pair_lookup_table(
+        ctx: Context<ExtendPairLookupTable>,
+        extra_addresses: Vec<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.extend_table(extra_addresses)
+    }
+
+    // Strategy presets
+    pub fn set_strategy_preset(
+        ctx: Context<SetStrategyPreset>,
+        name: [u8; 32],
+        params: StrategyPresetParams,
+    ) -> Result<()> {
+        ctx.accounts.set_preset(name, params, &ctx.bumps)
+    }
+
+    // Volume fee tiers
+    pub fn set_fee_tiers(ctx: Context<SetFeeTiers>, tiers: Vec<FeeTier>) -> Result<()> {
+        ctx.accounts.set_tiers(tiers)
+    }
+
+    // Protocol fee vaults
+    pub fn create_fee_vault(ctx: Context<CreateFeeVault>) -> Result<()> {
+        ctx.accounts.create_vault(&ctx.bumps)
+    }
+
+    pub fn sweep_fees<'info>(
+        ctx: Context<'_, '_, 'info, 'info, SweepFees<'info>>,
+        shares_bps: Vec<u16>,
+    ) -> Result<()> {
+        ctx.accounts.sweep(shares_bps, ctx.remaining_accounts)
+    }
+
+    // Insurance fund
+    pub fn create_insurance_vault(ctx: Context<CreateInsuranceVault>) -> Result<()> {
+        ctx.accounts.create_vault(&ctx.bumps)
+    }
+
+    pub fn set_insurance_share(ctx: Context<SetInsuranceShare>, share_bps: u16) -> Result<()> {
+        ctx.accounts.set_share(share_bps)
+    }
+
+    pub fn compensate_position(ctx: Context<CompensatePosition>, amount: u64, reason: [u8; 32]) -> Result<()> {
+        ctx.accounts.compensate(amount, reason)
+    }
+
+    // Venue program IDs
+    pub fn propose_external_programs(ctx: Context<UpdateExternalPrograms>, programs: ExternalPrograms) -> Result<()> {
+        ctx.accounts.propose(programs)
+    }
+
+    pub fn apply_external_programs(ctx: Context<UpdateExternalPrograms>) -> Result<()> {
+        ctx.accounts.apply()
+    }
+
+    pub fn cancel_external_programs(ctx: Context<UpdateExternalPrograms>) -> Result<()> {
+        ctx.accounts.cancel()
+    }
+
+    // Referral program
+    pub fn set_referral_tier(ctx: Context<SetReferralTier>, tier: u8, fee_share_bps: u16) -> Result<()> {
+        ctx.accounts.set_tier(tier, fee_share_bps, &ctx.bumps)
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        ctx.accounts.register(&ctx.bumps)
+    }
+
+    pub fn set_referrer_tier(ctx: Context<SetReferrerTier>) -> Result<()> {
+        ctx.accounts.set_tier()
+    }
+
+    pub fn claim_referral_fees(ctx: Context<ClaimReferralFees>) -> Result<()> {
+        ctx.accounts.claim()
+    }
+
     // User initialization
     pub fn initialize_user(ctx: Context<InitializeUser>) -> Result<()> {
         ctx.accounts.init_user(&ctx.bumps)
@@ -38,6 +128,10 @@ pub mod capital_reallocator {
         ctx.accounts.init_position(position_id, lp_range_min, lp_range_max, &ctx.bumps)
     }
 
+    pub fn create_position_from_preset(ctx: Context<CreatePositionFromPreset>, position_id: u64) -> Result<()> {
+        ctx.accounts.init_from_preset(position_id, &ctx.bumps)
+    }
+
     pub fn deposit_to_position(
         ctx: Context<DepositToPosition>,
         amount_a: u64,
@@ -46,6 +140,25 @@ pub mod capital_reallocator {
         ctx.accounts.deposit(amount_a, amount_b)
     }
 
+    // Recurring deposits
+    pub fn create_deposit_schedule(
+        ctx: Context<CreateDepositSchedule>,
+        amount_a: u64,
+        amount_b: u64,
+        interval_seconds: i64,
+        end_ts: i64,
+    ) -> Result<()> {
+        ctx.accounts.create_schedule(amount_a, amount_b, interval_seconds, end_ts, &ctx.bumps)
+    }
+
+    pub fn execute_scheduled_deposit(ctx: Context<ExecuteScheduledDeposit>) -> Result<()> {
+        ctx.accounts.execute()
+    }
+
+    pub fn cancel_deposit_schedule(ctx: Context<CancelDepositSchedule>) -> Result<()> {
+        ctx.accounts.cancel_schedule()
+    }
+
     pub fn pause_position(ctx: Context<ModifyPosition>) -> Result<()> {
         ctx.accounts.pause()
     }
@@ -54,22 +167,149 @@ pub mod capital_reallocator {
         ctx.accounts.resume()
     }
 
+    pub fn update_position_range(
+        ctx: Context<ModifyPosition>,
+        lp_range_min: u64,
+        lp_range_max: u64,
+    ) -> Result<()> {
+        ctx.accounts.update_range(lp_range_min, lp_range_max)
+    }
+
+    pub fn set_out_of_range_policy(
+        ctx: Context<ModifyPosition>,
+        policy: OutOfRangePolicy,
+    ) -> Result<()> {
+        ctx.accounts.set_out_of_range_policy(policy)
+    }
+
+    pub fn set_position_operator(
+        ctx: Context<SetPositionOperator>,
+        operator: Option<Pubkey>,
+    ) -> Result<()> {
+        ctx.accounts.set_operator(operator)
+    }
+
+    pub fn set_hedge_mode(ctx: Context<SetHedgeMode>, enabled: bool) -> Result<()> {
+        ctx.accounts.set_hedge(enabled)
+    }
+
+    pub fn set_lp_ladder(ctx: Context<SetLpLadder>, rungs: Vec<LpRungConfig>) -> Result<()> {
+        ctx.accounts.set_ladder(rungs)
+    }
+
     pub fn close_position(ctx: Context<ClosePosition>) -> Result<()> {
         ctx.accounts.close()
     }
 
+    // Portfolio operations over positions passed as remaining accounts
+    pub fn pause_portfolio<'info>(ctx: Context<'_, '_, 'info, 'info, ModifyPortfolio<'info>>) -> Result<()> {
+        ctx.accounts.set_paused(true, ctx.remaining_accounts)
+    }
+
+    pub fn resume_portfolio<'info>(ctx: Context<'_, '_, 'info, 'info, ModifyPortfolio<'info>>) -> Result<()> {
+        ctx.accounts.set_paused(false, ctx.remaining_accounts)
+    }
+
+    pub fn withdraw_from_portfolio<'info>(
+        ctx: Context<'_, '_, 'info, 'info, WithdrawFromPortfolio<'info>>,
+        withdraw_percentage: u8,
+    ) -> Result<()> {
+        ctx.accounts.withdraw(withdraw_percentage, ctx.remaining_accounts)
+    }
+
+    pub fn get_portfolio_value<'info>(
+        ctx: Context<'_, '_, 'info, 'info, GetPortfolioValue<'info>>,
+    ) -> Result<PortfolioValue> {
+        ctx.accounts.value(ctx.remaining_accounts)
+    }
+
     // Rebalancing operations
     pub fn check_position_status(ctx: Context<CheckPositionStatus>) -> Result<()> {
         ctx.accounts.check_status()
     }
 
-    pub fn rebalance_position(ctx: Context<RebalancePosition>) -> Result<()> {
-        ctx.accounts.rebalance()
+    pub fn rebalance_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, RebalancePosition<'info>>,
+        swap_data: Vec<u8>,
+        min_swap_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.rebalance(swap_data, min_swap_out, ctx.remaining_accounts)
+    }
+
+    // Multi-step rebalancing, one phase per transaction
+    pub fn start_rebalance(ctx: Context<StartRebalance>) -> Result<()> {
+        ctx.accounts.start(&ctx.bumps)
+    }
+
+    pub fn crank_rebalance<'info>(
+        ctx: Context<'_, '_, '_, 'info, CrankRebalance<'info>>,
+        swap_data: Vec<u8>,
+        min_swap_out: u64,
+    ) -> Result<()> {
+        ctx.accounts.crank(swap_data, min_swap_out, ctx.remaining_accounts)
+    }
+
+    pub fn expire_rebalance_session(ctx: Context<ExpireRebalanceSession>) -> Result<()> {
+        ctx.accounts.expire()
+    }
+
+    pub fn compound_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, CompoundPosition<'info>>,
+        reward_index: Option<u64>,
+        swap_data: Vec<u8>,
+        min_swap_out_a: u64,
+        min_swap_out_b: u64,
+    ) -> Result<()> {
+        ctx.accounts.compound(reward_index, swap_data, min_swap_out_a, min_swap_out_b, ctx.remaining_accounts)
+    }
+
+    pub fn harvest_kamino_rewards<'info>(
+        ctx: Context<'_, '_, '_, 'info, HarvestKaminoRewards<'info>>,
+        reward_index: u64,
+        route: RewardRoute,
+        swap_data: Vec<u8>,
+        min_swap_out_a: u64,
+        min_swap_out_b: u64,
+    ) -> Result<()> {
+        ctx.accounts.harvest(reward_index, route, swap_data, min_swap_out_a, min_swap_out_b, ctx.remaining_accounts)
+    }
+
+    // Read-only valuation, returned via return data
+    pub fn get_position_value(ctx: Context<GetPositionValue>) -> Result<PositionValue> {
+        ctx.accounts.value()
+    }
+
+    // Accounting audit
+    pub fn audit_position<'info>(
+        ctx: Context<'_, '_, '_, 'info, AuditPosition<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.audit(ctx.remaining_accounts)
+    }
+
+    pub fn resolve_audit(ctx: Context<ResolveAudit>, resolution: AuditResolution) -> Result<()> {
+        ctx.accounts.resolve(resolution)
+    }
+
+    // Account migrations
+    pub fn migrate_protocol(ctx: Context<MigrateProtocol>) -> Result<()> {
+        ctx.accounts.migrate()
+    }
+
+    pub fn migrate_user_account<'info>(
+        ctx: Context<'_, '_, '_, 'info, MigrateUserAccount<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.migrate(ctx.remaining_accounts)
+    }
+
+    pub fn migrate_position(ctx: Context<MigratePosition>) -> Result<()> {
+        ctx.accounts.migrate()
     }
 
     // External protocol withdrawal operations
-    pub fn withdraw_from_meteora(ctx: Context<WithdrawFromMeteora>) -> Result<()> {
-        ctx.accounts.withdraw_from_lp()
+    pub fn withdraw_from_meteora<'info>(
+        ctx: Context<'_, '_, '_, 'info, WithdrawFromMeteora<'info>>,
+    ) -> Result<()> {
+        ctx.accounts.withdraw_from_lp(ctx.remaining_accounts)
     }
     
     pub fn withdraw_from_kamino(ctx: Context<WithdrawFromKamino>) -> Result<()> {
@@ -81,6 +321,14 @@ pub mod capital_reallocator {
         ctx: Context<WithdrawFromPosition>,
         withdraw_percentage: u8,
     ) -> Result<()> {
-        ctx.accounts.withdraw(withdraw_percentage)
+        ctx.accounts.withdraw(withdraw_percentage, &ctx.bumps)
+    }
+
+    pub fn fulfill_withdrawal(ctx: Context<FulfillWithdrawal>) -> Result<()> {
+        ctx.accounts.fulfill()
+    }
+
+    pub fn cancel_withdrawal(ctx: Context<CancelWithdrawal>) -> Result<()> {
+        ctx.accounts.cancel()
     }
 }
@@ -0,0 +1,52 @@
+// protocols/jupiter.rs
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
+use crate::state::Position;
+
+// Jupiter Aggregator v6 Program ID (mainnet default)
+pub const JUPITER_PROGRAM_ID: Pubkey = pubkey!("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+
+// Helper functions for Jupiter integration
+impl Position {
+    /// Execute a Jupiter route built off-chain (quote + swap-instructions API)
+    /// with the position PDA as the user transfer authority.
+    /// `route_accounts` must be the route's account list in order.
+    pub fn jupiter_swap_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        jupiter_program: &AccountInfo<'info>,
+        route_accounts: &[AccountInfo<'info>],
+        route_data: Vec<u8>,
+    ) -> Result<()> {
+        msg!("Swapping via Jupiter with {} route accounts", route_accounts.len());
+        
+        // Create position signer seeds
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
+        
+        // The position PDA is the only signer we can provide
+        let account_metas = route_accounts
+            .iter()
+            .map(|account| AccountMeta {
+                pubkey: account.key(),
+                is_signer: account.key() == position_account_info.key(),
+                is_writable: account.is_writable,
+            })
+            .collect();
+        
+        let instruction = Instruction {
+            program_id: jupiter_program.key(),
+            accounts: account_metas,
+            data: route_data,
+        };
+        
+        let mut account_infos = route_accounts.to_vec();
+        account_infos.push(jupiter_program.clone());
+        
+        // Execute CPI using invoke_signed
+        invoke_signed(&instruction, &account_infos, signer_seeds)?;
+        
+        Ok(())
+    }
+}
@@ -1,13 +1,20 @@
 // protocols/kamino.rs
 use anchor_lang::prelude::*;
-use anchor_spl::token::{Token, TokenAccount, Mint};
+use anchor_spl::token::{accessor, Token, TokenAccount, Mint};
 use anchor_lang::solana_program::{instruction::Instruction, program::invoke_signed};
 use crate::state::Position;
 use crate::errors::ErrorCode;
+use crate::strategy::{curve_borrow_rate_bps, supply_apy_bps};
 
-// Kamino Lending Program ID (mainnet/devnet)
+// Kamino Lending Program ID (mainnet/devnet default)
 pub const KAMINO_LENDING_PROGRAM_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
 
+// Kamino Farms Program ID (reserve incentives for obligations)
+pub const KAMINO_FARMS_PROGRAM_ID: Pubkey = pubkey!("FarmsPZpWu9i7Kky8tPN37rs2TpmMrAZrC7S7vJa91Hr");
+
+// Seed for an obligation's farm user state: [b"user", farm_state, obligation]
+pub const FARM_USER_STATE_SEED: &[u8] = b"user";
+
 // Kamino instruction discriminators (from their IDL)
 pub const INIT_OBLIGATION_DISCRIMINATOR: [u8; 8] = [251, 10, 231, 76, 27, 11, 159, 11];
 pub const REFRESH_RESERVE_DISCRIMINATOR: [u8; 8] = [22, 92, 237, 58, 232, 143, 59, 3];
@@ -16,6 +23,11 @@ pub const DEPOSIT_RESERVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [216, 144, 179, 156
 pub const REDEEM_RESERVE_COLLATERAL_DISCRIMINATOR: [u8; 8] = [30, 241, 52, 195, 5, 91, 199, 245];
 pub const DEPOSIT_OBLIGATION_COLLATERAL_DISCRIMINATOR: [u8; 8] = [179, 184, 11, 107, 133, 238, 98, 248];
 pub const WITHDRAW_OBLIGATION_COLLATERAL_DISCRIMINATOR: [u8; 8] = [176, 105, 7, 141, 193, 120, 84, 88];
+pub const BORROW_OBLIGATION_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [121, 127, 18, 204, 73, 245, 225, 65];
+pub const REPAY_OBLIGATION_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [145, 178, 13, 225, 76, 240, 147, 72];
+
+// Kamino Farms instruction discriminators
+pub const HARVEST_REWARD_DISCRIMINATOR: [u8; 8] = [68, 200, 228, 233, 184, 32, 226, 188];
 
 // Kamino Reserve Configuration
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
@@ -53,12 +65,294 @@ pub struct ObligationLiquidity {
     pub market_value: u128,
 }
 
+// Raw account layouts (offsets include the 8-byte Anchor discriminator)
+pub const OBLIGATION_ACCOUNT_DISCRIMINATOR: [u8; 8] = [168, 206, 141, 106, 88, 76, 172, 167];
+pub const RESERVE_ACCOUNT_DISCRIMINATOR: [u8; 8] = [43, 242, 204, 202, 26, 247, 59, 127];
+pub const LENDING_MARKET_ACCOUNT_DISCRIMINATOR: [u8; 8] = [246, 114, 50, 98, 72, 157, 28, 120];
+
+// PDA seeds used by klend
+pub const LENDING_MARKET_AUTHORITY_SEED: &[u8] = b"lma";
+pub const RESERVE_LIQUIDITY_SUPPLY_SEED: &[u8] = b"reserve_liq_supply";
+pub const RESERVE_COLLATERAL_MINT_SEED: &[u8] = b"reserve_coll_mint";
+pub const RESERVE_COLLATERAL_SUPPLY_SEED: &[u8] = b"reserve_coll_supply";
+pub const RESERVE_FEE_VAULT_SEED: &[u8] = b"fee_receiver";
+
+const OBLIGATION_LENDING_MARKET_OFFSET: usize = 32;
+const OBLIGATION_OWNER_OFFSET: usize = 64;
+const OBLIGATION_DEPOSITS_OFFSET: usize = 96;
+const OBLIGATION_DEPOSIT_LEN: usize = 136;
+const OBLIGATION_MAX_DEPOSITS: usize = 8;
+const OBLIGATION_BORROWS_OFFSET: usize = 1208;
+const OBLIGATION_BORROW_LEN: usize = 200;
+const OBLIGATION_BORROWED_AMOUNT_SF_OFFSET: usize = 88; // within each borrow entry
+const OBLIGATION_MAX_BORROWS: usize = 5;
+
+const RESERVE_LENDING_MARKET_OFFSET: usize = 32;
+const RESERVE_LIQUIDITY_MINT_OFFSET: usize = 128;
+const RESERVE_AVAILABLE_AMOUNT_OFFSET: usize = 224;
+const RESERVE_BORROWED_AMOUNT_SF_OFFSET: usize = 232;
+const RESERVE_PROTOCOL_FEES_SF_OFFSET: usize = 344;
+const RESERVE_REFERRER_FEES_SF_OFFSET: usize = 360;
+const RESERVE_PENDING_REFERRER_FEES_SF_OFFSET: usize = 376;
+const RESERVE_COLLATERAL_SUPPLY_OFFSET: usize = 2592;
+const RESERVE_CONFIG_PROTOCOL_TAKE_RATE_PCT_OFFSET: usize = 4870;
+const RESERVE_CONFIG_LTV_PCT_OFFSET: usize = 4872;
+const RESERVE_CONFIG_LIQUIDATION_THRESHOLD_PCT_OFFSET: usize = 4873;
+const RESERVE_CONFIG_BORROW_RATE_CURVE_OFFSET: usize = 4920;
+const BORROW_RATE_CURVE_POINTS: usize = 11; // (utilization bps, borrow rate bps) as u32 pairs
+
+// Kamino scaled fractions carry 60 fractional bits
+const SCALED_FRACTION_BITS: u32 = 60;
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    let bytes: [u8; 32] = data.get(offset..offset + 32)
+        .ok_or(ErrorCode::InvalidAccountData)?
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidAccountData)?;
+    Ok(Pubkey::new_from_array(bytes))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32> {
+    let bytes: [u8; 4] = data.get(offset..offset + 4)
+        .ok_or(ErrorCode::InvalidAccountData)?
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidAccountData)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes: [u8; 8] = data.get(offset..offset + 8)
+        .ok_or(ErrorCode::InvalidAccountData)?
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+fn read_u128(data: &[u8], offset: usize) -> Result<u128> {
+    let bytes: [u8; 16] = data.get(offset..offset + 16)
+        .ok_or(ErrorCode::InvalidAccountData)?
+        .try_into()
+        .map_err(|_| ErrorCode::InvalidAccountData)?;
+    Ok(u128::from_le_bytes(bytes))
+}
+
+fn check_kamino_account(account: &AccountInfo, discriminator: &[u8; 8], program_id: &Pubkey) -> Result<()> {
+    require_keys_eq!(*account.owner, *program_id, ErrorCode::InvalidAccountData);
+    let data = account.try_borrow_data()?;
+    require!(data.len() >= 8 && data[..8] == discriminator[..], ErrorCode::InvalidAccountData);
+    Ok(())
+}
+
+/// Owner recorded on a Kamino obligation
+pub fn read_obligation_owner(obligation: &AccountInfo, program_id: &Pubkey) -> Result<Pubkey> {
+    check_kamino_account(obligation, &OBLIGATION_ACCOUNT_DISCRIMINATOR, program_id)?;
+    read_pubkey(&obligation.try_borrow_data()?, OBLIGATION_OWNER_OFFSET)
+}
+
+/// Collateral (cToken) amount the obligation holds in `reserve`
+pub fn read_obligation_deposit(obligation: &AccountInfo, reserve: &Pubkey, program_id: &Pubkey) -> Result<u64> {
+    check_kamino_account(obligation, &OBLIGATION_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = obligation.try_borrow_data()?;
+
+    for i in 0..OBLIGATION_MAX_DEPOSITS {
+        let offset = OBLIGATION_DEPOSITS_OFFSET + i * OBLIGATION_DEPOSIT_LEN;
+        if read_pubkey(&data, offset)? == *reserve {
+            return read_u64(&data, offset + 32);
+        }
+    }
+
+    Ok(0)
+}
+
+/// Liquidity the obligation owes to `reserve`, accrued interest included
+pub fn read_obligation_borrow(obligation: &AccountInfo, reserve: &Pubkey, program_id: &Pubkey) -> Result<u64> {
+    check_kamino_account(obligation, &OBLIGATION_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = obligation.try_borrow_data()?;
+
+    for i in 0..OBLIGATION_MAX_BORROWS {
+        let offset = OBLIGATION_BORROWS_OFFSET + i * OBLIGATION_BORROW_LEN;
+        if read_pubkey(&data, offset)? == *reserve {
+            let borrowed_sf = read_u128(&data, offset + OBLIGATION_BORROWED_AMOUNT_SF_OFFSET)?;
+            // Round up so the debt is never understated
+            let fraction_mask = (1u128 << SCALED_FRACTION_BITS) - 1;
+            let borrowed = (borrowed_sf >> SCALED_FRACTION_BITS) + u128::from(borrowed_sf & fraction_mask != 0);
+            return u64::try_from(borrowed).map_err(|_| ErrorCode::MathOverflow.into());
+        }
+    }
+
+    Ok(0)
+}
+
+/// Underlying liquidity the obligation holds in `reserve`, interest included
+pub fn read_obligation_liquidity(obligation: &AccountInfo, reserve: &AccountInfo, program_id: &Pubkey) -> Result<u64> {
+    let collateral = read_obligation_deposit(obligation, &reserve.key(), program_id)?;
+    collateral_to_liquidity(reserve, collateral, program_id)
+}
+
+/// Liquidity mint of a Kamino reserve
+pub fn read_reserve_liquidity_mint(reserve: &AccountInfo, program_id: &Pubkey) -> Result<Pubkey> {
+    check_kamino_account(reserve, &RESERVE_ACCOUNT_DISCRIMINATOR, program_id)?;
+    read_pubkey(&reserve.try_borrow_data()?, RESERVE_LIQUIDITY_MINT_OFFSET)
+}
+
+/// Liquidity a reserve can pay out right now, i.e. not lent out to borrowers
+pub fn read_reserve_available_liquidity(reserve: &AccountInfo, program_id: &Pubkey) -> Result<u64> {
+    check_kamino_account(reserve, &RESERVE_ACCOUNT_DISCRIMINATOR, program_id)?;
+    read_u64(&reserve.try_borrow_data()?, RESERVE_AVAILABLE_AMOUNT_OFFSET)
+}
+
+/// Current supply APY of a reserve in bps, from its borrow rate curve at
+/// the current utilization
+pub fn read_reserve_supply_apy_bps(reserve: &AccountInfo, program_id: &Pubkey) -> Result<u64> {
+    check_kamino_account(reserve, &RESERVE_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = reserve.try_borrow_data()?;
+
+    let available = read_u64(&data, RESERVE_AVAILABLE_AMOUNT_OFFSET)? as u128;
+    let borrowed = read_u128(&data, RESERVE_BORROWED_AMOUNT_SF_OFFSET)? >> SCALED_FRACTION_BITS;
+    let total = available.checked_add(borrowed).ok_or(ErrorCode::MathOverflow)?;
+    if total == 0 {
+        return Ok(0);
+    }
+    let utilization_bps = (borrowed * 10_000 / total) as u32;
+
+    let mut curve = [(0u32, 0u32); BORROW_RATE_CURVE_POINTS];
+    for (i, point) in curve.iter_mut().enumerate() {
+        let offset = RESERVE_CONFIG_BORROW_RATE_CURVE_OFFSET + i * 8;
+        *point = (read_u32(&data, offset)?, read_u32(&data, offset + 4)?);
+    }
+    let take_rate_pct = *data.get(RESERVE_CONFIG_PROTOCOL_TAKE_RATE_PCT_OFFSET)
+        .ok_or(ErrorCode::InvalidAccountData)?;
+
+    Ok(supply_apy_bps(curve_borrow_rate_bps(&curve, utilization_bps), utilization_bps, take_rate_pct))
+}
+
+/// Max loan-to-value and liquidation threshold of a reserve's collateral, in bps
+pub fn read_reserve_ltv_limits(reserve: &AccountInfo, program_id: &Pubkey) -> Result<(u16, u16)> {
+    check_kamino_account(reserve, &RESERVE_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = reserve.try_borrow_data()?;
+
+    let ltv_pct = *data.get(RESERVE_CONFIG_LTV_PCT_OFFSET).ok_or(ErrorCode::InvalidAccountData)?;
+    let liquidation_threshold_pct = *data.get(RESERVE_CONFIG_LIQUIDATION_THRESHOLD_PCT_OFFSET)
+        .ok_or(ErrorCode::InvalidAccountData)?;
+
+    Ok((ltv_pct as u16 * 100, liquidation_threshold_pct as u16 * 100))
+}
+
+/// Lending market authority PDA that signs for reserve vaults
+pub fn kamino_market_authority(lending_market: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[LENDING_MARKET_AUTHORITY_SEED, lending_market.as_ref()],
+        program_id,
+    ).0
+}
+
+/// Vaults and collateral mint klend derives from a reserve address
+pub struct KaminoReservePdas {
+    pub liquidity_supply: Pubkey,
+    pub collateral_mint: Pubkey,
+    pub collateral_supply: Pubkey,
+    pub fee_receiver: Pubkey,
+}
+
+pub fn kamino_reserve_pdas(reserve: &Pubkey, program_id: &Pubkey) -> KaminoReservePdas {
+    let derive = |seed: &[u8]| {
+        Pubkey::find_program_address(&[seed, reserve.as_ref()], program_id).0
+    };
+
+    KaminoReservePdas {
+        liquidity_supply: derive(RESERVE_LIQUIDITY_SUPPLY_SEED),
+        collateral_mint: derive(RESERVE_COLLATERAL_MINT_SEED),
+        collateral_supply: derive(RESERVE_COLLATERAL_SUPPLY_SEED),
+        fee_receiver: derive(RESERVE_FEE_VAULT_SEED),
+    }
+}
+
+/// Verify an account is a klend lending market
+pub fn validate_kamino_lending_market(lending_market: &AccountInfo, program_id: &Pubkey) -> Result<()> {
+    check_kamino_account(lending_market, &LENDING_MARKET_ACCOUNT_DISCRIMINATOR, program_id)
+}
+
+/// Verify a reserve belongs to `lending_market` and lends `mint`
+pub fn validate_kamino_reserve(reserve: &AccountInfo, lending_market: &Pubkey, mint: &Pubkey, program_id: &Pubkey) -> Result<()> {
+    check_kamino_account(reserve, &RESERVE_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = reserve.try_borrow_data()?;
+    
+    require_keys_eq!(read_pubkey(&data, RESERVE_LENDING_MARKET_OFFSET)?, *lending_market, ErrorCode::VenueMismatch);
+    require_keys_eq!(read_pubkey(&data, RESERVE_LIQUIDITY_MINT_OFFSET)?, *mint, ErrorCode::VenueMismatch);
+    Ok(())
+}
+
+/// Verify an obligation belongs to `lending_market` and is owned by `owner`
+pub fn validate_kamino_obligation(obligation: &AccountInfo, lending_market: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> Result<()> {
+    check_kamino_account(obligation, &OBLIGATION_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = obligation.try_borrow_data()?;
+    
+    require_keys_eq!(read_pubkey(&data, OBLIGATION_LENDING_MARKET_OFFSET)?, *lending_market, ErrorCode::VenueMismatch);
+    require_keys_eq!(read_pubkey(&data, OBLIGATION_OWNER_OFFSET)?, *owner, ErrorCode::VenueAccountNotOwned);
+    Ok(())
+}
+
+// Total liquidity backing a reserve's cTokens (available + borrowed - accrued
+// fees) and the cToken supply, i.e. the reserve's exchange rate
+fn reserve_exchange_rate(reserve: &AccountInfo, program_id: &Pubkey) -> Result<(u128, u128)> {
+    check_kamino_account(reserve, &RESERVE_ACCOUNT_DISCRIMINATOR, program_id)?;
+    let data = reserve.try_borrow_data()?;
+    
+    let borrowed_sf = read_u128(&data, RESERVE_BORROWED_AMOUNT_SF_OFFSET)?
+        .saturating_sub(read_u128(&data, RESERVE_PROTOCOL_FEES_SF_OFFSET)?)
+        .saturating_sub(read_u128(&data, RESERVE_REFERRER_FEES_SF_OFFSET)?)
+        .saturating_sub(read_u128(&data, RESERVE_PENDING_REFERRER_FEES_SF_OFFSET)?);
+    
+    let total_liquidity = (read_u64(&data, RESERVE_AVAILABLE_AMOUNT_OFFSET)? as u128)
+        .checked_add(borrowed_sf >> SCALED_FRACTION_BITS)
+        .ok_or(ErrorCode::MathOverflow)?;
+    
+    Ok((total_liquidity, read_u64(&data, RESERVE_COLLATERAL_SUPPLY_OFFSET)? as u128))
+}
+
+/// Convert reserve collateral into underlying liquidity at the reserve's
+/// current exchange rate (available + borrowed - accrued fees) / cToken supply
+pub fn collateral_to_liquidity(reserve: &AccountInfo, collateral_amount: u64, program_id: &Pubkey) -> Result<u64> {
+    let (total_liquidity, collateral_supply) = reserve_exchange_rate(reserve, program_id)?;
+    if collateral_supply == 0 {
+        return Ok(collateral_amount);
+    }
+    
+    let liquidity = (collateral_amount as u128)
+        .checked_mul(total_liquidity)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(collateral_supply)
+        .ok_or(ErrorCode::MathOverflow)?;
+    
+    u64::try_from(liquidity).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Inverse of collateral_to_liquidity: the cTokens worth `liquidity_amount`.
+/// Deposits round down like klend's mint; withdrawals round up so the
+/// redeemed liquidity covers the amount asked for.
+pub fn liquidity_to_collateral(reserve: &AccountInfo, liquidity_amount: u64, round_up: bool, program_id: &Pubkey) -> Result<u64> {
+    let (total_liquidity, collateral_supply) = reserve_exchange_rate(reserve, program_id)?;
+    if collateral_supply == 0 || total_liquidity == 0 {
+        return Ok(liquidity_amount);
+    }
+    
+    let scaled = (liquidity_amount as u128)
+        .checked_mul(collateral_supply)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let collateral = if round_up {
+        scaled.div_ceil(total_liquidity)
+    } else {
+        scaled / total_liquidity
+    };
+    
+    u64::try_from(collateral).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
 // Helper functions for Kamino integration
 impl Position {
     /// Initialize a Kamino obligation for the position
     pub fn init_kamino_obligation_cpi<'info>(
-        &mut self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         kamino_program: &AccountInfo<'info>,
         lending_market: &AccountInfo<'info>,
         obligation: &AccountInfo<'info>,
@@ -74,14 +368,10 @@ impl Position {
         instruction_data.extend_from_slice(&INIT_OBLIGATION_DISCRIMINATOR);
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         // Build account metas for CPI
         let account_metas = vec![
@@ -94,7 +384,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: KAMINO_LENDING_PROGRAM_ID,
+            program_id: kamino_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -114,7 +404,7 @@ impl Position {
         )?;
         
         // Store obligation reference
-        self.kamino_obligation = Some(obligation.key());
+        position.load_mut()?.set_kamino_obligation(Some(obligation.key()));
         
         msg!("Kamino obligation initialized");
         Ok(())
@@ -122,14 +412,14 @@ impl Position {
     
     /// Deposit tokens to Kamino lending
     pub fn deposit_to_kamino_cpi<'info>(
-        &mut self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         kamino_program: &AccountInfo<'info>,
         source_liquidity: &Account<'info, TokenAccount>,
         destination_collateral: &AccountInfo<'info>,
         reserve: &AccountInfo<'info>,
         reserve_liquidity_supply: &AccountInfo<'info>,
         reserve_collateral_mint: &AccountInfo<'info>,
+        reserve_collateral_supply: &AccountInfo<'info>,
         lending_market: &AccountInfo<'info>,
         lending_market_authority: &AccountInfo<'info>,
         obligation: &AccountInfo<'info>,
@@ -141,26 +431,25 @@ impl Position {
         msg!("Depositing {} to Kamino lending...", liquidity_amount);
         
         // First, refresh the reserve
-        self.refresh_kamino_reserve_cpi(
+        Self::refresh_kamino_reserve_cpi(
             kamino_program,
             reserve,
             clock,
         )?;
         
+        // The obligation holds cTokens, minted at the refreshed exchange rate
+        let collateral_amount = liquidity_to_collateral(reserve, liquidity_amount, false, &kamino_program.key())?;
+        
         // Build deposit instruction data
         let mut instruction_data = Vec::new();
         instruction_data.extend_from_slice(&DEPOSIT_RESERVE_LIQUIDITY_DISCRIMINATOR);
         liquidity_amount.serialize(&mut instruction_data)?;
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         // Build account metas for deposit
         let account_metas = vec![
@@ -177,7 +466,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: KAMINO_LENDING_PROGRAM_ID,
+            program_id: kamino_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -200,19 +489,19 @@ impl Position {
             signer_seeds,
         )?;
         
-        // Now deposit the collateral to the obligation
-        self.deposit_obligation_collateral_cpi(
-            position_account_info,
+        // Now deposit the minted collateral to the obligation
+        Self::deposit_obligation_collateral_cpi(
+            position,
             kamino_program,
             destination_collateral,
-            reserve_collateral_mint,
+            reserve_collateral_supply,
             reserve,
             obligation,
             lending_market,
             owner,
             clock,
             token_program,
-            liquidity_amount,
+            collateral_amount,
         )?;
         
         msg!("Successfully deposited {} to Kamino", liquidity_amount);
@@ -221,28 +510,35 @@ impl Position {
     
     /// Withdraw tokens from Kamino lending
     pub fn withdraw_from_kamino_cpi<'info>(
-        &mut self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         kamino_program: &AccountInfo<'info>,
         source_collateral: &AccountInfo<'info>,
         destination_liquidity: &Account<'info, TokenAccount>,
         reserve: &AccountInfo<'info>,
         reserve_liquidity_supply: &AccountInfo<'info>,
         reserve_collateral_mint: &AccountInfo<'info>,
+        reserve_collateral_supply: &AccountInfo<'info>,
         lending_market: &AccountInfo<'info>,
         lending_market_authority: &AccountInfo<'info>,
         obligation: &AccountInfo<'info>,
         owner: &AccountInfo<'info>,
         clock: &Sysvar<'info, Clock>,
         token_program: &Program<'info, Token>,
-        collateral_amount: u64,
-    ) -> Result<()> {
-        msg!("Withdrawing {} from Kamino lending...", collateral_amount);
+        liquidity_amount: u64,
+    ) -> Result<u64> {
+        msg!("Withdrawing {} from Kamino lending...", liquidity_amount);
+        
+        // The obligation holds cTokens; never ask for more than it has
+        let kamino_program_id = kamino_program.key();
+        let collateral_amount = liquidity_to_collateral(reserve, liquidity_amount, true, &kamino_program_id)?
+            .min(read_obligation_deposit(obligation, &reserve.key(), &kamino_program_id)?);
+        let balance_before = accessor::amount(&destination_liquidity.to_account_info())?;
         
         // First, withdraw collateral from obligation
-        self.withdraw_obligation_collateral_cpi(
-            position_account_info,
+        Self::withdraw_obligation_collateral_cpi(
+            position,
             kamino_program,
+            reserve_collateral_supply,
             source_collateral,
             reserve,
             obligation,
@@ -255,8 +551,8 @@ impl Position {
         )?;
         
         // Then redeem the collateral for liquidity
-        self.redeem_reserve_collateral_cpi(
-            position_account_info,
+        Self::redeem_reserve_collateral_cpi(
+            position,
             kamino_program,
             source_collateral,
             destination_liquidity,
@@ -270,13 +566,200 @@ impl Position {
             collateral_amount,
         )?;
         
-        msg!("Successfully withdrew {} from Kamino", collateral_amount);
+        // Liquidity actually redeemed, which the caller books into the vault
+        let received = accessor::amount(&destination_liquidity.to_account_info())?
+            .checked_sub(balance_before)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        msg!("Successfully withdrew {} from Kamino ({} collateral)", received, collateral_amount);
+        Ok(received)
+    }
+    
+    /// Borrow liquidity against the obligation's collateral. Every reserve the
+    /// obligation touches is refreshed first, deposits before borrows, as klend
+    /// requires a fresh obligation in the same instruction sequence.
+    pub fn borrow_from_kamino_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        kamino_program: &AccountInfo<'info>,
+        obligation: &AccountInfo<'info>,
+        lending_market: &AccountInfo<'info>,
+        lending_market_authority: &AccountInfo<'info>,
+        borrow_reserve: &AccountInfo<'info>,
+        borrow_reserve_liquidity_mint: &Account<'info, Mint>,
+        reserve_source_liquidity: &AccountInfo<'info>,
+        borrow_reserve_fee_receiver: &AccountInfo<'info>,
+        destination_liquidity: &Account<'info, TokenAccount>,
+        obligation_reserves: &[&AccountInfo<'info>],
+        instruction_sysvar: &AccountInfo<'info>,
+        clock: &Sysvar<'info, Clock>,
+        token_program: &Program<'info, Token>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        msg!("Borrowing {} from Kamino...", liquidity_amount);
+        
+        // A first borrow isn't among the obligation's reserves yet
+        Self::refresh_kamino_reserve_cpi(kamino_program, borrow_reserve, clock)?;
+        Self::refresh_kamino_obligation_cpi(kamino_program, lending_market, obligation, obligation_reserves, clock)?;
+        
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&BORROW_OBLIGATION_LIQUIDITY_DISCRIMINATOR);
+        liquidity_amount.serialize(&mut instruction_data)?;
+        
+        // Create position signer seeds
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
+        
+        // The optional referrer token state is passed as the klend program itself
+        let account_metas = vec![
+            AccountMeta::new_readonly(position_account_info.key(), true),
+            AccountMeta::new(obligation.key(), false),
+            AccountMeta::new_readonly(lending_market.key(), false),
+            AccountMeta::new_readonly(lending_market_authority.key(), false),
+            AccountMeta::new(borrow_reserve.key(), false),
+            AccountMeta::new_readonly(borrow_reserve_liquidity_mint.key(), false),
+            AccountMeta::new(reserve_source_liquidity.key(), false),
+            AccountMeta::new(borrow_reserve_fee_receiver.key(), false),
+            AccountMeta::new(destination_liquidity.key(), false),
+            AccountMeta::new_readonly(kamino_program.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(instruction_sysvar.key(), false),
+        ];
+        
+        let instruction = Instruction {
+            program_id: kamino_program.key(),
+            accounts: account_metas,
+            data: instruction_data,
+        };
+        
+        invoke_signed(
+            &instruction,
+            &[
+                position_account_info.clone(),
+                obligation.clone(),
+                lending_market.clone(),
+                lending_market_authority.clone(),
+                borrow_reserve.clone(),
+                borrow_reserve_liquidity_mint.to_account_info(),
+                reserve_source_liquidity.clone(),
+                borrow_reserve_fee_receiver.clone(),
+                destination_liquidity.to_account_info(),
+                kamino_program.clone(),
+                token_program.to_account_info(),
+                instruction_sysvar.clone(),
+            ],
+            signer_seeds,
+        )?;
+        
+        Ok(())
+    }
+    
+    /// Repay borrowed liquidity from a position vault
+    pub fn repay_to_kamino_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        kamino_program: &AccountInfo<'info>,
+        obligation: &AccountInfo<'info>,
+        lending_market: &AccountInfo<'info>,
+        repay_reserve: &AccountInfo<'info>,
+        repay_reserve_liquidity_mint: &Account<'info, Mint>,
+        reserve_destination_liquidity: &AccountInfo<'info>,
+        source_liquidity: &Account<'info, TokenAccount>,
+        obligation_reserves: &[&AccountInfo<'info>],
+        instruction_sysvar: &AccountInfo<'info>,
+        clock: &Sysvar<'info, Clock>,
+        token_program: &Program<'info, Token>,
+        liquidity_amount: u64,
+    ) -> Result<()> {
+        msg!("Repaying {} to Kamino...", liquidity_amount);
+        
+        Self::refresh_kamino_obligation_cpi(kamino_program, lending_market, obligation, obligation_reserves, clock)?;
+        
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&REPAY_OBLIGATION_LIQUIDITY_DISCRIMINATOR);
+        liquidity_amount.serialize(&mut instruction_data)?;
+        
+        // Create position signer seeds
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
+        
+        let account_metas = vec![
+            AccountMeta::new_readonly(position_account_info.key(), true),
+            AccountMeta::new(obligation.key(), false),
+            AccountMeta::new_readonly(lending_market.key(), false),
+            AccountMeta::new(repay_reserve.key(), false),
+            AccountMeta::new_readonly(repay_reserve_liquidity_mint.key(), false),
+            AccountMeta::new(reserve_destination_liquidity.key(), false),
+            AccountMeta::new(source_liquidity.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(instruction_sysvar.key(), false),
+        ];
+        
+        let instruction = Instruction {
+            program_id: kamino_program.key(),
+            accounts: account_metas,
+            data: instruction_data,
+        };
+        
+        invoke_signed(
+            &instruction,
+            &[
+                position_account_info.clone(),
+                obligation.clone(),
+                lending_market.clone(),
+                repay_reserve.clone(),
+                repay_reserve_liquidity_mint.to_account_info(),
+                reserve_destination_liquidity.clone(),
+                source_liquidity.to_account_info(),
+                token_program.to_account_info(),
+                instruction_sysvar.clone(),
+            ],
+            signer_seeds,
+        )?;
+        
         Ok(())
     }
     
     // Private helper methods
+    fn refresh_kamino_obligation_cpi<'info>(
+        kamino_program: &AccountInfo<'info>,
+        lending_market: &AccountInfo<'info>,
+        obligation: &AccountInfo<'info>,
+        obligation_reserves: &[&AccountInfo<'info>],
+        clock: &Sysvar<'info, Clock>,
+    ) -> Result<()> {
+        for reserve in obligation_reserves {
+            Self::refresh_kamino_reserve_cpi(kamino_program, reserve, clock)?;
+        }
+        
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&REFRESH_OBLIGATION_DISCRIMINATOR);
+        
+        // Deposit then borrow reserves follow as remaining accounts
+        let mut account_metas = vec![
+            AccountMeta::new_readonly(lending_market.key(), false),
+            AccountMeta::new(obligation.key(), false),
+        ];
+        let mut account_infos = vec![lending_market.clone(), obligation.clone()];
+        for reserve in obligation_reserves {
+            account_metas.push(AccountMeta::new_readonly(reserve.key(), false));
+            account_infos.push((*reserve).clone());
+        }
+        
+        let instruction = Instruction {
+            program_id: kamino_program.key(),
+            accounts: account_metas,
+            data: instruction_data,
+        };
+        
+        invoke_signed(&instruction, &account_infos, &[])?;
+        
+        Ok(())
+    }
+    
     fn refresh_kamino_reserve_cpi<'info>(
-        &self,
         kamino_program: &AccountInfo<'info>,
         reserve: &AccountInfo<'info>,
         clock: &Sysvar<'info, Clock>,
@@ -290,7 +773,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: KAMINO_LENDING_PROGRAM_ID,
+            program_id: kamino_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -308,11 +791,10 @@ impl Position {
     }
     
     fn deposit_obligation_collateral_cpi<'info>(
-        &self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         kamino_program: &AccountInfo<'info>,
         source_collateral: &AccountInfo<'info>,
-        reserve_collateral_mint: &AccountInfo<'info>,
+        reserve_collateral_supply: &AccountInfo<'info>,
         reserve: &AccountInfo<'info>,
         obligation: &AccountInfo<'info>,
         lending_market: &AccountInfo<'info>,
@@ -326,18 +808,14 @@ impl Position {
         collateral_amount.serialize(&mut instruction_data)?;
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         let account_metas = vec![
             AccountMeta::new(source_collateral.key(), false),
-            AccountMeta::new_readonly(reserve_collateral_mint.key(), false),
+            AccountMeta::new(reserve_collateral_supply.key(), false),
             AccountMeta::new_readonly(reserve.key(), false),
             AccountMeta::new(obligation.key(), false),
             AccountMeta::new_readonly(lending_market.key(), false),
@@ -347,7 +825,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: KAMINO_LENDING_PROGRAM_ID,
+            program_id: kamino_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -356,7 +834,7 @@ impl Position {
             &instruction,
             &[
                 source_collateral.clone(),
-                reserve_collateral_mint.clone(),
+                reserve_collateral_supply.clone(),
                 reserve.clone(),
                 obligation.clone(),
                 lending_market.clone(),
@@ -371,9 +849,9 @@ impl Position {
     }
     
     fn withdraw_obligation_collateral_cpi<'info>(
-        &self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         kamino_program: &AccountInfo<'info>,
+        reserve_collateral_supply: &AccountInfo<'info>,
         destination_collateral: &AccountInfo<'info>,
         reserve: &AccountInfo<'info>,
         obligation: &AccountInfo<'info>,
@@ -389,16 +867,13 @@ impl Position {
         collateral_amount.serialize(&mut instruction_data)?;
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         let account_metas = vec![
+            AccountMeta::new(reserve_collateral_supply.key(), false),
             AccountMeta::new(destination_collateral.key(), false),
             AccountMeta::new_readonly(reserve.key(), false),
             AccountMeta::new(obligation.key(), false),
@@ -410,7 +885,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: KAMINO_LENDING_PROGRAM_ID,
+            program_id: kamino_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -418,6 +893,7 @@ impl Position {
         invoke_signed(
             &instruction,
             &[
+                reserve_collateral_supply.clone(),
                 destination_collateral.clone(),
                 reserve.clone(),
                 obligation.clone(),
@@ -434,8 +910,7 @@ impl Position {
     }
     
     fn redeem_reserve_collateral_cpi<'info>(
-        &self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         kamino_program: &AccountInfo<'info>,
         source_collateral: &AccountInfo<'info>,
         destination_liquidity: &Account<'info, TokenAccount>,
@@ -453,14 +928,10 @@ impl Position {
         collateral_amount.serialize(&mut instruction_data)?;
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         let account_metas = vec![
             AccountMeta::new(source_collateral.key(), false),
@@ -476,7 +947,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: KAMINO_LENDING_PROGRAM_ID,
+            program_id: kamino_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -500,4 +971,243 @@ impl Position {
         
         Ok(())
     }
+    
+    /// Harvest accrued farm rewards for the position's obligation
+    pub fn harvest_kamino_farm_reward_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        farms_program: &AccountInfo<'info>,
+        user_state: &AccountInfo<'info>,
+        farm_state: &AccountInfo<'info>,
+        global_config: &AccountInfo<'info>,
+        reward_mint: &Account<'info, Mint>,
+        user_reward_ata: &Account<'info, TokenAccount>,
+        rewards_vault: &AccountInfo<'info>,
+        rewards_treasury_vault: &AccountInfo<'info>,
+        farm_vaults_authority: &AccountInfo<'info>,
+        scope_prices: Option<&AccountInfo<'info>>,
+        token_program: &Program<'info, Token>,
+        reward_index: u64,
+    ) -> Result<()> {
+        msg!("Harvesting Kamino farm reward {}...", reward_index);
+        
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&HARVEST_REWARD_DISCRIMINATOR);
+        reward_index.serialize(&mut instruction_data)?;
+        
+        // Create position signer seeds
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
+        
+        // Absent optional accounts are passed as the farms program itself
+        let scope_prices = scope_prices.unwrap_or(farms_program);
+        
+        let account_metas = vec![
+            AccountMeta::new(position_account_info.key(), true),
+            AccountMeta::new(user_state.key(), false),
+            AccountMeta::new(farm_state.key(), false),
+            AccountMeta::new_readonly(global_config.key(), false),
+            AccountMeta::new_readonly(reward_mint.key(), false),
+            AccountMeta::new(user_reward_ata.key(), false),
+            AccountMeta::new(rewards_vault.key(), false),
+            AccountMeta::new(rewards_treasury_vault.key(), false),
+            AccountMeta::new_readonly(farm_vaults_authority.key(), false),
+            AccountMeta::new_readonly(scope_prices.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+        ];
+        
+        let instruction = Instruction {
+            program_id: farms_program.key(),
+            accounts: account_metas,
+            data: instruction_data,
+        };
+        
+        invoke_signed(
+            &instruction,
+            &[
+                position_account_info.clone(),
+                user_state.clone(),
+                farm_state.clone(),
+                global_config.clone(),
+                reward_mint.to_account_info(),
+                user_reward_ata.to_account_info(),
+                rewards_vault.clone(),
+                rewards_treasury_vault.clone(),
+                farm_vaults_authority.clone(),
+                scope_prices.clone(),
+                token_program.to_account_info(),
+                farms_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::test_utils::{account_data, error_code, with_account};
+
+    const OBLIGATION_LEN: usize = OBLIGATION_BORROWS_OFFSET + OBLIGATION_MAX_BORROWS * OBLIGATION_BORROW_LEN;
+    const RESERVE_LEN: usize = RESERVE_CONFIG_BORROW_RATE_CURVE_OFFSET + BORROW_RATE_CURVE_POINTS * 8;
+
+    #[test]
+    fn reserve_checks_market_and_mint() {
+        let (market, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = account_data(&RESERVE_ACCOUNT_DISCRIMINATOR, RESERVE_LEN, &[
+            (RESERVE_LENDING_MARKET_OFFSET, market.as_ref()),
+            (RESERVE_LIQUIDITY_MINT_OFFSET, mint.as_ref()),
+        ]);
+        let program = KAMINO_LENDING_PROGRAM_ID;
+
+        with_account(&program, &mut data, |info| {
+            assert!(validate_kamino_reserve(info, &market, &mint, &program).is_ok());
+            assert_eq!(
+                error_code(validate_kamino_reserve(info, &Pubkey::new_unique(), &mint, &program)),
+                u32::from(ErrorCode::VenueMismatch)
+            );
+            assert_eq!(
+                error_code(validate_kamino_reserve(info, &market, &Pubkey::new_unique(), &program)),
+                u32::from(ErrorCode::VenueMismatch)
+            );
+            assert_eq!(
+                error_code(validate_kamino_reserve(info, &market, &mint, &Pubkey::new_unique())),
+                u32::from(ErrorCode::InvalidAccountData)
+            );
+        });
+    }
+
+    #[test]
+    fn obligation_checks_market_and_owner() {
+        let (market, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = account_data(&OBLIGATION_ACCOUNT_DISCRIMINATOR, OBLIGATION_LEN, &[
+            (OBLIGATION_LENDING_MARKET_OFFSET, market.as_ref()),
+            (OBLIGATION_OWNER_OFFSET, owner.as_ref()),
+        ]);
+        let program = KAMINO_LENDING_PROGRAM_ID;
+
+        with_account(&program, &mut data, |info| {
+            assert!(validate_kamino_obligation(info, &market, &owner, &program).is_ok());
+            assert_eq!(read_obligation_owner(info, &program).unwrap(), owner);
+            assert_eq!(
+                error_code(validate_kamino_obligation(info, &Pubkey::new_unique(), &owner, &program)),
+                u32::from(ErrorCode::VenueMismatch)
+            );
+            assert_eq!(
+                error_code(validate_kamino_obligation(info, &market, &Pubkey::new_unique(), &program)),
+                u32::from(ErrorCode::VenueAccountNotOwned)
+            );
+        });
+    }
+
+    #[test]
+    fn accounts_of_another_type_are_rejected() {
+        let program = KAMINO_LENDING_PROGRAM_ID;
+        let market = Pubkey::new_unique();
+
+        // An obligation passed as the reserve or the lending market
+        let mut data = account_data(&OBLIGATION_ACCOUNT_DISCRIMINATOR, RESERVE_LEN, &[
+            (RESERVE_LENDING_MARKET_OFFSET, market.as_ref()),
+        ]);
+        with_account(&program, &mut data, |info| {
+            assert_eq!(
+                error_code(validate_kamino_reserve(info, &market, &Pubkey::default(), &program)),
+                u32::from(ErrorCode::InvalidAccountData)
+            );
+            assert_eq!(error_code(validate_kamino_lending_market(info, &program)), u32::from(ErrorCode::InvalidAccountData));
+        });
+
+        let mut data = account_data(&LENDING_MARKET_ACCOUNT_DISCRIMINATOR, 8, &[]);
+        with_account(&program, &mut data, |info| {
+            assert!(validate_kamino_lending_market(info, &program).is_ok());
+        });
+
+        let mut data = vec![0; 4];
+        with_account(&program, &mut data, |info| {
+            assert_eq!(error_code(validate_kamino_lending_market(info, &program)), u32::from(ErrorCode::InvalidAccountData));
+        });
+    }
+
+    #[test]
+    fn reserve_supply_apy_follows_utilization() {
+        let mut curve = Vec::new();
+        for i in 0..BORROW_RATE_CURVE_POINTS as u32 {
+            // Linear curve: 0% to 20% borrow rate across 0-100% utilization
+            curve.extend_from_slice(&(i * 1_000).to_le_bytes());
+            curve.extend_from_slice(&(i * 200).to_le_bytes());
+        }
+        let borrowed_sf = 750_000u128 << SCALED_FRACTION_BITS;
+        let mut data = account_data(&RESERVE_ACCOUNT_DISCRIMINATOR, RESERVE_LEN, &[
+            (RESERVE_AVAILABLE_AMOUNT_OFFSET, &250_000u64.to_le_bytes()),
+            (RESERVE_BORROWED_AMOUNT_SF_OFFSET, &borrowed_sf.to_le_bytes()),
+            (RESERVE_CONFIG_PROTOCOL_TAKE_RATE_PCT_OFFSET, &[10]),
+            (RESERVE_CONFIG_BORROW_RATE_CURVE_OFFSET, &curve),
+        ]);
+        let program = KAMINO_LENDING_PROGRAM_ID;
+
+        // 75% utilization borrows at 15%; lenders get 90% of 75% of that
+        with_account(&program, &mut data, |info| {
+            assert_eq!(read_reserve_supply_apy_bps(info, &program).unwrap(), 1_012);
+        });
+
+        let mut empty = account_data(&RESERVE_ACCOUNT_DISCRIMINATOR, RESERVE_LEN, &[]);
+        with_account(&program, &mut empty, |info| {
+            assert_eq!(read_reserve_supply_apy_bps(info, &program).unwrap(), 0);
+        });
+    }
+
+    #[test]
+    fn collateral_converts_at_the_reserve_exchange_rate() {
+        // 1.5M liquidity (1M available, 500k borrowed) backs 1M cTokens
+        let borrowed_sf = 500_000u128 << SCALED_FRACTION_BITS;
+        let mut data = account_data(&RESERVE_ACCOUNT_DISCRIMINATOR, RESERVE_LEN, &[
+            (RESERVE_AVAILABLE_AMOUNT_OFFSET, &1_000_000u64.to_le_bytes()),
+            (RESERVE_BORROWED_AMOUNT_SF_OFFSET, &borrowed_sf.to_le_bytes()),
+            (RESERVE_COLLATERAL_SUPPLY_OFFSET, &1_000_000u64.to_le_bytes()),
+        ]);
+        let program = KAMINO_LENDING_PROGRAM_ID;
+
+        with_account(&program, &mut data, |info| {
+            assert_eq!(collateral_to_liquidity(info, 200, &program).unwrap(), 300);
+            assert_eq!(liquidity_to_collateral(info, 300, false, &program).unwrap(), 200);
+            assert_eq!(liquidity_to_collateral(info, 300, true, &program).unwrap(), 200);
+            // 301 liquidity is 200.67 cTokens
+            assert_eq!(liquidity_to_collateral(info, 301, false, &program).unwrap(), 200);
+            assert_eq!(liquidity_to_collateral(info, 301, true, &program).unwrap(), 201);
+            // Rounding up on withdraw always redeems at least the amount asked for
+            let collateral = liquidity_to_collateral(info, 301, true, &program).unwrap();
+            assert!(collateral_to_liquidity(info, collateral, &program).unwrap() >= 301);
+        });
+    }
+
+    #[test]
+    fn empty_reserve_converts_one_to_one() {
+        let mut data = account_data(&RESERVE_ACCOUNT_DISCRIMINATOR, RESERVE_LEN, &[]);
+        let program = KAMINO_LENDING_PROGRAM_ID;
+
+        with_account(&program, &mut data, |info| {
+            assert_eq!(collateral_to_liquidity(info, 123, &program).unwrap(), 123);
+            assert_eq!(liquidity_to_collateral(info, 123, true, &program).unwrap(), 123);
+        });
+    }
+
+    #[test]
+    fn truncated_reserve_fails_instead_of_panicking() {
+        let (market, mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = account_data(&RESERVE_ACCOUNT_DISCRIMINATOR, RESERVE_LIQUIDITY_MINT_OFFSET + 16, &[
+            (RESERVE_LENDING_MARKET_OFFSET, market.as_ref()),
+        ]);
+        let program = KAMINO_LENDING_PROGRAM_ID;
+
+        with_account(&program, &mut data, |info| {
+            assert_eq!(
+                error_code(validate_kamino_reserve(info, &market, &mint, &program)),
+                u32::from(ErrorCode::InvalidAccountData)
+            );
+            assert_eq!(error_code(read_reserve_supply_apy_bps(info, &program)), u32::from(ErrorCode::InvalidAccountData));
+        });
+    }
 }
@@ -0,0 +1,80 @@
+// protocols/lookup_table.rs
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    address_lookup_table::{
+        instruction::{create_lookup_table, extend_lookup_table},
+        state::AddressLookupTable,
+    },
+    program::invoke_signed,
+};
+use crate::errors::ErrorCode;
+
+// Address Lookup Table Program ID (native)
+pub const ADDRESS_LOOKUP_TABLE_PROGRAM_ID: Pubkey = pubkey!("AddressLookupTab1e1111111111111111111111111");
+
+/// Addresses stored in a lookup table owned by `authority`
+pub fn read_lookup_table_addresses(lookup_table: &AccountInfo, authority: &Pubkey) -> Result<Vec<Pubkey>> {
+    require_keys_eq!(*lookup_table.owner, ADDRESS_LOOKUP_TABLE_PROGRAM_ID, ErrorCode::InvalidLookupTable);
+
+    let data = lookup_table.try_borrow_data()?;
+    let table = AddressLookupTable::deserialize(&data).map_err(|_| ErrorCode::InvalidLookupTable)?;
+    require!(table.meta.authority == Some(*authority), ErrorCode::InvalidLookupTable);
+
+    Ok(table.addresses.to_vec())
+}
+
+/// Create a lookup table at the address derived from `authority` and
+/// `recent_slot`, with the PDA signing through `signer_seeds` as authority
+pub fn create_lookup_table_signed<'info>(
+    lookup_table: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    lookup_table_program: &AccountInfo<'info>,
+    recent_slot: u64,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let (ix, address) = create_lookup_table(authority.key(), payer.key(), recent_slot);
+    require_keys_eq!(address, lookup_table.key(), ErrorCode::InvalidLookupTable);
+
+    invoke_signed(
+        &ix,
+        &[
+            lookup_table.clone(),
+            authority.clone(),
+            payer.clone(),
+            system_program.clone(),
+            lookup_table_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+
+    Ok(())
+}
+
+/// Append `new_addresses` to a lookup table, `payer` funding the extra rent
+pub fn extend_lookup_table_signed<'info>(
+    lookup_table: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    lookup_table_program: &AccountInfo<'info>,
+    new_addresses: Vec<Pubkey>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let ix = extend_lookup_table(lookup_table.key(), authority.key(), Some(payer.key()), new_addresses);
+
+    invoke_signed(
+        &ix,
+        &[
+            lookup_table.clone(),
+            authority.clone(),
+            payer.clone(),
+            system_program.clone(),
+            lookup_table_program.clone(),
+        ],
+        signer_seeds,
+    )?;
+
+    Ok(())
+}
@@ -5,13 +5,131 @@ use anchor_lang::solana_program::{instruction::Instruction, program::invoke_sign
 use crate::state::Position;
 use crate::errors::ErrorCode;
 
-// Meteora DLMM Program ID
+// Meteora DLMM Program ID (mainnet default)
 pub const METEORA_DLMM_PROGRAM_ID: Pubkey = pubkey!("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
 
 // Meteora instruction discriminators (from their IDL)
 pub const ADD_LIQUIDITY_BY_STRATEGY_DISCRIMINATOR: [u8; 8] = [158, 20, 230, 72, 165, 58, 72, 82];
 pub const REMOVE_LIQUIDITY_DISCRIMINATOR: [u8; 8] = [80, 85, 209, 72, 24, 206, 177, 108];
 pub const INITIALIZE_POSITION_DISCRIMINATOR: [u8; 8] = [95, 180, 10, 172, 84, 174, 232, 40];
+pub const CLAIM_FEE_DISCRIMINATOR: [u8; 8] = [169, 32, 79, 137, 136, 232, 70, 137];
+pub const CLAIM_REWARD_DISCRIMINATOR: [u8; 8] = [149, 95, 181, 242, 94, 90, 158, 162];
+
+// Raw account layouts (offsets include the 8-byte Anchor discriminator)
+pub const POSITION_V2_ACCOUNT_DISCRIMINATOR: [u8; 8] = [117, 176, 212, 199, 245, 180, 133, 182];
+pub const LB_PAIR_ACCOUNT_DISCRIMINATOR: [u8; 8] = [33, 11, 49, 98, 181, 101, 177, 13];
+pub const BIN_ARRAY_ACCOUNT_DISCRIMINATOR: [u8; 8] = [92, 142, 92, 220, 5, 148, 70, 181];
+
+// Anchor event CPI authority seed
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+const POSITION_V2_LB_PAIR_OFFSET: usize = 8;
+const POSITION_V2_OWNER_OFFSET: usize = 40;
+
+const LB_PAIR_PROTOCOL_SHARE_OFFSET: usize = 32; // parameters.protocol_share
+const LB_PAIR_TOKEN_X_MINT_OFFSET: usize = 88;
+const LB_PAIR_TOKEN_Y_MINT_OFFSET: usize = 120;
+const LB_PAIR_RESERVE_X_OFFSET: usize = 152;
+const LB_PAIR_RESERVE_Y_OFFSET: usize = 184;
+const LB_PAIR_PROTOCOL_FEE_X_OFFSET: usize = 216;
+const LB_PAIR_PROTOCOL_FEE_Y_OFFSET: usize = 224;
+
+const BIN_ARRAY_LB_PAIR_OFFSET: usize = 24;
+
+fn read_meteora_account<'a>(
+    account: &'a AccountInfo,
+    discriminator: &[u8; 8],
+    min_len: usize,
+    program_id: &Pubkey,
+) -> Result<std::cell::Ref<'a, &'a mut [u8]>> {
+    require_keys_eq!(*account.owner, *program_id, ErrorCode::InvalidAccountData);
+    
+    let data = account.try_borrow_data()?;
+    require!(
+        data.len() >= min_len && data[..8] == discriminator[..],
+        ErrorCode::InvalidAccountData
+    );
+    
+    Ok(data)
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    Pubkey::try_from(&data[offset..offset + 32]).map_err(|_| ErrorCode::InvalidAccountData.into())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64> {
+    let bytes: [u8; 8] = data[offset..offset + 8].try_into().map_err(|_| ErrorCode::InvalidAccountData)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Mints and reserves recorded on a Meteora LbPair
+pub struct LbPairAccounts {
+    pub token_x_mint: Pubkey,
+    pub token_y_mint: Pubkey,
+    pub reserve_x: Pubkey,
+    pub reserve_y: Pubkey,
+}
+
+pub fn read_lb_pair(lb_pair: &AccountInfo, program_id: &Pubkey) -> Result<LbPairAccounts> {
+    let data = read_meteora_account(lb_pair, &LB_PAIR_ACCOUNT_DISCRIMINATOR, LB_PAIR_RESERVE_Y_OFFSET + 32, program_id)?;
+    
+    Ok(LbPairAccounts {
+        token_x_mint: read_pubkey(&data, LB_PAIR_TOKEN_X_MINT_OFFSET)?,
+        token_y_mint: read_pubkey(&data, LB_PAIR_TOKEN_Y_MINT_OFFSET)?,
+        reserve_x: read_pubkey(&data, LB_PAIR_RESERVE_X_OFFSET)?,
+        reserve_y: read_pubkey(&data, LB_PAIR_RESERVE_Y_OFFSET)?,
+    })
+}
+
+/// Protocol fee accumulators of a Meteora LbPair. They only grow with swaps
+/// until the protocol claims them, and are a fixed share of all swap fees.
+pub struct LbPairFees {
+    pub protocol_fee_x: u64,
+    pub protocol_fee_y: u64,
+    pub protocol_share_bps: u16,
+}
+
+pub fn read_lb_pair_fees(lb_pair: &AccountInfo, program_id: &Pubkey) -> Result<LbPairFees> {
+    let data = read_meteora_account(lb_pair, &LB_PAIR_ACCOUNT_DISCRIMINATOR, LB_PAIR_PROTOCOL_FEE_Y_OFFSET + 8, program_id)?;
+    
+    Ok(LbPairFees {
+        protocol_fee_x: read_u64(&data, LB_PAIR_PROTOCOL_FEE_X_OFFSET)?,
+        protocol_fee_y: read_u64(&data, LB_PAIR_PROTOCOL_FEE_Y_OFFSET)?,
+        protocol_share_bps: u16::from_le_bytes([
+            data[LB_PAIR_PROTOCOL_SHARE_OFFSET],
+            data[LB_PAIR_PROTOCOL_SHARE_OFFSET + 1],
+        ]),
+    })
+}
+
+/// Verify a bin array belongs to `lb_pair`
+pub fn validate_bin_array(bin_array: &AccountInfo, lb_pair: &Pubkey, program_id: &Pubkey) -> Result<()> {
+    let data = read_meteora_account(bin_array, &BIN_ARRAY_ACCOUNT_DISCRIMINATOR, BIN_ARRAY_LB_PAIR_OFFSET + 32, program_id)?;
+    
+    require_keys_eq!(read_pubkey(&data, BIN_ARRAY_LB_PAIR_OFFSET)?, *lb_pair, ErrorCode::VenueMismatch);
+    Ok(())
+}
+
+/// Verify a DLMM position is on `lb_pair` and owned by `owner`
+pub fn validate_meteora_position(meteora_position: &AccountInfo, lb_pair: &Pubkey, owner: &Pubkey, program_id: &Pubkey) -> Result<()> {
+    let (position_lb_pair, position_owner) = read_meteora_position(meteora_position, program_id)?;
+    
+    require_keys_eq!(position_lb_pair, *lb_pair, ErrorCode::VenueMismatch);
+    require_keys_eq!(position_owner, *owner, ErrorCode::VenueAccountNotOwned);
+    Ok(())
+}
+
+/// Event CPI authority of the DLMM program
+pub fn meteora_event_authority(program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], program_id).0
+}
+
+/// Read `(lb_pair, owner)` from a Meteora PositionV2 account
+pub fn read_meteora_position(meteora_position: &AccountInfo, program_id: &Pubkey) -> Result<(Pubkey, Pubkey)> {
+    let data = read_meteora_account(meteora_position, &POSITION_V2_ACCOUNT_DISCRIMINATOR, POSITION_V2_OWNER_OFFSET + 32, program_id)?;
+    
+    Ok((read_pubkey(&data, POSITION_V2_LB_PAIR_OFFSET)?, read_pubkey(&data, POSITION_V2_OWNER_OFFSET)?))
+}
 
 // Meteora Strategy Types
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
@@ -47,8 +165,7 @@ pub struct BinLiquidityDistribution {
 // Helper functions for Meteora integration
 impl Position {
     pub fn open_meteora_position_cpi<'info>(
-        &mut self,
-        position_account_info: &AccountInfo<'info>,
+        position: &AccountLoader<'info, Position>,
         meteora_program: &AccountInfo<'info>,
         lb_pair: &AccountInfo<'info>,
         meteora_position: &AccountInfo<'info>,
@@ -70,11 +187,444 @@ impl Position {
     ) -> Result<()> {
         msg!("Opening Meteora DLMM position with CPI...");
         
-        // Calculate bin range based on stored LP range
+        let (range_min, range_max) = {
+            let position = position.load()?;
+            (position.lp_range_min, position.lp_range_max)
+        };
+        
+        Self::add_meteora_liquidity_cpi(
+            position,
+            meteora_program,
+            lb_pair,
+            meteora_position,
+            position_token_a_vault,
+            position_token_b_vault,
+            meteora_reserve_x,
+            meteora_reserve_y,
+            token_a_mint,
+            token_b_mint,
+            meteora_bin_array_lower,
+            meteora_bin_array_upper,
+            token_program,
+            system_program,
+            rent,
+            event_authority,
+            range_min,
+            range_max,
+            amount_x,
+            amount_y,
+            current_price,
+        )?;
+        
+        // Update position tracking
+        let mut position = position.load_mut()?;
+        position.token_a_vault_balance = position.token_a_vault_balance
+            .checked_sub(amount_x)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_vault_balance = position.token_b_vault_balance
+            .checked_sub(amount_y)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_a_in_lp = position.token_a_in_lp
+            .checked_add(amount_x)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_in_lp = position.token_b_in_lp
+            .checked_add(amount_y)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.set_meteora_position(Some(meteora_position.key()));
+        
+        msg!("Successfully opened Meteora position");
+        Ok(())
+    }
+    
+    pub fn close_meteora_position_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        meteora_program: &AccountInfo<'info>,
+        lb_pair: &AccountInfo<'info>,
+        meteora_position: &AccountInfo<'info>,
+        position_token_a_vault: &Account<'info, TokenAccount>,
+        position_token_b_vault: &Account<'info, TokenAccount>,
+        meteora_reserve_x: &AccountInfo<'info>,
+        meteora_reserve_y: &AccountInfo<'info>,
+        token_a_mint: &Account<'info, Mint>,
+        token_b_mint: &Account<'info, Mint>,
+        meteora_bin_array_lower: &AccountInfo<'info>,
+        meteora_bin_array_upper: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+        event_authority: &AccountInfo<'info>,
+    ) -> Result<()> {
+        msg!("Closing Meteora DLMM position with CPI...");
+        
+        let (lp_amount_a, lp_amount_b) = {
+            let position = position.load()?;
+            (position.token_a_in_lp, position.token_b_in_lp)
+        };
+        
+        if lp_amount_a == 0 && lp_amount_b == 0 {
+            msg!("No liquidity to remove from Meteora");
+            return Ok(());
+        }
+        
+        Self::remove_meteora_liquidity_cpi(
+            position,
+            meteora_program,
+            lb_pair,
+            meteora_position,
+            position_token_a_vault,
+            position_token_b_vault,
+            meteora_reserve_x,
+            meteora_reserve_y,
+            token_a_mint,
+            token_b_mint,
+            meteora_bin_array_lower,
+            meteora_bin_array_upper,
+            token_program,
+            event_authority,
+        )?;
+        
+        // Update position tracking
+        let mut position = position.load_mut()?;
+        position.token_a_in_lp = 0;
+        position.token_b_in_lp = 0;
+        position.token_a_vault_balance = position.token_a_vault_balance
+            .checked_add(lp_amount_a)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_vault_balance = position.token_b_vault_balance
+            .checked_add(lp_amount_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.set_meteora_position(None);
+        
+        msg!("Successfully closed Meteora position, recovered {} A and {} B", lp_amount_a, lp_amount_b);
+        Ok(())
+    }
+    
+    /// Open liquidity for a single ladder rung using the rung's own range
+    pub fn open_meteora_rung_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        rung_index: usize,
+        meteora_program: &AccountInfo<'info>,
+        lb_pair: &AccountInfo<'info>,
+        meteora_position: &AccountInfo<'info>,
+        position_token_a_vault: &Account<'info, TokenAccount>,
+        position_token_b_vault: &Account<'info, TokenAccount>,
+        meteora_reserve_x: &AccountInfo<'info>,
+        meteora_reserve_y: &AccountInfo<'info>,
+        token_a_mint: &Account<'info, Mint>,
+        token_b_mint: &Account<'info, Mint>,
+        meteora_bin_array_lower: &AccountInfo<'info>,
+        meteora_bin_array_upper: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+        rent: &Sysvar<'info, Rent>,
+        event_authority: &AccountInfo<'info>,
+        amount_x: u64,
+        amount_y: u64,
+        current_price: u64,
+    ) -> Result<()> {
+        let rung = position.load()?.lp_rungs[rung_index];
+        msg!(
+            "Opening Meteora rung {} ({}-{}) with {} X, {} Y",
+            rung_index, rung.range_min, rung.range_max, amount_x, amount_y
+        );
+        
+        Self::add_meteora_liquidity_cpi(
+            position,
+            meteora_program,
+            lb_pair,
+            meteora_position,
+            position_token_a_vault,
+            position_token_b_vault,
+            meteora_reserve_x,
+            meteora_reserve_y,
+            token_a_mint,
+            token_b_mint,
+            meteora_bin_array_lower,
+            meteora_bin_array_upper,
+            token_program,
+            system_program,
+            rent,
+            event_authority,
+            rung.range_min,
+            rung.range_max,
+            amount_x,
+            amount_y,
+            current_price,
+        )?;
+        
+        // Update position tracking
+        let mut position = position.load_mut()?;
+        position.token_a_vault_balance = position.token_a_vault_balance
+            .checked_sub(amount_x)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_vault_balance = position.token_b_vault_balance
+            .checked_sub(amount_y)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_a_in_lp = position.token_a_in_lp
+            .checked_add(amount_x)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_in_lp = position.token_b_in_lp
+            .checked_add(amount_y)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let rung = &mut position.lp_rungs[rung_index];
+        rung.token_a_in_lp = rung.token_a_in_lp
+            .checked_add(amount_x)
+            .ok_or(ErrorCode::MathOverflow)?;
+        rung.token_b_in_lp = rung.token_b_in_lp
+            .checked_add(amount_y)
+            .ok_or(ErrorCode::MathOverflow)?;
+        rung.meteora_position = meteora_position.key();
+        
+        Ok(())
+    }
+    
+    /// Remove all liquidity held by a single ladder rung back to the vaults
+    pub fn close_meteora_rung_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        rung_index: usize,
+        meteora_program: &AccountInfo<'info>,
+        lb_pair: &AccountInfo<'info>,
+        meteora_position: &AccountInfo<'info>,
+        position_token_a_vault: &Account<'info, TokenAccount>,
+        position_token_b_vault: &Account<'info, TokenAccount>,
+        meteora_reserve_x: &AccountInfo<'info>,
+        meteora_reserve_y: &AccountInfo<'info>,
+        token_a_mint: &Account<'info, Mint>,
+        token_b_mint: &Account<'info, Mint>,
+        meteora_bin_array_lower: &AccountInfo<'info>,
+        meteora_bin_array_upper: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+        event_authority: &AccountInfo<'info>,
+    ) -> Result<()> {
+        let rung = position.load()?.lp_rungs[rung_index];
+        
+        if !rung.has_liquidity() {
+            msg!("Rung {} has no liquidity to remove", rung_index);
+            return Ok(());
+        }
+        
+        msg!(
+            "Closing Meteora rung {}, recovering {} A and {} B",
+            rung_index, rung.token_a_in_lp, rung.token_b_in_lp
+        );
+        
+        Self::remove_meteora_liquidity_cpi(
+            position,
+            meteora_program,
+            lb_pair,
+            meteora_position,
+            position_token_a_vault,
+            position_token_b_vault,
+            meteora_reserve_x,
+            meteora_reserve_y,
+            token_a_mint,
+            token_b_mint,
+            meteora_bin_array_lower,
+            meteora_bin_array_upper,
+            token_program,
+            event_authority,
+        )?;
+        
+        // Update position tracking
+        let mut position = position.load_mut()?;
+        position.token_a_in_lp = position.token_a_in_lp
+            .checked_sub(rung.token_a_in_lp)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_in_lp = position.token_b_in_lp
+            .checked_sub(rung.token_b_in_lp)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_a_vault_balance = position.token_a_vault_balance
+            .checked_add(rung.token_a_in_lp)
+            .ok_or(ErrorCode::MathOverflow)?;
+        position.token_b_vault_balance = position.token_b_vault_balance
+            .checked_add(rung.token_b_in_lp)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        let rung = &mut position.lp_rungs[rung_index];
+        rung.token_a_in_lp = 0;
+        rung.token_b_in_lp = 0;
+        rung.meteora_position = Pubkey::default();
+        
+        Ok(())
+    }
+    
+    /// Claim accrued swap fees from a Meteora position into the position vaults
+    pub fn claim_meteora_fee_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        meteora_program: &AccountInfo<'info>,
+        lb_pair: &AccountInfo<'info>,
+        meteora_position: &AccountInfo<'info>,
+        meteora_bin_array_lower: &AccountInfo<'info>,
+        meteora_bin_array_upper: &AccountInfo<'info>,
+        meteora_reserve_x: &AccountInfo<'info>,
+        meteora_reserve_y: &AccountInfo<'info>,
+        position_token_a_vault: &Account<'info, TokenAccount>,
+        position_token_b_vault: &Account<'info, TokenAccount>,
+        token_a_mint: &Account<'info, Mint>,
+        token_b_mint: &Account<'info, Mint>,
+        token_program: &Program<'info, Token>,
+        event_authority: &AccountInfo<'info>,
+    ) -> Result<()> {
+        msg!("Claiming Meteora swap fees with CPI...");
+        
+        // Build instruction data
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&CLAIM_FEE_DISCRIMINATOR);
+        
+        // Create position signer seeds
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
+        
+        // Build account metas for CPI
+        let account_metas = vec![
+            AccountMeta::new(lb_pair.key(), false),
+            AccountMeta::new(meteora_position.key(), false),
+            AccountMeta::new(meteora_bin_array_lower.key(), false),
+            AccountMeta::new(meteora_bin_array_upper.key(), false),
+            AccountMeta::new_readonly(position_account_info.key(), true), // position authority
+            AccountMeta::new(meteora_reserve_x.key(), false),
+            AccountMeta::new(meteora_reserve_y.key(), false),
+            AccountMeta::new(position_token_a_vault.key(), false),
+            AccountMeta::new(position_token_b_vault.key(), false),
+            AccountMeta::new_readonly(token_a_mint.key(), false),
+            AccountMeta::new_readonly(token_b_mint.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(event_authority.key(), false),
+            AccountMeta::new_readonly(meteora_program.key(), false),
+        ];
+        
+        let instruction = Instruction {
+            program_id: meteora_program.key(),
+            accounts: account_metas,
+            data: instruction_data,
+        };
+        
+        // Execute CPI using invoke_signed
+        invoke_signed(
+            &instruction,
+            &[
+                lb_pair.clone(),
+                meteora_position.clone(),
+                meteora_bin_array_lower.clone(),
+                meteora_bin_array_upper.clone(),
+                position_account_info.clone(),
+                meteora_reserve_x.clone(),
+                meteora_reserve_y.clone(),
+                position_token_a_vault.to_account_info(),
+                position_token_b_vault.to_account_info(),
+                token_a_mint.to_account_info(),
+                token_b_mint.to_account_info(),
+                token_program.to_account_info(),
+                event_authority.clone(),
+                meteora_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        
+        Ok(())
+    }
+    
+    /// Claim farming rewards for one of the LB pair's reward slots
+    pub fn claim_meteora_reward_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        meteora_program: &AccountInfo<'info>,
+        lb_pair: &AccountInfo<'info>,
+        meteora_position: &AccountInfo<'info>,
+        meteora_bin_array_lower: &AccountInfo<'info>,
+        meteora_bin_array_upper: &AccountInfo<'info>,
+        meteora_reward_vault: &AccountInfo<'info>,
+        reward_mint: &Account<'info, Mint>,
+        position_reward_vault: &Account<'info, TokenAccount>,
+        token_program: &Program<'info, Token>,
+        event_authority: &AccountInfo<'info>,
+        reward_index: u64,
+    ) -> Result<()> {
+        msg!("Claiming Meteora reward {} with CPI...", reward_index);
+        
+        // Build instruction data
+        let mut instruction_data = Vec::new();
+        instruction_data.extend_from_slice(&CLAIM_REWARD_DISCRIMINATOR);
+        reward_index.serialize(&mut instruction_data)?;
+        
+        // Create position signer seeds
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
+        let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
+        
+        // Build account metas for CPI
+        let account_metas = vec![
+            AccountMeta::new(lb_pair.key(), false),
+            AccountMeta::new(meteora_position.key(), false),
+            AccountMeta::new(meteora_bin_array_lower.key(), false),
+            AccountMeta::new(meteora_bin_array_upper.key(), false),
+            AccountMeta::new_readonly(position_account_info.key(), true), // position authority
+            AccountMeta::new(meteora_reward_vault.key(), false),
+            AccountMeta::new_readonly(reward_mint.key(), false),
+            AccountMeta::new(position_reward_vault.key(), false),
+            AccountMeta::new_readonly(token_program.key(), false),
+            AccountMeta::new_readonly(event_authority.key(), false),
+            AccountMeta::new_readonly(meteora_program.key(), false),
+        ];
+        
+        let instruction = Instruction {
+            program_id: meteora_program.key(),
+            accounts: account_metas,
+            data: instruction_data,
+        };
+        
+        // Execute CPI using invoke_signed
+        invoke_signed(
+            &instruction,
+            &[
+                lb_pair.clone(),
+                meteora_position.clone(),
+                meteora_bin_array_lower.clone(),
+                meteora_bin_array_upper.clone(),
+                position_account_info.clone(),
+                meteora_reward_vault.clone(),
+                reward_mint.to_account_info(),
+                position_reward_vault.to_account_info(),
+                token_program.to_account_info(),
+                event_authority.clone(),
+                meteora_program.clone(),
+            ],
+            signer_seeds,
+        )?;
+        
+        Ok(())
+    }
+    
+    // Raw add_liquidity_by_strategy CPI, no position bookkeeping
+    fn add_meteora_liquidity_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
+        meteora_program: &AccountInfo<'info>,
+        lb_pair: &AccountInfo<'info>,
+        meteora_position: &AccountInfo<'info>,
+        position_token_a_vault: &Account<'info, TokenAccount>,
+        position_token_b_vault: &Account<'info, TokenAccount>,
+        meteora_reserve_x: &AccountInfo<'info>,
+        meteora_reserve_y: &AccountInfo<'info>,
+        token_a_mint: &Account<'info, Mint>,
+        token_b_mint: &Account<'info, Mint>,
+        meteora_bin_array_lower: &AccountInfo<'info>,
+        meteora_bin_array_upper: &AccountInfo<'info>,
+        token_program: &Program<'info, Token>,
+        system_program: &Program<'info, System>,
+        rent: &Sysvar<'info, Rent>,
+        event_authority: &AccountInfo<'info>,
+        range_min: u64,
+        range_max: u64,
+        amount_x: u64,
+        amount_y: u64,
+        current_price: u64,
+    ) -> Result<()> {
+        // Calculate bin range based on the requested LP range
         let (min_bin_id, max_bin_id) = calculate_meteora_bin_range(
             current_price,
-            self.lp_range_min,
-            self.lp_range_max,
+            range_min,
+            range_max,
             25, // 0.25% bin step - typical for most pairs
         )?;
         
@@ -100,14 +650,10 @@ impl Position {
         liquidity_param.serialize(&mut instruction_data)?;
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         // Build account metas for CPI
         let account_metas = vec![
@@ -130,7 +676,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: METEORA_DLMM_PROGRAM_ID,
+            program_id: meteora_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -159,27 +705,12 @@ impl Position {
             signer_seeds,
         )?;
         
-        // Update position tracking
-        self.token_a_vault_balance = self.token_a_vault_balance
-            .checked_sub(amount_x)
-            .ok_or(ErrorCode::MathOverflow)?;
-        self.token_b_vault_balance = self.token_b_vault_balance
-            .checked_sub(amount_y)
-            .ok_or(ErrorCode::MathOverflow)?;
-        self.token_a_in_lp = self.token_a_in_lp
-            .checked_add(amount_x)
-            .ok_or(ErrorCode::MathOverflow)?;
-        self.token_b_in_lp = self.token_b_in_lp
-            .checked_add(amount_y)
-            .ok_or(ErrorCode::MathOverflow)?;
-        
-        msg!("Successfully opened Meteora position");
         Ok(())
     }
     
-    pub fn close_meteora_position_cpi<'info>(
-        &mut self,
-        position_account_info: &AccountInfo<'info>,
+    // Raw remove_liquidity CPI, no position bookkeeping
+    fn remove_meteora_liquidity_cpi<'info>(
+        position: &AccountLoader<'info, Position>,
         meteora_program: &AccountInfo<'info>,
         lb_pair: &AccountInfo<'info>,
         meteora_position: &AccountInfo<'info>,
@@ -194,16 +725,6 @@ impl Position {
         token_program: &Program<'info, Token>,
         event_authority: &AccountInfo<'info>,
     ) -> Result<()> {
-        msg!("Closing Meteora DLMM position with CPI...");
-        
-        let lp_amount_a = self.token_a_in_lp;
-        let lp_amount_b = self.token_b_in_lp;
-        
-        if lp_amount_a == 0 && lp_amount_b == 0 {
-            msg!("No liquidity to remove from Meteora");
-            return Ok(());
-        }
-        
         // Remove 100% of liquidity parameters
         let bin_ids_to_remove = vec![0i32]; // This would be determined by position data
         let liquidity_bps_to_remove = vec![10000u16]; // 100% (basis points)
@@ -217,14 +738,10 @@ impl Position {
         should_claim_and_close.serialize(&mut instruction_data)?;
         
         // Create position signer seeds
-        let position_id_bytes = self.position_id.to_le_bytes();
-        let position_seeds = &[
-            b"position",
-            self.owner.as_ref(),
-            position_id_bytes.as_ref(),
-            &[self.bump],
-        ];
+        let signer = position.load()?.signer();
+        let position_seeds = signer.seeds();
         let signer_seeds = &[&position_seeds[..]];
+        let position_account_info = position.to_account_info();
         
         // Build account metas for CPI
         let account_metas = vec![
@@ -245,7 +762,7 @@ impl Position {
         ];
         
         let instruction = Instruction {
-            program_id: METEORA_DLMM_PROGRAM_ID,
+            program_id: meteora_program.key(),
             accounts: account_metas,
             data: instruction_data,
         };
@@ -272,17 +789,6 @@ impl Position {
             signer_seeds,
         )?;
         
-        // Update position tracking
-        self.token_a_in_lp = 0;
-        self.token_b_in_lp = 0;
-        self.token_a_vault_balance = self.token_a_vault_balance
-            .checked_add(lp_amount_a)
-            .ok_or(ErrorCode::MathOverflow)?;
-        self.token_b_vault_balance = self.token_b_vault_balance
-            .checked_add(lp_amount_b)
-            .ok_or(ErrorCode::MathOverflow)?;
-        
-        msg!("Successfully closed Meteora position, recovered {} A and {} B", lp_amount_a, lp_amount_b);
         Ok(())
     }
 }
@@ -311,3 +817,108 @@ pub fn calculate_meteora_bin_range(
     
     Ok((min_bin_id, max_bin_id))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocols::test_utils::{account_data, error_code, with_account};
+
+    const LB_PAIR_LEN: usize = LB_PAIR_PROTOCOL_FEE_Y_OFFSET + 8;
+    const POSITION_LEN: usize = POSITION_V2_OWNER_OFFSET + 32;
+    const BIN_ARRAY_LEN: usize = BIN_ARRAY_LB_PAIR_OFFSET + 32;
+
+    #[test]
+    fn meteora_position_checks_pair_and_owner() {
+        let (lb_pair, owner) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = account_data(&POSITION_V2_ACCOUNT_DISCRIMINATOR, POSITION_LEN, &[
+            (POSITION_V2_LB_PAIR_OFFSET, lb_pair.as_ref()),
+            (POSITION_V2_OWNER_OFFSET, owner.as_ref()),
+        ]);
+        let program = METEORA_DLMM_PROGRAM_ID;
+        
+        with_account(&program, &mut data, |info| {
+            assert!(validate_meteora_position(info, &lb_pair, &owner, &program).is_ok());
+            assert_eq!(
+                error_code(validate_meteora_position(info, &Pubkey::new_unique(), &owner, &program)),
+                u32::from(ErrorCode::VenueMismatch)
+            );
+            assert_eq!(
+                error_code(validate_meteora_position(info, &lb_pair, &Pubkey::new_unique(), &program)),
+                u32::from(ErrorCode::VenueAccountNotOwned)
+            );
+            // Configured program differs from the account owner
+            assert_eq!(
+                error_code(validate_meteora_position(info, &lb_pair, &owner, &Pubkey::new_unique())),
+                u32::from(ErrorCode::InvalidAccountData)
+            );
+        });
+    }
+
+    #[test]
+    fn meteora_accounts_need_their_discriminator_and_length() {
+        let lb_pair = Pubkey::new_unique();
+        let program = METEORA_DLMM_PROGRAM_ID;
+        
+        // A bin array passed where a position is expected
+        let mut data = account_data(&BIN_ARRAY_ACCOUNT_DISCRIMINATOR, POSITION_LEN, &[]);
+        with_account(&program, &mut data, |info| {
+            assert_eq!(error_code(read_meteora_position(info, &program)), u32::from(ErrorCode::InvalidAccountData));
+        });
+        
+        let mut data = account_data(&BIN_ARRAY_ACCOUNT_DISCRIMINATOR, BIN_ARRAY_LEN - 1, &[]);
+        with_account(&program, &mut data, |info| {
+            assert_eq!(
+                error_code(validate_bin_array(info, &lb_pair, &program)),
+                u32::from(ErrorCode::InvalidAccountData)
+            );
+        });
+        
+        let mut data = vec![0; 4];
+        with_account(&program, &mut data, |info| {
+            assert_eq!(error_code(read_lb_pair(info, &program)), u32::from(ErrorCode::InvalidAccountData));
+        });
+    }
+
+    #[test]
+    fn bin_array_must_belong_to_the_pair() {
+        let lb_pair = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let mut data = account_data(&BIN_ARRAY_ACCOUNT_DISCRIMINATOR, BIN_ARRAY_LEN, &[
+            (BIN_ARRAY_LB_PAIR_OFFSET, lb_pair.as_ref()),
+        ]);
+        
+        with_account(&program, &mut data, |info| {
+            assert!(validate_bin_array(info, &lb_pair, &program).is_ok());
+            assert_eq!(
+                error_code(validate_bin_array(info, &Pubkey::new_unique(), &program)),
+                u32::from(ErrorCode::VenueMismatch)
+            );
+        });
+    }
+
+    #[test]
+    fn lb_pair_reads_mints_reserves_and_fees() {
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let mut data = account_data(&LB_PAIR_ACCOUNT_DISCRIMINATOR, LB_PAIR_LEN, &[
+            (LB_PAIR_PROTOCOL_SHARE_OFFSET, &500u16.to_le_bytes()),
+            (LB_PAIR_TOKEN_X_MINT_OFFSET, keys[0].as_ref()),
+            (LB_PAIR_TOKEN_Y_MINT_OFFSET, keys[1].as_ref()),
+            (LB_PAIR_RESERVE_X_OFFSET, keys[2].as_ref()),
+            (LB_PAIR_RESERVE_Y_OFFSET, keys[3].as_ref()),
+            (LB_PAIR_PROTOCOL_FEE_X_OFFSET, &7u64.to_le_bytes()),
+            (LB_PAIR_PROTOCOL_FEE_Y_OFFSET, &9u64.to_le_bytes()),
+        ]);
+        let program = METEORA_DLMM_PROGRAM_ID;
+        
+        with_account(&program, &mut data, |info| {
+            let pair = read_lb_pair(info, &program).unwrap();
+            assert_eq!(
+                [pair.token_x_mint, pair.token_y_mint, pair.reserve_x, pair.reserve_y],
+                [keys[0], keys[1], keys[2], keys[3]]
+            );
+            
+            let fees = read_lb_pair_fees(info, &program).unwrap();
+            assert_eq!((fees.protocol_fee_x, fees.protocol_fee_y, fees.protocol_share_bps), (7, 9, 500));
+        });
+    }
+}
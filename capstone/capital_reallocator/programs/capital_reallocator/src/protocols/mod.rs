@@ -1,6 +1,13 @@
 pub mod meteora;
 pub mod kamino;
+pub mod jupiter;
+pub mod lookup_table;
+
+#[cfg(test)]
+mod test_utils;
 
 pub use meteora::*;
 pub use kamino::*;
+pub use jupiter::*;
+pub use lookup_table::*;
 
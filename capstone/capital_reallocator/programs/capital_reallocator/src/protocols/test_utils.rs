@@ -0,0 +1,31 @@
+// protocols/test_utils.rs
+// Fabricated venue accounts for the protocol parsers' unit tests
+use anchor_lang::prelude::*;
+
+/// Account data starting with `discriminator`, zero-filled to `len`, with
+/// each `(offset, bytes)` field written in
+pub(crate) fn account_data(discriminator: &[u8; 8], len: usize, fields: &[(usize, &[u8])]) -> Vec<u8> {
+    let mut data = vec![0; len];
+    data[..8].copy_from_slice(discriminator);
+    for (offset, bytes) in fields {
+        data[*offset..offset + bytes.len()].copy_from_slice(bytes);
+    }
+    data
+}
+
+/// Run `check` against an account owned by `owner` holding `data`
+pub(crate) fn with_account<T>(owner: &Pubkey, data: &mut [u8], check: impl FnOnce(&AccountInfo) -> T) -> T {
+    let key = Pubkey::new_unique();
+    let mut lamports = 0;
+    let info = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+    check(&info)
+}
+
+/// Error code number of a failed call
+pub(crate) fn error_code<T>(result: Result<T>) -> u32 {
+    match result {
+        Err(Error::AnchorError(error)) => error.error_code_number,
+        Err(error) => panic!("unexpected error {:?}", error),
+        Ok(_) => panic!("expected an error"),
+    }
+}
@@ -0,0 +1,535 @@
+// state/legacy.rs
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use super::{ExternalPrograms, FeeTier, FeeVaultCounter, InsuranceVaultCounter, LpRung, OutOfRangePolicy, Position, ProtocolAuthority, UserMainAccount, POSITION_DISCRIMINATOR};
+
+// Layouts shipped before account versioning (v0). They share the Anchor
+// discriminator of the current account types and are told apart by size.
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct ProtocolAuthorityV0 {
+    pub program_id: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub protocol_fee_bps: u16,
+    pub total_positions: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct UserMainAccountV0 {
+    pub owner: Pubkey,
+    pub position_count: u64,
+    pub total_positions_created: u64,
+    pub bump: u8,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Debug)]
+pub struct PositionV0 {
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_vault_balance: u64,
+    pub token_b_vault_balance: u64,
+    pub token_a_in_lp: u64,
+    pub token_b_in_lp: u64,
+    pub token_a_in_lending: u64,
+    pub token_b_in_lending: u64,
+    pub lp_range_min: u64,
+    pub lp_range_max: u64,
+    pub pause_flag: bool,
+    pub created_at: i64,
+    pub last_rebalance_price: u64,
+    pub last_rebalance_slot: u64,
+    pub total_rebalances: u64,
+    pub meteora_position: Option<Pubkey>,
+    pub kamino_obligation: Option<Pubkey>,
+    pub bump: u8,
+}
+
+// sha256("account:Position")[..8], shared by the Borsh position layouts
+pub const BORSH_POSITION_DISCRIMINATOR: &[u8] = &[170, 188, 143, 228, 122, 64, 247, 208];
+
+const V8_POSITION_RESERVED_BYTES: usize = 105;
+
+// Borsh position layout from v1 to v8, before positions went zero-copy (v9)
+#[account(discriminator = BORSH_POSITION_DISCRIMINATOR)]
+#[derive(InitSpace)]
+pub struct PositionV8 {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub position_id: u64,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+
+    // Token balances in different locations
+    pub token_a_vault_balance: u64,
+    pub token_b_vault_balance: u64,
+    pub token_a_in_lp: u64,
+    pub token_b_in_lp: u64,
+    pub token_a_in_lending: u64,
+    pub token_b_in_lending: u64,
+
+    // LP range configuration
+    pub lp_range_min: u64,
+    pub lp_range_max: u64,
+
+    // Laddered LP rungs (lp_rung_count == 0 means single-range position)
+    pub lp_rung_count: u8,
+    pub lp_rungs: [LpRungV8; MAX_LP_RUNGS],
+
+    // Position state
+    pub pause_flag: bool,
+    pub created_at: i64,
+    pub flagged: bool, // set when an audit discrepancy could not be reconciled
+
+    // Delegated operator (pause/resume, range changes, venue withdrawals)
+    pub operator: Option<Pubkey>,
+
+    // Rebalancing tracking
+    pub last_rebalance_price: u64,
+    pub last_rebalance_slot: u64,
+    pub total_rebalances: u64,
+
+    // External protocol position tracking
+    pub meteora_position: Option<Pubkey>,
+    pub kamino_obligation: Option<Pubkey>,
+
+    pub bump: u8,
+
+    // Cost basis: deposits net of fees, scaled down by withdrawals (v2)
+    pub net_deposit_a: u64,
+    pub net_deposit_b: u64,
+
+    // Delta-neutral hedge leg: token A posted as Kamino collateral to borrow
+    // token B against the LP's token B exposure (v3)
+    pub hedge_enabled: bool,
+    pub hedge_collateral_a: u64,
+    pub hedge_borrowed_b: u64,
+
+    // Which token to hold while the price is outside the range, and whether
+    // the funds currently sit converted to it (v4)
+    pub out_of_range_policy: OutOfRangePolicy,
+    pub out_of_range_converted: bool,
+
+    // Withdrawn share still sitting in LP or lending, owed to the owner
+    // through the position's withdrawal request (v5)
+    pub queued_withdraw_a: u64,
+    pub queued_withdraw_b: u64,
+
+    // Rebalance gate overrides (0 = protocol default) and the strategy
+    // preset the position was created from (v6)
+    pub rebalance_threshold_bps: u16,
+    pub min_rebalance_slots: u64,
+    pub preset: Option<Pubkey>,
+
+    // Meteora pair protocol fee accumulators at the start of the current
+    // LP fee APR sample (v7)
+    pub lp_fee_snapshot_x: u64,
+    pub lp_fee_snapshot_y: u64,
+    pub lp_fee_snapshot_at: i64,
+
+    // Open multi-step rebalance; the position is locked until it ends (v8)
+    pub rebalance_session: Option<Pubkey>,
+
+    pub reserved: [u8; V8_POSITION_RESERVED_BYTES],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default)]
+pub struct LpRungV8 {
+    pub range_min: u64,
+    pub range_max: u64,
+    pub weight_bps: u16,
+    pub meteora_position: Option<Pubkey>,
+    pub token_a_in_lp: u64,
+    pub token_b_in_lp: u64,
+}
+
+/// Versioned view over raw account data
+pub enum Versioned<Legacy, Current> {
+    V0(Legacy),
+    Current(Current),
+}
+
+fn decode<Legacy, Current>(data: &[u8], legacy_space: usize) -> Result<Versioned<Legacy, Current>>
+where
+    Legacy: AnchorDeserialize,
+    Current: AccountDeserialize + Discriminator + Space,
+{
+    require!(
+        data.len() >= 8 && data[..8] == *Current::DISCRIMINATOR,
+        ErrorCode::InvalidAccountData
+    );
+    
+    if data.len() == 8 + Current::INIT_SPACE {
+        let mut slice = data;
+        return Ok(Versioned::Current(Current::try_deserialize(&mut slice)?));
+    }
+    
+    if data.len() == 8 + legacy_space {
+        let legacy = Legacy::deserialize(&mut &data[8..])
+            .map_err(|_| ErrorCode::InvalidAccountData)?;
+        return Ok(Versioned::V0(legacy));
+    }
+    
+    // A versioned layout from before the account grew. Fields added since
+    // sit where the zeroed reserved bytes and the new tail decode as empty.
+    require!(data.len() < 8 + Current::INIT_SPACE, ErrorCode::UnsupportedAccountVersion);
+    let mut padded = data.to_vec();
+    padded.resize(8 + Current::INIT_SPACE, 0);
+    Ok(Versioned::Current(Current::try_deserialize(&mut padded.as_slice())?))
+}
+
+impl ProtocolAuthority {
+    pub fn decode_versioned(data: &[u8]) -> Result<Versioned<ProtocolAuthorityV0, Self>> {
+        decode(data, ProtocolAuthorityV0::INIT_SPACE)
+    }
+}
+
+impl UserMainAccount {
+    pub fn decode_versioned(data: &[u8]) -> Result<Versioned<UserMainAccountV0, Self>> {
+        decode(data, UserMainAccountV0::INIT_SPACE)
+    }
+}
+
+/// Position data by encoding: the Borsh layouts (v0 to v8) or zero-copy (v9 on)
+pub enum PositionLayout {
+    Borsh(Box<Versioned<PositionV0, PositionV8>>),
+    ZeroCopy(Box<Position>),
+}
+
+impl Position {
+    pub fn decode_versioned(data: &[u8]) -> Result<PositionLayout> {
+        if data.len() >= 8 && data[..8] == *POSITION_DISCRIMINATOR {
+            require!(data.len() >= 8 + Position::INIT_SPACE, ErrorCode::InvalidAccountData);
+            let position: Position = bytemuck::pod_read_unaligned(&data[8..8 + Position::INIT_SPACE]);
+            return Ok(PositionLayout::ZeroCopy(Box::new(position)));
+        }
+        decode(data, PositionV0::INIT_SPACE).map(|borsh| PositionLayout::Borsh(Box::new(borsh)))
+    }
+}
+
+impl ProtocolAuthorityV0 {
+    // v0 had no admin, so the migration supplies one
+    pub fn upgrade(self, admin: Pubkey) -> ProtocolAuthority {
+        ProtocolAuthority {
+            version: PROTOCOL_VERSION,
+            program_id: self.program_id,
+            admin,
+            fee_recipient: self.fee_recipient,
+            protocol_fee_bps: self.protocol_fee_bps,
+            total_positions: self.total_positions,
+            bump: self.bump,
+            fee_tier_count: 0,
+            fee_tiers: [FeeTier::default(); MAX_FEE_TIERS],
+            fee_vault_count: 0,
+            fee_vaults: [FeeVaultCounter::default(); MAX_FEE_VAULTS],
+            insurance_share_bps: 0,
+            insurance_vault_count: 0,
+            insurance_vaults: [InsuranceVaultCounter::default(); MAX_FEE_VAULTS],
+            external_programs: ExternalPrograms::MAINNET,
+            pending_external_programs: ExternalPrograms::default(),
+            pending_external_programs_eta: 0,
+            reserved: [0; PROTOCOL_RESERVED_BYTES],
+        }
+    }
+}
+
+impl From<UserMainAccountV0> for UserMainAccount {
+    fn from(v0: UserMainAccountV0) -> Self {
+        UserMainAccount {
+            version: USER_ACCOUNT_VERSION,
+            owner: v0.owner,
+            position_count: v0.position_count,
+            total_positions_created: v0.total_positions_created,
+            bump: v0.bump,
+            referrer: None,
+            first_deposit_at: 0,
+            cumulative_deposit_value: 0,
+            open_position_count: 0,
+            open_position_ids: [0; MAX_OPEN_POSITIONS],
+            reserved: [0; USER_RESERVED_BYTES],
+        }
+    }
+}
+
+impl From<PositionV0> for PositionV8 {
+    fn from(v0: PositionV0) -> Self {
+        PositionV8 {
+            version: 0, // net deposits are seeded on the way to the current layout
+            owner: v0.owner,
+            position_id: v0.position_id,
+            token_a_mint: v0.token_a_mint,
+            token_b_mint: v0.token_b_mint,
+            token_a_vault_balance: v0.token_a_vault_balance,
+            token_b_vault_balance: v0.token_b_vault_balance,
+            token_a_in_lp: v0.token_a_in_lp,
+            token_b_in_lp: v0.token_b_in_lp,
+            token_a_in_lending: v0.token_a_in_lending,
+            token_b_in_lending: v0.token_b_in_lending,
+            lp_range_min: v0.lp_range_min,
+            lp_range_max: v0.lp_range_max,
+            lp_rung_count: 0,
+            lp_rungs: [LpRungV8::default(); MAX_LP_RUNGS],
+            pause_flag: v0.pause_flag,
+            created_at: v0.created_at,
+            flagged: false,
+            operator: None,
+            last_rebalance_price: v0.last_rebalance_price,
+            last_rebalance_slot: v0.last_rebalance_slot,
+            total_rebalances: v0.total_rebalances,
+            meteora_position: v0.meteora_position,
+            kamino_obligation: v0.kamino_obligation,
+            bump: v0.bump,
+            net_deposit_a: 0,
+            net_deposit_b: 0,
+            hedge_enabled: false,
+            hedge_collateral_a: 0,
+            hedge_borrowed_b: 0,
+            out_of_range_policy: OutOfRangePolicy::Hold,
+            out_of_range_converted: false,
+            queued_withdraw_a: 0,
+            queued_withdraw_b: 0,
+            rebalance_threshold_bps: 0,
+            min_rebalance_slots: 0,
+            preset: None,
+            lp_fee_snapshot_x: 0,
+            lp_fee_snapshot_y: 0,
+            lp_fee_snapshot_at: 0,
+            rebalance_session: None,
+            reserved: [0; V8_POSITION_RESERVED_BYTES],
+        }
+    }
+}
+
+impl From<LpRungV8> for LpRung {
+    fn from(rung: LpRungV8) -> Self {
+        LpRung {
+            range_min: rung.range_min,
+            range_max: rung.range_max,
+            token_a_in_lp: rung.token_a_in_lp,
+            token_b_in_lp: rung.token_b_in_lp,
+            meteora_position: rung.meteora_position.unwrap_or_default(),
+            weight_bps: rung.weight_bps,
+            padding: [0; 6],
+        }
+    }
+}
+
+// Fields added after the account's version decode from zeroed bytes as off
+// (hedge disabled, out-of-range policy Hold, nothing queued, default
+// rebalance thresholds, no preset, no LP fee sample, no rebalance session)
+impl From<PositionV8> for Position {
+    fn from(v8: PositionV8) -> Self {
+        let mut position = Position {
+            version: POSITION_VERSION,
+            bump: v8.bump,
+            pause_flag: v8.pause_flag as u8,
+            flagged: v8.flagged as u8,
+            hedge_enabled: v8.hedge_enabled as u8,
+            out_of_range_policy: v8.out_of_range_policy as u8,
+            out_of_range_converted: v8.out_of_range_converted as u8,
+            lp_rung_count: v8.lp_rung_count,
+            owner: v8.owner,
+            position_id: v8.position_id,
+            token_a_mint: v8.token_a_mint,
+            token_b_mint: v8.token_b_mint,
+            token_a_vault_balance: v8.token_a_vault_balance,
+            token_b_vault_balance: v8.token_b_vault_balance,
+            token_a_in_lp: v8.token_a_in_lp,
+            token_b_in_lp: v8.token_b_in_lp,
+            token_a_in_lending: v8.token_a_in_lending,
+            token_b_in_lending: v8.token_b_in_lending,
+            lp_range_min: v8.lp_range_min,
+            lp_range_max: v8.lp_range_max,
+            lp_rungs: v8.lp_rungs.map(LpRung::from),
+            created_at: v8.created_at,
+            operator: v8.operator.unwrap_or_default(),
+            last_rebalance_price: v8.last_rebalance_price,
+            last_rebalance_slot: v8.last_rebalance_slot,
+            total_rebalances: v8.total_rebalances,
+            meteora_position: v8.meteora_position.unwrap_or_default(),
+            kamino_obligation: v8.kamino_obligation.unwrap_or_default(),
+            net_deposit_a: v8.net_deposit_a,
+            net_deposit_b: v8.net_deposit_b,
+            hedge_collateral_a: v8.hedge_collateral_a,
+            hedge_borrowed_b: v8.hedge_borrowed_b,
+            queued_withdraw_a: v8.queued_withdraw_a,
+            queued_withdraw_b: v8.queued_withdraw_b,
+            rebalance_threshold_bps: v8.rebalance_threshold_bps,
+            padding: [0; 6],
+            min_rebalance_slots: v8.min_rebalance_slots,
+            preset: v8.preset.unwrap_or_default(),
+            lp_fee_snapshot_x: v8.lp_fee_snapshot_x,
+            lp_fee_snapshot_y: v8.lp_fee_snapshot_y,
+            lp_fee_snapshot_at: v8.lp_fee_snapshot_at,
+            rebalance_session: v8.rebalance_session.unwrap_or_default(),
+            reserved: [0; POSITION_RESERVED_BYTES],
+        };
+        if v8.version < 2 {
+            position.seed_net_deposits();
+        }
+        position
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Bytes the protocol account grew by at each version
+    const PROTOCOL_V3_GROWTH: usize = 1 + FeeVaultCounter::INIT_SPACE * MAX_FEE_VAULTS;
+    const PROTOCOL_V4_GROWTH: usize = 2 + 1 + InsuranceVaultCounter::INIT_SPACE * MAX_FEE_VAULTS;
+    const PROTOCOL_V5_GROWTH: usize = 2 * ExternalPrograms::INIT_SPACE + 8;
+    const USER_V4_GROWTH: usize = 1 + 8 * MAX_OPEN_POSITIONS;
+
+    // Grown fields sit right before `reserved`, so with them zeroed the
+    // current encoding cut to the old size is byte for byte the old layout
+    fn encode_at_size<T: AccountSerialize>(account: &T, size: usize) -> Vec<u8> {
+        let mut data = Vec::new();
+        account.try_serialize(&mut data).unwrap();
+        assert!(data[size..].iter().all(|byte| *byte == 0));
+        data.truncate(size);
+        data
+    }
+
+    fn protocol_at(version: u8) -> ProtocolAuthority {
+        let v0 = ProtocolAuthorityV0 {
+            program_id: crate::ID,
+            fee_recipient: Pubkey::new_unique(),
+            protocol_fee_bps: 25,
+            total_positions: 3,
+            bump: 254,
+        };
+        let mut protocol = v0.upgrade(Pubkey::new_unique());
+        protocol.version = version;
+        protocol.external_programs = ExternalPrograms::default();
+        protocol.fee_tier_count = 1;
+        protocol.fee_tiers[0] = FeeTier { min_volume: 1_000, deposit_fee_bps: 10, withdraw_fee_bps: 5 };
+        protocol
+    }
+
+    fn decode_protocol(data: &[u8]) -> ProtocolAuthority {
+        match ProtocolAuthority::decode_versioned(data).unwrap() {
+            Versioned::Current(protocol) => protocol,
+            Versioned::V0(_) => panic!("decoded as v0"),
+        }
+    }
+
+    #[test]
+    fn older_protocol_sizes_decode_with_empty_grown_fields() {
+        let current = 8 + ProtocolAuthority::INIT_SPACE;
+        let sizes = [
+            (2, current - PROTOCOL_V5_GROWTH - PROTOCOL_V4_GROWTH - PROTOCOL_V3_GROWTH),
+            (3, current - PROTOCOL_V5_GROWTH - PROTOCOL_V4_GROWTH),
+            (4, current - PROTOCOL_V5_GROWTH),
+        ];
+        
+        for (version, size) in sizes {
+            let original = protocol_at(version);
+            let data = encode_at_size(&original, size);
+            // Anchor cannot load it until migrate_protocol reallocates it
+            assert!(ProtocolAuthority::try_deserialize(&mut data.as_slice()).is_err());
+            
+            let mut protocol = decode_protocol(&data);
+            assert_eq!(protocol.version, version);
+            assert_eq!(protocol.admin, original.admin);
+            assert_eq!(protocol.total_positions, 3);
+            assert_eq!(protocol.active_fee_tiers().len(), 1);
+            assert_eq!(protocol.fee_tiers[0].deposit_fee_bps, 10);
+            assert_eq!(protocol.fee_vault_count, 0);
+            assert_eq!(protocol.insurance_share_bps, 0);
+            assert_eq!(protocol.insurance_vault_count, 0);
+            assert_eq!(protocol.pending_external_programs_eta, 0);
+            assert!(protocol.reserved.iter().all(|byte| *byte == 0));
+            
+            protocol.upgrade_external_programs();
+            assert_eq!(protocol.external_programs, ExternalPrograms::MAINNET);
+        }
+    }
+
+    #[test]
+    fn current_protocol_keeps_configured_programs() {
+        let mut original = protocol_at(PROTOCOL_VERSION);
+        original.external_programs.jupiter = Pubkey::new_unique();
+        let data = encode_at_size(&original, 8 + ProtocolAuthority::INIT_SPACE);
+        
+        let mut protocol = decode_protocol(&data);
+        protocol.upgrade_external_programs();
+        assert_eq!(protocol.external_programs.jupiter, original.external_programs.jupiter);
+    }
+
+    #[test]
+    fn v0_protocol_is_told_apart_by_size() {
+        let v0 = ProtocolAuthorityV0 {
+            program_id: crate::ID,
+            fee_recipient: Pubkey::new_unique(),
+            protocol_fee_bps: 25,
+            total_positions: 3,
+            bump: 254,
+        };
+        let mut data = ProtocolAuthority::DISCRIMINATOR.to_vec();
+        v0.serialize(&mut data).unwrap();
+        
+        match ProtocolAuthority::decode_versioned(&data).unwrap() {
+            Versioned::V0(decoded) => assert_eq!(decoded.fee_recipient, v0.fee_recipient),
+            Versioned::Current(_) => panic!("decoded as current"),
+        }
+    }
+
+    #[test]
+    fn v3_user_account_decodes_with_empty_position_index() {
+        let mut original = UserMainAccount::from(UserMainAccountV0 {
+            owner: Pubkey::new_unique(),
+            position_count: 2,
+            total_positions_created: 5,
+            bump: 253,
+        });
+        original.version = 3;
+        original.referrer = Some(Pubkey::new_unique());
+        original.first_deposit_at = 1_700_000_000;
+        original.cumulative_deposit_value = 42;
+        let data = encode_at_size(&original, 8 + UserMainAccount::INIT_SPACE - USER_V4_GROWTH);
+        // Anchor cannot load it until migrate_user_account reallocates it
+        assert!(UserMainAccount::try_deserialize(&mut data.as_slice()).is_err());
+        
+        let mut user = match UserMainAccount::decode_versioned(&data).unwrap() {
+            Versioned::Current(user) => user,
+            Versioned::V0(_) => panic!("decoded as v0"),
+        };
+        assert_eq!(user.version, 3);
+        assert_eq!(user.owner, original.owner);
+        assert_eq!(user.referrer, original.referrer);
+        assert_eq!(user.cumulative_deposit_value, 42);
+        assert!(user.open_positions().is_empty());
+        
+        user.upgrade_from(3, 1_800_000_000);
+        assert_eq!(user.version, USER_ACCOUNT_VERSION);
+        assert_eq!(user.first_deposit_at, 1_700_000_000);
+    }
+
+    #[test]
+    fn v1_user_account_with_positions_is_past_first_deposit() {
+        let mut user = UserMainAccount::from(UserMainAccountV0 {
+            owner: Pubkey::new_unique(),
+            position_count: 1,
+            total_positions_created: 1,
+            bump: 253,
+        });
+        user.upgrade_from(1, 1_800_000_000);
+        assert_eq!(user.first_deposit_at, 1_800_000_000);
+    }
+
+    #[test]
+    fn unknown_sizes_are_rejected() {
+        let mut data = ProtocolAuthority::DISCRIMINATOR.to_vec();
+        data.resize(8 + ProtocolAuthority::INIT_SPACE + 1, 0);
+        assert!(ProtocolAuthority::decode_versioned(&data).is_err());
+        
+        let mut data = UserMainAccount::DISCRIMINATOR.to_vec();
+        data.resize(8 + UserMainAccount::INIT_SPACE, 0);
+        assert!(ProtocolAuthority::decode_versioned(&data).is_err());
+    }
+}
@@ -1,28 +1,493 @@
 // state/mod.rs
 use anchor_lang::prelude::*;
+use crate::constants::{
+    POSITION_SEED, FEE_VAULT_SEED, INSURANCE_VAULT_SEED, MAX_LP_RUNGS, MAX_FEE_TIERS, MAX_FEE_VAULTS, MAX_OPEN_POSITIONS, PRICE_DECIMALS, VOLUME_DECIMALS, USER_ACCOUNT_VERSION, PROTOCOL_RESERVED_BYTES, USER_RESERVED_BYTES,
+    POSITION_RESERVED_BYTES, PAIR_RESERVED_BYTES, DEPOSIT_SCHEDULE_RESERVED_BYTES, REFERRER_RESERVED_BYTES, REFERRAL_TIER_RESERVED_BYTES,
+    WITHDRAWAL_REQUEST_RESERVED_BYTES, STRATEGY_PRESET_RESERVED_BYTES, REBALANCE_SESSION_RESERVED_BYTES,
+};
+use crate::errors::ErrorCode;
+use crate::protocols::{METEORA_DLMM_PROGRAM_ID, KAMINO_LENDING_PROGRAM_ID, KAMINO_FARMS_PROGRAM_ID, JUPITER_PROGRAM_ID};
 
+pub mod legacy;
+
+
+// Account layouts are versioned and every change bumps the version. Fields
+// that fit are carved out of the trailing `reserved` bytes (zeroed bytes
+// decode as defaults). Larger ones grow the account instead (user v4,
+// protocol v3 to v5): new fields go before `reserved`, so an older account's
+// zeroed reserved bytes plus zero padding decode them as empty, but Anchor
+// cannot load the shorter account until the migrate_* instruction has
+// reallocated and rewritten it. Accounts created before versioning are
+// upgraded the same way.
 #[account]
 #[derive(InitSpace)]
 pub struct ProtocolAuthority {
+    pub version: u8,
     pub program_id: Pubkey,
+    pub admin: Pubkey,
     pub fee_recipient: Pubkey,
     pub protocol_fee_bps: u16,
     pub total_positions: u64,
     pub bump: u8,
+
+    // Volume fee tiers (v2), ascending by min_volume. Users below the
+    // first tier, or all users when empty, pay protocol_fee_bps.
+    pub fee_tier_count: u8,
+    pub fee_tiers: [FeeTier; MAX_FEE_TIERS],
+
+    // Protocol fee vault PDAs, one per mint, with their counters (v3).
+    // Deposit and withdraw fees accrue there until the admin sweeps them.
+    pub fee_vault_count: u8,
+    pub fee_vaults: [FeeVaultCounter; MAX_FEE_VAULTS],
+
+    // Share of deposit and withdraw fees routed to the mint's insurance vault,
+    // and the insurance vaults with their counters (v4). The admin pays
+    // positions back out of them with compensate_position.
+    pub insurance_share_bps: u16,
+    pub insurance_vault_count: u8,
+    pub insurance_vaults: [InsuranceVaultCounter; MAX_FEE_VAULTS],
+
+    // Venue programs every CPI and venue account read is checked against (v5),
+    // and a proposed replacement the admin can apply once
+    // pending_external_programs_eta has passed (0 when none is pending)
+    pub external_programs: ExternalPrograms,
+    pub pending_external_programs: ExternalPrograms,
+    pub pending_external_programs_eta: i64,
+
+    pub reserved: [u8; PROTOCOL_RESERVED_BYTES],
+}
+
+// Program IDs of the venues the protocol integrates with. Mainnet and devnet
+// use the deployed programs; localnet can point them at mocks or forks.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExternalPrograms {
+    pub meteora_dlmm: Pubkey,
+    pub kamino_lending: Pubkey,
+    pub kamino_farms: Pubkey,
+    pub jupiter: Pubkey,
+}
+
+impl ExternalPrograms {
+    pub const MAINNET: Self = Self {
+        meteora_dlmm: METEORA_DLMM_PROGRAM_ID,
+        kamino_lending: KAMINO_LENDING_PROGRAM_ID,
+        kamino_farms: KAMINO_FARMS_PROGRAM_ID,
+        jupiter: JUPITER_PROGRAM_ID,
+    };
+
+    pub fn is_complete(&self) -> bool {
+        [self.meteora_dlmm, self.kamino_lending, self.kamino_farms, self.jupiter]
+            .iter()
+            .all(|program| *program != Pubkey::default())
+    }
+}
+
+// Fee discount for users whose cumulative deposit volume reaches min_volume
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default)]
+pub struct FeeTier {
+    pub min_volume: u64,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+}
+
+// Fees accrued into one mint's fee vault and how much has been swept out
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default)]
+pub struct FeeVaultCounter {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub accrued: u64,
+    pub swept: u64,
+}
+
+// Fees routed into one mint's insurance vault and compensation paid from it
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default)]
+pub struct InsuranceVaultCounter {
+    pub mint: Pubkey,
+    pub bump: u8,
+    pub deposited: u64,
+    pub compensated: u64,
+}
+
+// Fee rates applied to one user; tier is None for the flat protocol fee
+#[derive(Clone, Copy, Debug)]
+pub struct AppliedFee {
+    pub tier: Option<u8>,
+    pub deposit_fee_bps: u16,
+    pub withdraw_fee_bps: u16,
+}
+
+impl ProtocolAuthority {
+    pub fn fee_at(amount: u64, fee_bps: u16) -> Result<u64> {
+        Ok((amount as u128)
+            .checked_mul(fee_bps as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10_000)
+            .ok_or(ErrorCode::MathOverflow)? as u64)
+    }
+
+    pub fn active_fee_tiers(&self) -> &[FeeTier] {
+        &self.fee_tiers[..self.fee_tier_count as usize]
+    }
+
+    pub fn fee_vault(&self, mint: &Pubkey) -> Result<&FeeVaultCounter> {
+        self.fee_vaults[..self.fee_vault_count as usize]
+            .iter()
+            .find(|vault| vault.mint == *mint)
+            .ok_or(ErrorCode::FeeVaultNotFound.into())
+    }
+
+    pub fn fee_vault_mut(&mut self, mint: &Pubkey) -> Result<&mut FeeVaultCounter> {
+        self.fee_vaults[..self.fee_vault_count as usize]
+            .iter_mut()
+            .find(|vault| vault.mint == *mint)
+            .ok_or(ErrorCode::FeeVaultNotFound.into())
+    }
+
+    pub fn fee_vault_address(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let bump = self.fee_vault(mint)?.bump;
+        Pubkey::create_program_address(&[FEE_VAULT_SEED, mint.as_ref(), &[bump]], &crate::ID)
+            .map_err(|_| ErrorCode::FeeVaultNotFound.into())
+    }
+
+    pub fn register_fee_vault(&mut self, mint: Pubkey, bump: u8) -> Result<()> {
+        let count = self.fee_vault_count as usize;
+        require!(count < MAX_FEE_VAULTS, ErrorCode::TooManyFeeVaults);
+
+        self.fee_vaults[count] = FeeVaultCounter { mint, bump, accrued: 0, swept: 0 };
+        self.fee_vault_count += 1;
+        Ok(())
+    }
+
+    // Called once the fee has been transferred into the mint's fee vault
+    pub fn record_fee(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let vault = self.fee_vault_mut(mint)?;
+        vault.accrued = vault.accrued.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    pub fn insurance_vault(&self, mint: &Pubkey) -> Result<&InsuranceVaultCounter> {
+        self.insurance_vaults[..self.insurance_vault_count as usize]
+            .iter()
+            .find(|vault| vault.mint == *mint)
+            .ok_or(ErrorCode::InsuranceVaultNotFound.into())
+    }
+
+    pub fn insurance_vault_mut(&mut self, mint: &Pubkey) -> Result<&mut InsuranceVaultCounter> {
+        self.insurance_vaults[..self.insurance_vault_count as usize]
+            .iter_mut()
+            .find(|vault| vault.mint == *mint)
+            .ok_or(ErrorCode::InsuranceVaultNotFound.into())
+    }
+
+    pub fn insurance_vault_address(&self, mint: &Pubkey) -> Result<Pubkey> {
+        let bump = self.insurance_vault(mint)?.bump;
+        Pubkey::create_program_address(&[INSURANCE_VAULT_SEED, mint.as_ref(), &[bump]], &crate::ID)
+            .map_err(|_| ErrorCode::InsuranceVaultNotFound.into())
+    }
+
+    pub fn register_insurance_vault(&mut self, mint: Pubkey, bump: u8) -> Result<()> {
+        let count = self.insurance_vault_count as usize;
+        require!(count < MAX_FEE_VAULTS, ErrorCode::TooManyFeeVaults);
+
+        self.insurance_vaults[count] = InsuranceVaultCounter { mint, bump, deposited: 0, compensated: 0 };
+        self.insurance_vault_count += 1;
+        Ok(())
+    }
+
+    // Splits a protocol fee into the fee vault and insurance vault parts
+    pub fn insurance_split(&self, fee: u64) -> Result<(u64, u64)> {
+        let insurance = Self::fee_at(fee, self.insurance_share_bps)?;
+        Ok((fee - insurance, insurance))
+    }
+
+    // Called once the share has been transferred into the mint's insurance vault
+    pub fn record_insurance(&mut self, mint: &Pubkey, amount: u64) -> Result<()> {
+        if amount == 0 {
+            return Ok(());
+        }
+        let vault = self.insurance_vault_mut(mint)?;
+        vault.deposited = vault.deposited.checked_add(amount).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    // Accounts written before v5 have zeroed program IDs
+    pub fn upgrade_external_programs(&mut self) {
+        if self.external_programs == ExternalPrograms::default() {
+            self.external_programs = ExternalPrograms::MAINNET;
+        }
+    }
+
+    // Highest tier the volume qualifies for
+    pub fn fee_for_volume(&self, volume: u64) -> AppliedFee {
+        match self.active_fee_tiers().iter().rposition(|tier| volume >= tier.min_volume) {
+            Some(index) => AppliedFee {
+                tier: Some(index as u8),
+                deposit_fee_bps: self.fee_tiers[index].deposit_fee_bps,
+                withdraw_fee_bps: self.fee_tiers[index].withdraw_fee_bps,
+            },
+            None => AppliedFee {
+                tier: None,
+                deposit_fee_bps: self.protocol_fee_bps,
+                withdraw_fee_bps: self.protocol_fee_bps,
+            },
+        }
+    }
+}
+
+// Admin-managed registry entry for a supported token pair
+#[account]
+#[derive(InitSpace)]
+pub struct PairConfig {
+    pub version: u8,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    pub token_a_decimals: u8,
+    pub token_b_decimals: u8,
+
+    // Pyth feed quoting token B in token A units
+    pub price_feed_id: [u8; 32],
+
+    // Canonical venues for the pair
+    pub meteora_lb_pair: Pubkey,
+    pub kamino_lending_market: Pubkey,
+    pub kamino_reserve_a: Pubkey,
+    pub kamino_reserve_b: Pubkey,
+
+    // Position size limits in raw token units (0 = no limit).
+    // A position must reach the minimum in at least one token.
+    pub min_position_a: u64,
+    pub min_position_b: u64,
+    pub max_position_a: u64,
+    pub max_position_b: u64,
+
+    pub enabled: bool,
+    pub bump: u8,
+
+    // While in range, hold lending instead of LP when the pair's recent fee
+    // APR trails the Kamino supply APY by more than the margin (v2)
+    pub yield_check_enabled: bool,
+    pub yield_margin_bps: u16,
+
+    // Address lookup table holding the pair's venue accounts, owned by this
+    // PDA, for keepers' v0 transactions (v3)
+    pub lookup_table: Option<Pubkey>,
+
+    pub reserved: [u8; PAIR_RESERVED_BYTES],
+}
+
+// Pair parameters supplied by the admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PairConfigParams {
+    pub price_feed_id: [u8; 32],
+    pub meteora_lb_pair: Pubkey,
+    pub kamino_lending_market: Pubkey,
+    pub kamino_reserve_a: Pubkey,
+    pub kamino_reserve_b: Pubkey,
+    pub min_position_a: u64,
+    pub min_position_b: u64,
+    pub max_position_a: u64,
+    pub max_position_b: u64,
+    pub enabled: bool,
+    pub yield_check_enabled: bool,
+    pub yield_margin_bps: u16,
+}
+
+impl PairConfig {
+    // Value of `amount_b` in token A units at `price`
+    pub fn value_b_in_a(&self, amount_b: u64, price: u64) -> Result<u64> {
+        let value = (amount_b as u128)
+            .checked_mul(price as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_mul(10u128.pow(self.token_a_decimals as u32))
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10u128.pow(self.token_b_decimals as u32 + PRICE_DECIMALS))
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        u64::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+    
+    // Amount of token B worth `value_a` token A units at `price`
+    pub fn amount_b_for_value(&self, value_a: u64, price: u64) -> Result<u64> {
+        require!(price > 0, ErrorCode::StalePriceData);
+        
+        let amount = (value_a as u128)
+            .checked_mul(10u128.pow(self.token_b_decimals as u32 + PRICE_DECIMALS))
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10u128.pow(self.token_a_decimals as u32))
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(price as u128)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        u64::try_from(amount).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+    
+    // Deposit volume credit: token A value at VOLUME_DECIMALS. Token B only
+    // counts when an oracle price is supplied.
+    pub fn deposit_volume(&self, amount_a: u64, amount_b: u64, price: Option<u64>) -> Result<u64> {
+        let value_b = match price {
+            Some(price) => self.value_b_in_a(amount_b, price)?,
+            None => 0,
+        };
+        let value = (amount_a as u128)
+            .checked_add(value_b as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_mul(10u128.pow(VOLUME_DECIMALS))
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10u128.pow(self.token_a_decimals as u32))
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        u64::try_from(value).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+    
+    // Check a position's totals after a deposit against the pair limits
+    pub fn check_position_size(&self, total_a: u64, total_b: u64) -> Result<()> {
+        require!(
+            (self.max_position_a == 0 || total_a <= self.max_position_a) &&
+            (self.max_position_b == 0 || total_b <= self.max_position_b),
+            ErrorCode::PositionSizeOutOfBounds
+        );
+        require!(
+            total_a >= self.min_position_a || total_b >= self.min_position_b,
+            ErrorCode::PositionSizeOutOfBounds
+        );
+        Ok(())
+    }
 }
 
 #[account]
 #[derive(InitSpace)]
 pub struct UserMainAccount {
+    pub version: u8,
     pub owner: Pubkey,
     pub position_count: u64,
     pub total_positions_created: u64,
     pub bump: u8,
+
+    // Referral (v2): the Referrer PDA recorded at the first deposit
+    pub referrer: Option<Pubkey>,
+    pub first_deposit_at: i64, // 0 until the first deposit
+
+    // Cumulative deposit volume at VOLUME_DECIMALS, drives fee tiers (v3)
+    pub cumulative_deposit_value: u64,
+
+    // Ids of the open positions, so clients can find them without a
+    // program account scan (v4). Only the first open_position_count are set.
+    pub open_position_count: u8,
+    pub open_position_ids: [u64; MAX_OPEN_POSITIONS],
+
+    pub reserved: [u8; USER_RESERVED_BYTES],
+}
+
+impl UserMainAccount {
+    // Bring an account decoded at `from_version` up to date. Accounts from
+    // before v2 may already have deposited, so they are treated as past
+    // their first deposit and can no longer take a referrer.
+    pub fn upgrade_from(&mut self, from_version: u8, now: i64) {
+        if from_version < 2 && self.total_positions_created > 0 && self.first_deposit_at == 0 {
+            self.first_deposit_at = now;
+        }
+        self.version = USER_ACCOUNT_VERSION;
+    }
+
+    pub fn record_deposit_volume(&mut self, volume: u64) {
+        self.cumulative_deposit_value = self.cumulative_deposit_value.saturating_add(volume);
+    }
+
+    pub fn open_positions(&self) -> &[u64] {
+        &self.open_position_ids[..self.open_position_count as usize]
+    }
+
+    pub fn index_position(&mut self, position_id: u64) -> Result<()> {
+        if self.open_positions().contains(&position_id) {
+            return Ok(());
+        }
+        let count = self.open_position_count as usize;
+        require!(count < MAX_OPEN_POSITIONS, ErrorCode::TooManyOpenPositions);
+        
+        self.open_position_ids[count] = position_id;
+        self.open_position_count += 1;
+        Ok(())
+    }
+
+    // Swap-remove; the index is unordered
+    pub fn unindex_position(&mut self, position_id: u64) {
+        let count = self.open_position_count as usize;
+        if let Some(slot) = self.open_positions().iter().position(|id| *id == position_id) {
+            self.open_position_ids[slot] = self.open_position_ids[count - 1];
+            self.open_position_ids[count - 1] = 0;
+            self.open_position_count -= 1;
+        }
+    }
+}
+
+// Referral account for a wallet. Its share of deposit fees accrues in
+// token accounts owned by this PDA until the owner claims it.
+#[account]
+#[derive(InitSpace)]
+pub struct Referrer {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub tier: u8,
+    pub referred_users: u64,
+    pub bump: u8,
+    pub reserved: [u8; REFERRER_RESERVED_BYTES],
 }
 
+// Admin-set referral tier: share of the protocol deposit fee paid to referrers on it
 #[account]
 #[derive(InitSpace)]
+pub struct ReferralTier {
+    pub version: u8,
+    pub tier: u8,
+    pub fee_share_bps: u16,
+    pub bump: u8,
+    pub reserved: [u8; REFERRAL_TIER_RESERVED_BYTES],
+}
+
+impl ReferralTier {
+    pub fn referral_fee(&self, fee: u64) -> Result<u64> {
+        Ok((fee as u128)
+            .checked_mul(self.fee_share_bps as u128)
+            .ok_or(ErrorCode::MathOverflow)?
+            .checked_div(10_000)
+            .ok_or(ErrorCode::MathOverflow)? as u64)
+    }
+}
+
+// sha256("account:PositionZeroCopy")[..8]
+pub const POSITION_DISCRIMINATOR: &[u8] = &[55, 199, 185, 248, 167, 11, 115, 172];
+
+// Positions are zero-copy: instructions read and write the account data in
+// place instead of Borsh-decoding the whole struct. The layout is repr(C)
+// with explicit padding, so flags are 0/1 bytes, the out-of-range policy is
+// its variant index and unset keys are Pubkey::default(); use the accessors
+// below rather than the raw fields. It has its own discriminator, so
+// Borsh-encoded positions fail to load until migrate_position rewrites them.
+#[account(zero_copy, discriminator = POSITION_DISCRIMINATOR)]
+#[derive(InitSpace)]
 pub struct Position {
+    pub version: u8,
+    pub bump: u8,
+
+    // Position state
+    pub pause_flag: u8,
+    pub flagged: u8, // set when an audit discrepancy could not be reconciled
+
+    // Delta-neutral hedge leg: token A posted as Kamino collateral to borrow
+    // token B against the LP's token B exposure (v3)
+    pub hedge_enabled: u8,
+
+    // Which token to hold while the price is outside the range, and whether
+    // the funds currently sit converted to it (v4)
+    pub out_of_range_policy: u8,
+    pub out_of_range_converted: u8,
+
+    // Laddered LP rungs (lp_rung_count == 0 means single-range position)
+    pub lp_rung_count: u8,
+
     pub owner: Pubkey,
     pub position_id: u64,
     pub token_a_mint: Pubkey,
@@ -39,19 +504,522 @@ pub struct Position {
     // LP range configuration
     pub lp_range_min: u64,
     pub lp_range_max: u64,
+    pub lp_rungs: [LpRung; MAX_LP_RUNGS],
 
-    // Position state
-    pub pause_flag: bool,
     pub created_at: i64,
 
+    // Delegated operator (pause/resume, range changes, venue withdrawals)
+    pub operator: Pubkey,
+
     // Rebalancing tracking
     pub last_rebalance_price: u64,
     pub last_rebalance_slot: u64,
     pub total_rebalances: u64,
 
     // External protocol position tracking
-    pub meteora_position: Option<Pubkey>,
-    pub kamino_obligation: Option<Pubkey>,
+    pub meteora_position: Pubkey,
+    pub kamino_obligation: Pubkey,
+
+    // Cost basis: deposits net of fees, scaled down by withdrawals (v2)
+    pub net_deposit_a: u64,
+    pub net_deposit_b: u64,
+
+    pub hedge_collateral_a: u64,
+    pub hedge_borrowed_b: u64,
 
+    // Withdrawn share still sitting in LP or lending, owed to the owner
+    // through the position's withdrawal request (v5)
+    pub queued_withdraw_a: u64,
+    pub queued_withdraw_b: u64,
+
+    // Rebalance gate overrides (0 = protocol default) and the strategy
+    // preset the position was created from (v6)
+    pub rebalance_threshold_bps: u16,
+    pub padding: [u8; 6],
+    pub min_rebalance_slots: u64,
+    pub preset: Pubkey,
+
+    // Meteora pair protocol fee accumulators at the start of the current
+    // LP fee APR sample (v7)
+    pub lp_fee_snapshot_x: u64,
+    pub lp_fee_snapshot_y: u64,
+    pub lp_fee_snapshot_at: i64,
+
+    // Open multi-step rebalance; the position is locked until it ends (v8)
+    pub rebalance_session: Pubkey,
+
+    pub reserved: [u8; POSITION_RESERVED_BYTES],
+}
+
+// Account space is sized from INIT_SPACE while loads cast size_of bytes
+const _: () = assert!(Position::INIT_SPACE == std::mem::size_of::<Position>());
+
+// Part of a withdrawal the vaults couldn't pay out, e.g. while a Kamino
+// reserve is fully utilized. Keepers fill it as liquidity frees up; the
+// owner can cancel it and keep the share in the position.
+#[account]
+#[derive(InitSpace)]
+pub struct WithdrawalRequest {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub owed_a: u64, // gross, before the withdraw fee
+    pub owed_b: u64,
+    pub filled_a: u64,
+    pub filled_b: u64,
+    pub fee_bps: u16, // withdraw fee locked in when queued
+    pub created_at: i64,
     pub bump: u8,
+    pub reserved: [u8; WITHDRAWAL_REQUEST_RESERVED_BYTES],
+}
+
+// Rebalance split into phases that each fit in one transaction. The keeper
+// that starts it pays the rent and gets it back when it completes or expires.
+#[account]
+#[derive(InitSpace)]
+pub struct RebalanceSession {
+    pub version: u8,
+    pub position: Pubkey,
+    pub keeper: Pubkey,
+    pub phase: RebalancePhase,
+
+    // Target venue decided at the start, and whether the price had left the
+    // range (out-of-range conversion only applies then)
+    pub target_lp: bool,
+    pub price_in_range: bool,
+    pub start_price: u64,
+
+    pub started_slot: u64,
+    pub expires_slot: u64,
+    pub bump: u8,
+    pub reserved: [u8; REBALANCE_SESSION_RESERVED_BYTES],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebalancePhase {
+    Unwind, // pull funds out of the venue being left
+    Swap,   // out-of-range conversion or re-entry balancing
+    Deploy, // put the vault funds into the target venue
+}
+
+// Recurring deposit into a position, pulled from the owner's token accounts
+// through an SPL delegate approval granted to this PDA
+#[account]
+#[derive(InitSpace)]
+pub struct DepositSchedule {
+    pub version: u8,
+    pub owner: Pubkey,
+    pub position: Pubkey,
+    pub amount_a: u64,
+    pub amount_b: u64,
+    pub interval_seconds: i64,
+    pub next_execution_ts: i64,
+    pub end_ts: i64,
+    pub executions: u64,
+    pub bump: u8,
+    pub reserved: [u8; DEPOSIT_SCHEDULE_RESERVED_BYTES],
+}
+
+// A single Meteora sub-position covering its own price range
+#[zero_copy]
+#[derive(InitSpace, Debug, Default)]
+pub struct LpRung {
+    pub range_min: u64,
+    pub range_max: u64,
+    pub token_a_in_lp: u64,
+    pub token_b_in_lp: u64,
+    pub meteora_position: Pubkey, // Pubkey::default() until liquidity is added
+    pub weight_bps: u16,
+    pub padding: [u8; 6],
+}
+
+impl LpRung {
+    pub fn meteora_position(&self) -> Option<Pubkey> {
+        some_key(self.meteora_position)
+    }
+
+    pub fn contains(&self, price: u64) -> bool {
+        price >= self.range_min && price <= self.range_max
+    }
+
+    pub fn has_liquidity(&self) -> bool {
+        self.token_a_in_lp > 0 || self.token_b_in_lp > 0
+    }
+}
+
+// Rung parameters supplied by the owner when configuring a ladder
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct LpRungConfig {
+    pub range_min: u64,
+    pub range_max: u64,
+    pub weight_bps: u16,
+}
+
+// Admin-published position template. Ranges are offsets from the spot
+// price at creation, so one preset fits any price level.
+#[account]
+#[derive(InitSpace)]
+pub struct StrategyPreset {
+    pub version: u8,
+    pub name: [u8; 32],
+
+    // Single range: total width in bps of spot, centred on it
+    pub range_width_bps: u16,
+
+    // Rebalance gate for positions created from the preset
+    pub rebalance_threshold_bps: u16,
+    pub min_rebalance_slots: u64,
+
+    // Optional ladder allocation; replaces the single range when set
+    pub rung_count: u8,
+    pub rungs: [PresetRung; MAX_LP_RUNGS],
+
+    pub out_of_range_policy: OutOfRangePolicy,
+    pub hedge_enabled: bool,
+
+    pub enabled: bool,
+    pub bump: u8,
+    pub reserved: [u8; STRATEGY_PRESET_RESERVED_BYTES],
+}
+
+impl StrategyPreset {
+    pub fn active_rungs(&self) -> &[PresetRung] {
+        &self.rungs[..self.rung_count as usize]
+    }
+}
+
+// Ladder rung as bps below and above spot
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default)]
+pub struct PresetRung {
+    pub lower_bps: u16,
+    pub upper_bps: u16,
+    pub weight_bps: u16,
+}
+
+// Preset parameters supplied by the admin
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct StrategyPresetParams {
+    pub range_width_bps: u16,
+    pub rebalance_threshold_bps: u16,
+    pub min_rebalance_slots: u64,
+    pub rungs: Vec<PresetRung>,
+    pub out_of_range_policy: OutOfRangePolicy,
+    pub hedge_enabled: bool,
+    pub enabled: bool,
+}
+
+// Where harvested incentive rewards end up
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RewardRoute {
+    SwapToPosition,
+    SendToOwner,
+}
+
+// What happens to the pair tokens when the price leaves the range. Token A is
+// the quote token (prices are token B in token A units), token B the base.
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OutOfRangePolicy {
+    #[default]
+    Hold,           // lend both tokens as they come out of the LP
+    ConvertToQuote, // sell token B, lend only token A
+    ConvertToBase,  // sell token A, lend only token B
+}
+
+impl OutOfRangePolicy {
+    // Variant index as stored on the zero-copy position
+    pub fn from_index(index: u8) -> Self {
+        match index {
+            1 => OutOfRangePolicy::ConvertToQuote,
+            2 => OutOfRangePolicy::ConvertToBase,
+            _ => OutOfRangePolicy::Hold,
+        }
+    }
+}
+
+// Explicit resolution of an audit discrepancy
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuditResolution {
+    CreditDonation, // adopt vault surplus into the tracked balance
+    FlagPosition,   // halt automation until the admin clears it
+    ClearFlag,      // admin only
+}
+
+impl Position {
+    // Token totals across vault, LP and lending
+    pub fn total_token_a(&self) -> u64 {
+        self.token_a_vault_balance
+            .saturating_add(self.token_a_in_lp)
+            .saturating_add(self.token_a_in_lending)
+    }
+
+    pub fn total_token_b(&self) -> u64 {
+        self.token_b_vault_balance
+            .saturating_add(self.token_b_in_lp)
+            .saturating_add(self.token_b_in_lending)
+    }
+
+    // Owner or the appointed operator
+    pub fn is_authorized(&self, signer: &Pubkey) -> bool {
+        self.owner == *signer || self.operator() == Some(*signer)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_flag != 0
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.pause_flag = paused as u8;
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        self.flagged != 0
+    }
+
+    pub fn set_flagged(&mut self, flagged: bool) {
+        self.flagged = flagged as u8;
+    }
+
+    pub fn hedge_enabled(&self) -> bool {
+        self.hedge_enabled != 0
+    }
+
+    pub fn set_hedge_enabled(&mut self, enabled: bool) {
+        self.hedge_enabled = enabled as u8;
+    }
+
+    pub fn out_of_range_policy(&self) -> OutOfRangePolicy {
+        OutOfRangePolicy::from_index(self.out_of_range_policy)
+    }
+
+    pub fn set_out_of_range_policy(&mut self, policy: OutOfRangePolicy) {
+        self.out_of_range_policy = policy as u8;
+    }
+
+    pub fn out_of_range_converted(&self) -> bool {
+        self.out_of_range_converted != 0
+    }
+
+    pub fn set_out_of_range_converted(&mut self, converted: bool) {
+        self.out_of_range_converted = converted as u8;
+    }
+
+    pub fn operator(&self) -> Option<Pubkey> {
+        some_key(self.operator)
+    }
+
+    pub fn set_operator(&mut self, operator: Option<Pubkey>) {
+        self.operator = operator.unwrap_or_default();
+    }
+
+    pub fn meteora_position(&self) -> Option<Pubkey> {
+        some_key(self.meteora_position)
+    }
+
+    pub fn set_meteora_position(&mut self, meteora_position: Option<Pubkey>) {
+        self.meteora_position = meteora_position.unwrap_or_default();
+    }
+
+    pub fn kamino_obligation(&self) -> Option<Pubkey> {
+        some_key(self.kamino_obligation)
+    }
+
+    pub fn set_kamino_obligation(&mut self, obligation: Option<Pubkey>) {
+        self.kamino_obligation = obligation.unwrap_or_default();
+    }
+
+    pub fn preset(&self) -> Option<Pubkey> {
+        some_key(self.preset)
+    }
+
+    pub fn rebalance_session(&self) -> Option<Pubkey> {
+        some_key(self.rebalance_session)
+    }
+
+    pub fn set_rebalance_session(&mut self, session: Option<Pubkey>) {
+        self.rebalance_session = session.unwrap_or_default();
+    }
+
+    // Seeds for signing venue CPIs, copied out so no borrow of the account
+    // data is held across the invoke
+    pub fn signer(&self) -> PositionSigner {
+        PositionSigner {
+            owner: self.owner,
+            position_id: self.position_id.to_le_bytes(),
+            bump: [self.bump],
+        }
+    }
+
+    pub fn has_ladder(&self) -> bool {
+        self.lp_rung_count > 0
+    }
+
+    pub fn active_rungs(&self) -> &[LpRung] {
+        &self.lp_rungs[..self.lp_rung_count as usize]
+    }
+
+    // Collateral or debt left on Kamino, including after the hedge is disabled
+    pub fn has_open_hedge(&self) -> bool {
+        self.hedge_collateral_a > 0 || self.hedge_borrowed_b > 0
+    }
+
+    // Token B the hedge should owe before any collateral cap: the LP's B
+    // exposure while hedging is on, nothing once it's off
+    pub fn hedge_target_b(&self) -> u64 {
+        if self.hedge_enabled() { self.token_b_in_lp } else { 0 }
+    }
+
+    // Whether the hedge leg has anything to borrow, repay or release
+    pub fn hedge_needs_resize(&self) -> bool {
+        let target_b = self.hedge_target_b();
+        target_b != self.hedge_borrowed_b || (target_b == 0 && self.hedge_collateral_a > 0)
+    }
+
+    pub fn has_queued_withdrawal(&self) -> bool {
+        self.queued_withdraw_a > 0 || self.queued_withdraw_b > 0
+    }
+
+    pub fn is_rebalancing(&self) -> bool {
+        self.rebalance_session().is_some()
+    }
+
+    // Positions from before v2 never tracked deposits; their current
+    // holdings become the cost basis
+    pub fn seed_net_deposits(&mut self) {
+        self.net_deposit_a = self.total_token_a();
+        self.net_deposit_b = self.total_token_b();
+    }
+
+    // Take `percentage` of the holdings not already queued. The vaults pay
+    // what they hold (tracked and actual); the rest is queued. The cost
+    // basis shrinks with the whole share withdrawn.
+    pub fn split_withdrawal(&mut self, percentage: u8, vault_a: u64, vault_b: u64) -> Result<WithdrawalSplit> {
+        require!(percentage > 0 && percentage <= 100, ErrorCode::InvalidPercentage);
+        if percentage == 100 {
+            require!(!self.has_open_hedge(), ErrorCode::HedgeStillOpen);
+        }
+        
+        let available_a = self.total_token_a()
+            .checked_sub(self.queued_withdraw_a)
+            .ok_or(ErrorCode::MathOverflow)?;
+        // Token B borrowed for the hedge is owed back to Kamino
+        let available_b = self.total_token_b()
+            .saturating_sub(self.hedge_borrowed_b)
+            .saturating_sub(self.queued_withdraw_b);
+        
+        let withdraw_a = percent_of(available_a, percentage)?;
+        let withdraw_b = percent_of(available_b, percentage)?;
+        
+        let paid_a = withdraw_a.min(self.token_a_vault_balance).min(vault_a);
+        let paid_b = withdraw_b.min(self.token_b_vault_balance).min(vault_b);
+        let split = WithdrawalSplit {
+            paid_a,
+            paid_b,
+            queued_a: withdraw_a - paid_a,
+            queued_b: withdraw_b - paid_b,
+        };
+        
+        self.token_a_vault_balance -= split.paid_a;
+        self.token_b_vault_balance -= split.paid_b;
+        self.queued_withdraw_a = self.queued_withdraw_a
+            .checked_add(split.queued_a)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.queued_withdraw_b = self.queued_withdraw_b
+            .checked_add(split.queued_b)
+            .ok_or(ErrorCode::MathOverflow)?;
+        
+        if percentage == 100 {
+            self.net_deposit_a = 0;
+            self.net_deposit_b = 0;
+        } else {
+            self.net_deposit_a = percent_of(self.net_deposit_a, 100 - percentage)?;
+            self.net_deposit_b = percent_of(self.net_deposit_b, 100 - percentage)?;
+        }
+        
+        Ok(split)
+    }
+}
+
+pub struct PositionSigner {
+    owner: Pubkey,
+    position_id: [u8; 8],
+    bump: [u8; 1],
+}
+
+impl PositionSigner {
+    pub fn seeds(&self) -> [&[u8]; 4] {
+        [POSITION_SEED, self.owner.as_ref(), &self.position_id, &self.bump]
+    }
+}
+
+// Zero-copy accounts store an absent key as Pubkey::default()
+fn some_key(key: Pubkey) -> Option<Pubkey> {
+    (key != Pubkey::default()).then_some(key)
+}
+
+// Gross amounts of a withdrawal, before the withdraw fee
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WithdrawalSplit {
+    pub paid_a: u64,
+    pub paid_b: u64,
+    pub queued_a: u64,
+    pub queued_b: u64,
+}
+
+fn percent_of(amount: u64, percentage: u8) -> Result<u64> {
+    Ok((amount as u128)
+        .checked_mul(percentage as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        .checked_div(100)
+        .ok_or(ErrorCode::MathOverflow)? as u64)
+}
+
+// Value breakdown returned by get_position_value. Amounts are raw token
+// units, values are in token A units at the oracle price.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PositionValue {
+    pub position_id: u64,
+    pub price: u64,
+    pub confidence: u64,
+
+    pub vault_a: u64,
+    pub vault_b: u64,
+    pub lp_a: u64,
+    pub lp_b: u64,
+    pub lending_a: u64,
+    pub lending_b: u64,
+    pub lending_from_venue: bool, // lending amounts read from Kamino rather than tracked
+    pub hedge_collateral_a: u64,
+    pub hedge_borrowed_b: u64,
+
+    // Already owed to the owner and left out of the totals
+    pub queued_withdraw_a: u64,
+    pub queued_withdraw_b: u64,
+
+    pub total_a: u64,
+    pub total_b: u64,
+    pub total_value: u64, // net of the hedge borrow
+
+    pub net_deposit_a: u64,
+    pub net_deposit_b: u64,
+    pub net_deposit_value: u64,
+    pub unrealized_pnl: i64,
+}
+
+// Aggregated value returned by get_portfolio_value. All positions share
+// token A, so values add up in its units.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct PortfolioValue {
+    pub owner: Pubkey,
+    pub token_a_mint: Pubkey,
+    pub total_value: u64,
+    pub net_deposit_value: u64,
+    pub unrealized_pnl: i64,
+    pub positions: Vec<PositionValueSummary>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct PositionValueSummary {
+    pub position_id: u64,
+    pub total_value: u64,
+    pub unrealized_pnl: i64,
 }
@@ -0,0 +1,371 @@
+// strategy.rs
+// Rebalance decision logic with no account, sysvar or logging dependencies.
+// The on-chain rebalance and the off-chain backtester both call into this so
+// a simulated strategy makes exactly the decisions the program would.
+use anchor_lang::prelude::*;
+use crate::constants::*;
+use crate::errors::ErrorCode;
+use crate::events::RebalanceAction;
+use crate::state::Position;
+
+/// Where the price sits relative to a range once the oracle confidence is applied
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PriceZone {
+    InRange,
+    OutOfRange,
+    // Confidence band straddles a range boundary
+    Uncertain,
+}
+
+pub fn classify_price(price: u64, confidence: u64, range_min: u64, range_max: u64) -> PriceZone {
+    let price_lower = price.saturating_sub(confidence);
+    let price_upper = price.saturating_add(confidence);
+
+    if price_lower >= range_min && price_upper <= range_max {
+        PriceZone::InRange
+    } else if price_upper < range_min || price_lower > range_max {
+        PriceZone::OutOfRange
+    } else {
+        PriceZone::Uncertain
+    }
+}
+
+/// Tunables for the rebalance gate
+#[derive(Clone, Copy, Debug)]
+pub struct RebalanceParams {
+    pub threshold_bps: u16,
+    pub min_slots_between: u64,
+}
+
+impl Default for RebalanceParams {
+    fn default() -> Self {
+        Self {
+            threshold_bps: REBALANCE_THRESHOLD_BPS,
+            min_slots_between: MIN_SLOTS_BETWEEN_REBALANCES,
+        }
+    }
+}
+
+impl RebalanceParams {
+    /// A position's own thresholds, falling back to the defaults where unset
+    pub fn for_position(position: &Position) -> Self {
+        let defaults = Self::default();
+        Self {
+            threshold_bps: match position.rebalance_threshold_bps {
+                0 => defaults.threshold_bps,
+                bps => bps,
+            },
+            min_slots_between: match position.min_rebalance_slots {
+                0 => defaults.min_slots_between,
+                slots => slots,
+            },
+        }
+    }
+}
+
+/// Range from `lower_bps` below to `upper_bps` above `price`
+pub fn range_around(price: u64, lower_bps: u16, upper_bps: u16) -> Result<(u64, u64)> {
+    require!(lower_bps < 10_000, ErrorCode::InvalidPriceRange);
+
+    let scale = |bps: u64| -> Result<u64> {
+        let scaled = (price as u128 * bps as u128) / 10_000;
+        u64::try_from(scaled).map_err(|_| ErrorCode::MathOverflow.into())
+    };
+    let range_min = scale(10_000 - lower_bps as u64)?;
+    let range_max = scale(10_000 + upper_bps as u64)?;
+
+    require!(range_min > 0 && range_min < range_max, ErrorCode::InvalidPriceRange);
+    Ok((range_min, range_max))
+}
+
+/// Outcome of the time and price-movement checks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebalanceGate {
+    Ready,
+    TooSoon { slots_since: u64 },
+    BelowThreshold { change_bps: u64 },
+}
+
+pub fn rebalance_gate(
+    params: &RebalanceParams,
+    slots_since: u64,
+    last_price: u64,
+    current_price: u64,
+) -> Result<RebalanceGate> {
+    if slots_since < params.min_slots_between {
+        return Ok(RebalanceGate::TooSoon { slots_since });
+    }
+
+    // First rebalance has no reference price
+    if last_price > 0 {
+        let change_bps = current_price.abs_diff(last_price)
+            .checked_mul(10_000)
+            .ok_or(ErrorCode::MathOverflow)?
+            / last_price;
+
+        if change_bps < params.threshold_bps as u64 {
+            return Ok(RebalanceGate::BelowThreshold { change_bps });
+        }
+    }
+
+    Ok(RebalanceGate::Ready)
+}
+
+/// Which buckets currently hold funds
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Allocation {
+    pub has_lp: bool,
+    pub has_lending: bool,
+    pub has_idle: bool,
+}
+
+impl Allocation {
+    pub fn of(position: &Position) -> Self {
+        Self {
+            has_lp: position.token_a_in_lp > 0 || position.token_b_in_lp > 0,
+            has_lending: position.token_a_in_lending > 0 || position.token_b_in_lending > 0,
+            has_idle: position.token_a_vault_balance > 0 || position.token_b_vault_balance > 0,
+        }
+    }
+
+    /// Funds sit somewhere other than where the price says they belong
+    pub fn needs_rebalance(&self, in_range: bool) -> bool {
+        (in_range && self.has_lending) ||  // Should be in LP but in lending
+        (!in_range && self.has_lp) ||      // Should be in lending but in LP
+        self.has_idle                      // Has idle funds to deploy
+    }
+
+    /// Move for a single-range position
+    pub fn plan(&self, in_range: bool) -> RebalanceAction {
+        if in_range && (self.has_idle || self.has_lending) {
+            RebalanceAction::MoveToLP
+        } else if !in_range && (self.has_idle || self.has_lp) {
+            RebalanceAction::MoveToLending
+        } else {
+            RebalanceAction::NoAction
+        }
+    }
+}
+
+/// Loan-to-value of a borrow against collateral, both valued in token A, in bps
+pub fn hedge_ltv_bps(debt_value: u64, collateral_value: u64) -> u16 {
+    if debt_value == 0 {
+        return 0;
+    }
+    if collateral_value == 0 {
+        return u16::MAX;
+    }
+    (debt_value as u128 * 10_000 / collateral_value as u128).min(u16::MAX as u128) as u16
+}
+
+/// Largest debt value `collateral_value` carries at `target_ltv_bps`
+pub fn hedge_debt_capacity(collateral_value: u64, target_ltv_bps: u16) -> u64 {
+    (collateral_value as u128 * target_ltv_bps as u128 / 10_000) as u64
+}
+
+/// Collateral value needed to carry `debt_value` at `target_ltv_bps`, rounded up
+pub fn hedge_collateral_required(debt_value: u64, target_ltv_bps: u16) -> Result<u64> {
+    if target_ltv_bps == 0 {
+        // Collateral the reserve won't lend against can never carry debt
+        return Ok(if debt_value == 0 { 0 } else { u64::MAX });
+    }
+    let required = (debt_value as u128 * 10_000).div_ceil(target_ltv_bps as u128);
+    u64::try_from(required).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Borrow rate at `utilization_bps` on a Kamino rate curve of
+/// (utilization bps, borrow rate bps) points, interpolated linearly
+pub fn curve_borrow_rate_bps(curve: &[(u32, u32)], utilization_bps: u32) -> u64 {
+    let mut previous = match curve.first() {
+        Some(&point) => point,
+        None => return 0,
+    };
+    for &(utilization, rate) in curve.iter().skip(1) {
+        if utilization_bps <= utilization {
+            let (prev_utilization, prev_rate) = previous;
+            if utilization <= prev_utilization {
+                return rate as u64;
+            }
+            let progress = (utilization_bps.saturating_sub(prev_utilization)) as i128;
+            let slope = rate as i128 - prev_rate as i128;
+            let span = (utilization - prev_utilization) as i128;
+            return (prev_rate as i128 + slope * progress / span).max(0) as u64;
+        }
+        previous = (utilization, rate);
+    }
+    previous.1 as u64
+}
+
+/// Lenders earn the borrow rate on the borrowed share, less the protocol take
+pub fn supply_apy_bps(borrow_rate_bps: u64, utilization_bps: u32, protocol_take_rate_pct: u8) -> u64 {
+    let take = protocol_take_rate_pct.min(100) as u128;
+    (borrow_rate_bps as u128 * utilization_bps as u128 * (100 - take) / (10_000 * 100)) as u64
+}
+
+/// Annualised LP fee yield in bps from fees earned over `elapsed_seconds`
+/// against the liquidity that earned them, both valued in token A
+pub fn lp_fee_apr_bps(fee_value: u64, tvl_value: u64, elapsed_seconds: i64) -> u64 {
+    if tvl_value == 0 || elapsed_seconds <= 0 {
+        return 0;
+    }
+    let apr = fee_value as u128 * 10_000 * SECONDS_PER_YEAR as u128
+        / (tvl_value as u128 * elapsed_seconds as u128);
+    apr.min(u64::MAX as u128) as u64
+}
+
+/// In range, lending still wins when LP fees trail it by more than the margin
+pub fn lending_outyields_lp(lp_fee_apr_bps: u64, lending_apy_bps: u64, margin_bps: u16) -> bool {
+    lending_apy_bps > lp_fee_apr_bps.saturating_add(margin_bps as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: RebalanceParams = RebalanceParams { threshold_bps: 100, min_slots_between: 10 };
+
+    #[test]
+    fn classify_price_applies_confidence() {
+        assert_eq!(classify_price(150, 5, 100, 200), PriceZone::InRange);
+        // Confidence band touching the bounds still counts as in range
+        assert_eq!(classify_price(105, 5, 100, 200), PriceZone::InRange);
+        assert_eq!(classify_price(195, 5, 100, 200), PriceZone::InRange);
+        assert_eq!(classify_price(102, 5, 100, 200), PriceZone::Uncertain);
+        assert_eq!(classify_price(198, 5, 100, 200), PriceZone::Uncertain);
+        assert_eq!(classify_price(94, 5, 100, 200), PriceZone::OutOfRange);
+        assert_eq!(classify_price(206, 5, 100, 200), PriceZone::OutOfRange);
+        // Band ending exactly on a bound overlaps it
+        assert_eq!(classify_price(95, 5, 100, 200), PriceZone::Uncertain);
+    }
+
+    #[test]
+    fn classify_price_saturates_at_the_extremes() {
+        assert_eq!(classify_price(3, 10, 100, 200), PriceZone::OutOfRange);
+        assert_eq!(classify_price(u64::MAX - 1, 10, 100, 200), PriceZone::OutOfRange);
+        assert_eq!(classify_price(5, 10, 0, 200), PriceZone::InRange);
+    }
+
+    #[test]
+    fn rebalance_gate_waits_for_min_slots() {
+        assert_eq!(
+            rebalance_gate(&PARAMS, 9, 100_000, 200_000).unwrap(),
+            RebalanceGate::TooSoon { slots_since: 9 }
+        );
+        assert_eq!(rebalance_gate(&PARAMS, 10, 100_000, 200_000).unwrap(), RebalanceGate::Ready);
+    }
+
+    #[test]
+    fn rebalance_gate_needs_threshold_move() {
+        assert_eq!(
+            rebalance_gate(&PARAMS, 10, 100_000, 100_999).unwrap(),
+            RebalanceGate::BelowThreshold { change_bps: 99 }
+        );
+        assert_eq!(
+            rebalance_gate(&PARAMS, 10, 100_000, 99_001).unwrap(),
+            RebalanceGate::BelowThreshold { change_bps: 99 }
+        );
+        assert_eq!(rebalance_gate(&PARAMS, 10, 100_000, 101_000).unwrap(), RebalanceGate::Ready);
+        assert_eq!(rebalance_gate(&PARAMS, 10, 100_000, 99_000).unwrap(), RebalanceGate::Ready);
+    }
+
+    #[test]
+    fn rebalance_gate_first_rebalance_has_no_reference() {
+        assert_eq!(rebalance_gate(&PARAMS, u64::MAX, 0, 123).unwrap(), RebalanceGate::Ready);
+    }
+
+    #[test]
+    fn rebalance_gate_rejects_overflowing_moves() {
+        assert!(rebalance_gate(&PARAMS, 10, 1, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn hedge_ltv_handles_empty_sides() {
+        assert_eq!(hedge_ltv_bps(0, 0), 0);
+        assert_eq!(hedge_ltv_bps(0, 1_000), 0);
+        assert_eq!(hedge_ltv_bps(1, 0), u16::MAX);
+    }
+
+    #[test]
+    fn hedge_ltv_rounds_down_and_saturates() {
+        assert_eq!(hedge_ltv_bps(1, 3), 3_333);
+        assert_eq!(hedge_ltv_bps(1_000, 1_000), 10_000);
+        assert_eq!(hedge_ltv_bps(u64::MAX, u64::MAX), 10_000);
+        // Debt far beyond the collateral caps at the u16 range
+        assert_eq!(hedge_ltv_bps(7, 1), u16::MAX);
+        assert_eq!(hedge_ltv_bps(u64::MAX, 1), u16::MAX);
+    }
+
+    #[test]
+    fn hedge_debt_capacity_rounds_down() {
+        assert_eq!(hedge_debt_capacity(1_000, 0), 0);
+        assert_eq!(hedge_debt_capacity(0, 5_000), 0);
+        assert_eq!(hedge_debt_capacity(999, 5_000), 499);
+        assert_eq!(hedge_debt_capacity(1_000, 10_000), 1_000);
+        assert_eq!(hedge_debt_capacity(u64::MAX, 10_000), u64::MAX);
+    }
+
+    #[test]
+    fn hedge_collateral_required_rounds_up() {
+        assert_eq!(hedge_collateral_required(0, 5_000).unwrap(), 0);
+        assert_eq!(hedge_collateral_required(1, 5_000).unwrap(), 2);
+        assert_eq!(hedge_collateral_required(999, 3_000).unwrap(), 3_330);
+        assert_eq!(hedge_collateral_required(1_000, 10_000).unwrap(), 1_000);
+        // The required collateral always carries the debt it was sized for
+        let required = hedge_collateral_required(1_001, 7_000).unwrap();
+        assert!(hedge_debt_capacity(required, 7_000) >= 1_001);
+        assert!(hedge_debt_capacity(required - 1, 7_000) < 1_001);
+    }
+
+    #[test]
+    fn hedge_collateral_required_at_the_limits() {
+        // Zero LTV collateral carries nothing
+        assert_eq!(hedge_collateral_required(0, 0).unwrap(), 0);
+        assert_eq!(hedge_collateral_required(1, 0).unwrap(), u64::MAX);
+        assert_eq!(hedge_collateral_required(u64::MAX, 10_000).unwrap(), u64::MAX);
+        assert!(hedge_collateral_required(u64::MAX, 9_999).is_err());
+    }
+
+    #[test]
+    fn lp_fee_apr_annualises_the_sample() {
+        // 1% of TVL earned over a day is 365% a year
+        assert_eq!(lp_fee_apr_bps(1_000, 100_000, 86_400), 36_500);
+        assert_eq!(lp_fee_apr_bps(500, 100_000, SECONDS_PER_YEAR), 50);
+        assert_eq!(lp_fee_apr_bps(0, 100_000, 86_400), 0);
+    }
+
+    #[test]
+    fn lp_fee_apr_without_a_usable_sample_is_zero() {
+        assert_eq!(lp_fee_apr_bps(1_000, 0, 86_400), 0);
+        assert_eq!(lp_fee_apr_bps(1_000, 100_000, 0), 0);
+        assert_eq!(lp_fee_apr_bps(1_000, 100_000, -5), 0);
+        assert_eq!(lp_fee_apr_bps(u64::MAX, 1, 1), u64::MAX);
+    }
+
+    #[test]
+    fn lending_needs_to_beat_lp_by_the_margin() {
+        assert!(!lending_outyields_lp(500, 600, 100));
+        assert!(lending_outyields_lp(500, 601, 100));
+        assert!(lending_outyields_lp(500, 501, 0));
+        assert!(!lending_outyields_lp(500, 500, 0));
+        assert!(!lending_outyields_lp(u64::MAX, u64::MAX, u16::MAX));
+    }
+
+    #[test]
+    fn borrow_rate_interpolates_the_curve() {
+        let curve = [(0, 0), (8_000, 1_000), (10_000, 5_000)];
+        assert_eq!(curve_borrow_rate_bps(&curve, 0), 0);
+        assert_eq!(curve_borrow_rate_bps(&curve, 4_000), 500);
+        assert_eq!(curve_borrow_rate_bps(&curve, 8_000), 1_000);
+        assert_eq!(curve_borrow_rate_bps(&curve, 9_000), 3_000);
+        assert_eq!(curve_borrow_rate_bps(&curve, 12_000), 5_000);
+        assert_eq!(curve_borrow_rate_bps(&[], 5_000), 0);
+    }
+
+    #[test]
+    fn supply_apy_shares_the_borrow_rate() {
+        // 10% borrow rate at 50% utilization with a 20% take
+        assert_eq!(supply_apy_bps(1_000, 5_000, 20), 400);
+        assert_eq!(supply_apy_bps(1_000, 10_000, 0), 1_000);
+        assert_eq!(supply_apy_bps(1_000, 5_000, 150), 0);
+    }
+}
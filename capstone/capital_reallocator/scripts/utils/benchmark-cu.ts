@@ -0,0 +1,129 @@
+// scripts/utils/benchmark-cu.ts
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { CapitalReallocator } from "../../target/types/capital_reallocator";
+import {
+  Connection,
+  PublicKey,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { BN } from "bn.js";
+import { execSync } from 'child_process';
+import { existsSync, readFileSync, writeFileSync } from 'fs';
+import { loadState, loadUserKeypair } from './setup';
+
+const RESULTS_FILE = 'scripts/cu-benchmark.json';
+
+// Simulate without signatures so the benchmark never changes on-chain state
+async function unitsConsumed(
+  connection: Connection,
+  payer: PublicKey,
+  instruction: TransactionInstruction,
+): Promise<number | null> {
+  const { blockhash } = await connection.getLatestBlockhash();
+  const message = new TransactionMessage({
+    payerKey: payer,
+    recentBlockhash: blockhash,
+    instructions: [instruction],
+  }).compileToV0Message();
+
+  const result = await connection.simulateTransaction(new VersionedTransaction(message), {
+    sigVerify: false,
+    replaceRecentBlockhash: true,
+  });
+  if (result.value.err) {
+    console.log('⚠️  Simulation failed:', JSON.stringify(result.value.err));
+    (result.value.logs || []).slice(-5).forEach((log) => console.log('   ', log));
+    return null;
+  }
+  return result.value.unitsConsumed ?? null;
+}
+
+async function benchmarkComputeUnits() {
+  console.log('⏱️  Measuring compute units for position instructions...');
+
+  const state = loadState();
+  const user = loadUserKeypair();
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.CapitalReallocator as Program<CapitalReallocator>;
+
+  if (!state.position) {
+    throw new Error('No position found. Run create-position.ts first');
+  }
+
+  const position = new PublicKey(state.position);
+  const shared = {
+    position,
+    protocolAuthority: new PublicKey(state.protocolAuthority),
+    userTokenA: new PublicKey(state.userTokenA),
+    userTokenB: new PublicKey(state.userTokenB),
+    positionTokenAVault: new PublicKey(state.positionTokenAVault),
+    positionTokenBVault: new PublicKey(state.positionTokenBVault),
+    feeVaultA: new PublicKey(state.feeVaultA),
+    feeVaultB: new PublicKey(state.feeVaultB),
+    userMainAccount: new PublicKey(state.userMainAccount),
+    owner: user.publicKey,
+    tokenAMint: new PublicKey(state.tokenAMint),
+    tokenBMint: new PublicKey(state.tokenBMint),
+    tokenProgram: TOKEN_PROGRAM_ID,
+  };
+
+  const instructions: Record<string, TransactionInstruction> = {
+    deposit_to_position: await program.methods
+      .depositToPosition(new BN(1_000_000), new BN(0))
+      .accountsPartial({
+        ...shared,
+        pairConfig: new PublicKey(state.pairConfig),
+        referrer: null,
+        referralTier: null,
+        referrerTokenA: null,
+        referrerTokenB: null,
+      })
+      .instruction(),
+    withdraw_from_position: await program.methods
+      .withdrawFromPosition(50)
+      .accountsPartial(shared)
+      .instruction(),
+    pause_position: await program.methods
+      .pausePosition()
+      .accountsPartial({ position, authority: user.publicKey })
+      .instruction(),
+  };
+
+  const results: Record<string, number | null> = {};
+  for (const [name, instruction] of Object.entries(instructions)) {
+    results[name] = await unitsConsumed(provider.connection, user.publicKey, instruction);
+    console.log(`${name.padEnd(24)} ${results[name] ?? 'n/a'} CU`);
+  }
+
+  // Key results by commit so runs before and after a layout change can be diffed
+  const commit = execSync('git rev-parse --short HEAD').toString().trim();
+  const history = existsSync(RESULTS_FILE) ? JSON.parse(readFileSync(RESULTS_FILE, 'utf8')) : {};
+  history[commit] = results;
+  writeFileSync(RESULTS_FILE, JSON.stringify(history, null, 2));
+
+  console.log(`\n💾 Results saved to ${RESULTS_FILE} under ${commit}`);
+
+  // Markdown table against the previous recorded commit, e.g. the last
+  // Borsh build when run right after the zero-copy change
+  const commits = Object.keys(history).filter((key) => key !== commit);
+  const before = commits[commits.length - 1];
+  if (before) {
+    const cell = (units: number | null | undefined) => (units == null ? 'n/a' : units.toString());
+    console.log(`\n| Instruction | ${before} | ${commit} |`);
+    console.log('|---|---|---|');
+    for (const name of Object.keys(results)) {
+      console.log(`| \`${name}\` | ${cell(history[before][name])} | ${cell(results[name])} |`);
+    }
+  }
+}
+
+benchmarkComputeUnits().catch((error) => {
+  console.error('❌ Benchmark failed:', error);
+  process.exit(1);
+});
@@ -45,7 +45,7 @@ async function checkBalances() {
       console.log('Position ID:', positionState.positionId.toString());
       console.log('Owner:', positionState.owner.toString());
       console.log('Created at:', new Date(positionState.createdAt.toNumber() * 1000).toISOString());
-      console.log('Paused:', positionState.pauseFlag);
+      console.log('Paused:', positionState.pauseFlag === 1);
       
       console.log('\nLP Range:');
       console.log('Min: $' + (positionState.lpRangeMin.toNumber() / 10**6).toFixed(2));
@@ -98,14 +98,21 @@ async function checkBalances() {
   }
   
   // Fee Balances
-  if (state.feeTokenA && state.feeTokenB) {
-    console.log('\n💰 Protocol Fee Balances:');
+  if (state.feeVaultA && state.feeVaultB) {
+    console.log('\n💰 Protocol Fee Vaults:');
     try {
-      const feeAccountA = await getAccount(provider.connection, new PublicKey(state.feeTokenA));
-      const feeAccountB = await getAccount(provider.connection, new PublicKey(state.feeTokenB));
+      const feeAccountA = await getAccount(provider.connection, new PublicKey(state.feeVaultA));
+      const feeAccountB = await getAccount(provider.connection, new PublicKey(state.feeVaultB));
       
-      console.log('Fee Token A collected:', (Number(feeAccountA.amount) / 10**6).toFixed(6));
-      console.log('Fee Token B collected:', (Number(feeAccountB.amount) / 10**9).toFixed(9));
+      console.log('Fee Token A unswept:', (Number(feeAccountA.amount) / 10**6).toFixed(6));
+      console.log('Fee Token B unswept:', (Number(feeAccountB.amount) / 10**9).toFixed(9));
+      
+      const protocolState = await program.account.protocolAuthority.fetch(
+        new PublicKey(state.protocolAuthority)
+      );
+      for (const vault of protocolState.feeVaults.slice(0, protocolState.feeVaultCount)) {
+        console.log(`Mint ${vault.mint.toString().slice(0, 8)}... accrued: ${vault.accrued.toString()}, swept: ${vault.swept.toString()}`);
+      }
       
     } catch (error) {
       console.log('❌ Error fetching fee balances:', error.message);
@@ -23,6 +23,10 @@ async function createPosition() {
   anchor.setProvider(provider);
   const program = anchor.workspace.CapitalReallocator as Program<CapitalReallocator>;
   
+  if (!state.pairConfig) {
+    throw new Error('Token pair not registered. Run set-pair.ts first');
+  }
+  
   // Position parameters
   const positionId = new BN(1);
   const lpRangeMin = new BN(rangeMinUSD * 10**6); 
@@ -64,6 +68,7 @@ async function createPosition() {
       position,
       userMainAccount: new PublicKey(state.userMainAccount),
       protocolAuthority: new PublicKey(state.protocolAuthority),
+      pairConfig: new PublicKey(state.pairConfig),
       tokenAMint: new PublicKey(state.tokenAMint),
       tokenBMint: new PublicKey(state.tokenBMint),
       positionTokenAVault,
@@ -89,7 +94,7 @@ async function createPosition() {
   console.log('💰 Token B mint:', positionState.tokenBMint.toString());
   console.log('📈 LP Range Min:', (positionState.lpRangeMin.toNumber() / 10**6).toFixed(2));
   console.log('📈 LP Range Max:', (positionState.lpRangeMax.toNumber() / 10**6).toFixed(2));
-  console.log('⏸️  Paused:', positionState.pauseFlag);
+  console.log('⏸️  Paused:', positionState.pauseFlag === 1);
   console.log('📅 Created at:', new Date(positionState.createdAt.toNumber() * 1000).toISOString());
   
   // Verify vault balances
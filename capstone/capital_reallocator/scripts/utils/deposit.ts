@@ -57,12 +57,18 @@ async function depositTokens() {
     .accountsPartial({
       position: new PublicKey(state.position),
       protocolAuthority: new PublicKey(state.protocolAuthority),
+      pairConfig: new PublicKey(state.pairConfig),
       userTokenA: new PublicKey(state.userTokenA),
       userTokenB: new PublicKey(state.userTokenB),
       positionTokenAVault: new PublicKey(state.positionTokenAVault),
       positionTokenBVault: new PublicKey(state.positionTokenBVault),
-      feeTokenA: new PublicKey(state.feeTokenA),
-      feeTokenB: new PublicKey(state.feeTokenB),
+      feeVaultA: new PublicKey(state.feeVaultA),
+      feeVaultB: new PublicKey(state.feeVaultB),
+      userMainAccount: new PublicKey(state.userMainAccount),
+      referrer: null,
+      referralTier: null,
+      referrerTokenA: null,
+      referrerTokenB: null,
       owner: user.publicKey,
       tokenAMint: new PublicKey(state.tokenAMint),
       tokenBMint: new PublicKey(state.tokenBMint),
@@ -77,8 +83,8 @@ async function depositTokens() {
   // Get final balances
   const userAccountAAfter = await getAccount(provider.connection, new PublicKey(state.userTokenA));
   const userAccountBAfter = await getAccount(provider.connection, new PublicKey(state.userTokenB));
-  const feeAccountA = await getAccount(provider.connection, new PublicKey(state.feeTokenA));
-  const feeAccountB = await getAccount(provider.connection, new PublicKey(state.feeTokenB));
+  const feeAccountA = await getAccount(provider.connection, new PublicKey(state.feeVaultA));
+  const feeAccountB = await getAccount(provider.connection, new PublicKey(state.feeVaultB));
   
   // Get position state
   const positionState = await program.account.position.fetch(new PublicKey(state.position));
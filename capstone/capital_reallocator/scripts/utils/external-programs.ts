@@ -0,0 +1,66 @@
+// scripts/utils/external-programs.ts
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { CapitalReallocator } from "../../target/types/capital_reallocator";
+import { PublicKey } from "@solana/web3.js";
+import { loadState, loadUserKeypair } from './setup';
+
+// Usage: yarn external-programs [show]
+//        yarn external-programs propose <meteora dlmm> <kamino lending> <kamino farms> <jupiter>
+//        yarn external-programs apply|cancel
+// Proposals apply at once while no positions are open (e.g. localnet with
+// mock programs), otherwise they wait out the 2 day timelock.
+async function externalPrograms() {
+  const [command = 'show', ...args] = process.argv.slice(2);
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.CapitalReallocator as Program<CapitalReallocator>;
+
+  // The protocol admin is the account that ran init-protocol
+  const user = loadUserKeypair();
+  const state = loadState();
+  const protocolAuthority = new PublicKey(state.protocolAuthority);
+  const accounts = { protocolAuthority, admin: user.publicKey };
+
+  if (command === 'propose') {
+    if (args.length !== 4) {
+      throw new Error('Pass the Meteora DLMM, Kamino lending, Kamino farms and Jupiter program IDs');
+    }
+    const [meteoraDlmm, kaminoLending, kaminoFarms, jupiter] = args.map((arg) => new PublicKey(arg));
+    const tx = await program.methods
+      .proposeExternalPrograms({ meteoraDlmm, kaminoLending, kaminoFarms, jupiter })
+      .accountsPartial(accounts)
+      .signers([user])
+      .rpc();
+    console.log('📝 Proposed:', tx);
+  } else if (command === 'apply') {
+    const tx = await program.methods.applyExternalPrograms().accountsPartial(accounts).signers([user]).rpc();
+    console.log('✅ Applied:', tx);
+  } else if (command === 'cancel') {
+    const tx = await program.methods.cancelExternalPrograms().accountsPartial(accounts).signers([user]).rpc();
+    console.log('🚫 Cancelled:', tx);
+  } else if (command !== 'show') {
+    throw new Error(`Unknown command ${command}`);
+  }
+
+  const protocol = await program.account.protocolAuthority.fetch(protocolAuthority);
+  const show = (label: string, programs: typeof protocol.externalPrograms) => {
+    console.log(`\n${label}`);
+    console.log('  Meteora DLMM:  ', programs.meteoraDlmm.toString());
+    console.log('  Kamino lending:', programs.kaminoLending.toString());
+    console.log('  Kamino farms:  ', programs.kaminoFarms.toString());
+    console.log('  Jupiter:       ', programs.jupiter.toString());
+  };
+
+  show('🔌 Active venue programs', protocol.externalPrograms);
+  const eta = protocol.pendingExternalProgramsEta.toNumber();
+  if (eta > 0) {
+    show(`⏳ Pending, can be applied from ${new Date(eta * 1000).toISOString()}`, protocol.pendingExternalPrograms);
+  }
+}
+
+externalPrograms().catch((error) => {
+  console.error('❌ External program update failed:', error);
+  process.exit(1);
+});
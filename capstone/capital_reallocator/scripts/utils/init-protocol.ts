@@ -4,8 +4,6 @@ import { Program } from "@coral-xyz/anchor";
 import { CapitalReallocator } from "../../target/types/capital_reallocator";
 import { PublicKey, Keypair } from "@solana/web3.js";
 import { 
-  getAssociatedTokenAddress, 
-  createAssociatedTokenAccountInstruction,
   TOKEN_PROGRAM_ID,
   TOKEN_2022_PROGRAM_ID
 } from "@solana/spl-token";
@@ -69,88 +67,13 @@ async function initializeProtocol() {
     console.log('📊 Protocol Fee:', protocolAccount.protocolFeeBps, 'bps');
     console.log('🔢 Total Positions:', protocolAccount.totalPositions.toString());
     
-    // Still create fee recipient token accounts if they don't exist
-    await ensureFeeRecipientAccounts(program, user, state, protocolAccount.feeRecipient);
+    // Still create the fee vaults if they don't exist
+    await ensureFeeVaults(program, user, state);
     return;
   } catch (error) {
     console.log('🆕 Protocol not initialized, creating...');
   }
   
-  // Get token mints and detect their programs
-  const tokenAMint = new PublicKey(state.tokenAMint);
-  const tokenBMint = new PublicKey(state.tokenBMint);
-  
-  // Detect token programs
-  const tokenAProgram = await getTokenProgram(provider.connection, tokenAMint);
-  const tokenBProgram = await getTokenProgram(provider.connection, tokenBMint);
-  
-  // Get associated token addresses with correct programs
-  const feeTokenA = await getAssociatedTokenAddress(
-    tokenAMint, 
-    feeRecipient.publicKey,
-    false, // allowOwnerOffCurve
-    tokenAProgram
-  );
-  
-  const feeTokenB = await getAssociatedTokenAddress(
-    tokenBMint, 
-    feeRecipient.publicKey,
-    false,
-    tokenBProgram
-  );
-  
-  console.log('💼 Fee Token A account:', feeTokenA.toString());
-  console.log('💼 Fee Token B account:', feeTokenB.toString());
-  
-  // Create associated token accounts with correct programs
-  const createFeeAccountsIx = [];
-  
-  // Check if accounts exist first
-  const feeTokenAInfo = await provider.connection.getAccountInfo(feeTokenA);
-  const feeTokenBInfo = await provider.connection.getAccountInfo(feeTokenB);
-  
-  if (feeTokenAInfo) {
-    console.log('✅ Fee Token A account already exists');
-  } else {
-    console.log('🏗️ Creating Fee Token A account...');
-    createFeeAccountsIx.push(
-      createAssociatedTokenAccountInstruction(
-        user.publicKey,     // payer
-        feeTokenA,         // ata
-        feeRecipient.publicKey, // owner
-        tokenAMint,        // mint
-        tokenAProgram      // programId
-      )
-    );
-  }
-  
-  if (feeTokenBInfo) {
-    console.log('✅ Fee Token B account already exists');
-  } else {
-    console.log('🏗️ Creating Fee Token B account...');
-    createFeeAccountsIx.push(
-      createAssociatedTokenAccountInstruction(
-        user.publicKey,
-        feeTokenB,
-        feeRecipient.publicKey,
-        tokenBMint,
-        tokenBProgram
-      )
-    );
-  }
-  
-  // Create accounts if needed
-  if (createFeeAccountsIx.length > 0) {
-    console.log(`🚀 Creating ${createFeeAccountsIx.length} fee token account(s)...`);
-    const createAccountsTx = new anchor.web3.Transaction().add(...createFeeAccountsIx);
-    const signature = await provider.connection.sendTransaction(createAccountsTx, [user]);
-    
-    // Wait for confirmation
-    await provider.connection.confirmTransaction(signature);
-    console.log('✅ Created fee recipient token accounts');
-    console.log('📝 Transaction:', signature);
-  }
-  
   try {
     // Initialize protocol with 0.5% fee (50 bps)
     await program.methods
@@ -172,11 +95,9 @@ async function initializeProtocol() {
     // Update state file with camelCase field names
     const currentState = JSON.parse(readFileSync('./scripts/state.json', 'utf8'));
     currentState.feeRecipient = feeRecipient.publicKey.toString();
-    currentState.feeTokenA = feeTokenA.toString();
-    currentState.feeTokenB = feeTokenB.toString();
-    
     writeFileSync('./scripts/state.json', JSON.stringify(currentState, null, 2));
-    console.log('💾 Updated state file with fee recipient info');
+    
+    await ensureFeeVaults(program, user, state);
     
   } catch (error) {
     console.error('❌ Failed to initialize protocol:', error);
@@ -193,74 +114,52 @@ async function initializeProtocol() {
   }
 }
 
-async function ensureFeeRecipientAccounts(
+// Fees accrue into protocol-owned vault PDAs, one per mint
+async function ensureFeeVaults(
   program: Program<CapitalReallocator>, 
   user: Keypair, 
-  state: any, 
-  feeRecipient: PublicKey
+  state: any
 ) {
   const provider = anchor.AnchorProvider.env();
-  const tokenAMint = new PublicKey(state.tokenAMint);
-  const tokenBMint = new PublicKey(state.tokenBMint);
-  
-  const tokenAProgram = await getTokenProgram(provider.connection, tokenAMint);
-  const tokenBProgram = await getTokenProgram(provider.connection, tokenBMint);
-  
-  const feeTokenA = await getAssociatedTokenAddress(tokenAMint, feeRecipient, false, tokenAProgram);
-  const feeTokenB = await getAssociatedTokenAddress(tokenBMint, feeRecipient, false, tokenBProgram);
-  
-  console.log('💼 Fee Token A account:', feeTokenA.toString());
-  console.log('💼 Fee Token B account:', feeTokenB.toString());
-  
-  // Check if accounts exist and create if needed
-  const accountsToCreate = [];
-  
-  const feeTokenAInfo = await provider.connection.getAccountInfo(feeTokenA);
-  const feeTokenBInfo = await provider.connection.getAccountInfo(feeTokenB);
-  
-  if (!feeTokenAInfo) {
-    console.log('🏗️ Need to create Fee Token A account');
-    accountsToCreate.push(
-      createAssociatedTokenAccountInstruction(
-        user.publicKey,
-        feeTokenA,
-        feeRecipient,
-        tokenAMint,
-        tokenAProgram
-      )
-    );
-  }
+  const protocolAuthority = new PublicKey(state.protocolAuthority);
+  const feeVaults: Record<string, string> = {};
   
-  if (!feeTokenBInfo) {
-    console.log('🏗️ Need to create Fee Token B account');
-    accountsToCreate.push(
-      createAssociatedTokenAccountInstruction(
-        user.publicKey,
-        feeTokenB,
-        feeRecipient,
-        tokenBMint,
-        tokenBProgram
-      )
+  for (const [key, mintAddress] of [['feeVaultA', state.tokenAMint], ['feeVaultB', state.tokenBMint]]) {
+    const mint = new PublicKey(mintAddress);
+    const [feeVault] = PublicKey.findProgramAddressSync(
+      [Buffer.from("fee_vault"), mint.toBuffer()],
+      program.programId
     );
-  }
-  
-  if (accountsToCreate.length > 0) {
-    const createAccountsTx = new anchor.web3.Transaction().add(...accountsToCreate);
-    const signature = await provider.connection.sendTransaction(createAccountsTx, [user]);
-    await provider.connection.confirmTransaction(signature);
-    console.log('✅ Created missing fee token accounts');
+    feeVaults[key] = feeVault.toString();
+    
+    if (await provider.connection.getAccountInfo(feeVault)) {
+      console.log(`✅ ${key} already exists:`, feeVault.toString());
+      continue;
+    }
+    
+    console.log(`🏗️ Creating ${key}...`);
+    const tokenProgram = await getTokenProgram(provider.connection, mint);
+    const signature = await program.methods
+      .createFeeVault()
+      .accountsPartial({
+        feeVault,
+        protocolAuthority,
+        mint,
+        admin: user.publicKey,
+        tokenProgram,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    console.log(`✅ Created ${key}:`, feeVault.toString());
     console.log('📝 Transaction:', signature);
   }
   
   // Update state with camelCase field names
   const currentState = JSON.parse(readFileSync('./scripts/state.json', 'utf8'));
-  if (!currentState.feeTokenA) {  // Check camelCase field
-    currentState.feeRecipient = feeRecipient.toString();
-    currentState.feeTokenA = feeTokenA.toString();
-    currentState.feeTokenB = feeTokenB.toString();
-    writeFileSync('./scripts/state.json', JSON.stringify(currentState, null, 2));
-    console.log('💾 Updated state file with fee account info');
-  }
+  Object.assign(currentState, feeVaults);
+  writeFileSync('./scripts/state.json', JSON.stringify(currentState, null, 2));
+  console.log('💾 Updated state file with fee vaults');
 }
 
 // Run if this file is executed directly
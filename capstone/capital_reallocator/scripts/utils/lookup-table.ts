@@ -0,0 +1,108 @@
+// scripts/utils/lookup-table.ts
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { CapitalReallocator } from "../../target/types/capital_reallocator";
+import {
+  AddressLookupTableProgram,
+  Connection,
+  PublicKey,
+  SystemProgram,
+  TransactionInstruction,
+  TransactionMessage,
+  VersionedTransaction,
+} from "@solana/web3.js";
+import { BN } from "bn.js";
+import { loadState, loadUserKeypair } from './setup';
+import { writeFileSync, readFileSync } from 'fs';
+
+// Build a v0 transaction that resolves the pair's venue accounts through
+// its lookup table, keeping rebalance transactions under the size limit
+export async function buildV0Transaction(
+  connection: Connection,
+  payer: PublicKey,
+  instructions: TransactionInstruction[],
+  lookupTable: PublicKey,
+): Promise<VersionedTransaction> {
+  const table = (await connection.getAddressLookupTable(lookupTable)).value;
+  if (!table) {
+    throw new Error(`Lookup table ${lookupTable.toString()} not found`);
+  }
+
+  const { blockhash } = await connection.getLatestBlockhash();
+  const message = new TransactionMessage({
+    payerKey: payer,
+    recentBlockhash: blockhash,
+    instructions,
+  }).compileToV0Message([table]);
+
+  return new VersionedTransaction(message);
+}
+
+async function syncLookupTable() {
+  console.log('📇 Syncing pair lookup table...');
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.CapitalReallocator as Program<CapitalReallocator>;
+
+  // The protocol admin is the account that ran init-protocol
+  const user = loadUserKeypair();
+  const state = loadState();
+
+  const pairConfig = new PublicKey(state.pairConfig);
+  const protocolAuthority = new PublicKey(state.protocolAuthority);
+  const pair = await program.account.pairConfig.fetch(pairConfig);
+
+  let lookupTable: PublicKey = pair.lookupTable;
+  if (!lookupTable) {
+    const recentSlot = await provider.connection.getSlot("finalized");
+    [lookupTable] = PublicKey.findProgramAddressSync(
+      [pairConfig.toBuffer(), new BN(recentSlot).toArrayLike(Buffer, "le", 8)],
+      AddressLookupTableProgram.programId
+    );
+
+    const tx = await program.methods
+      .createPairLookupTable(new BN(recentSlot))
+      .accountsPartial({
+        pairConfig,
+        protocolAuthority,
+        lookupTable,
+        admin: user.publicKey,
+        addressLookupTableProgram: AddressLookupTableProgram.programId,
+        systemProgram: SystemProgram.programId,
+      })
+      .signers([user])
+      .rpc();
+    console.log('✅ Lookup table created:', lookupTable.toString());
+    console.log('📝 Transaction:', tx);
+  }
+
+  // The LB pair is only passed once the pair has a real Meteora market
+  const hasLbPair = !pair.meteoraLbPair.equals(PublicKey.default);
+  const tx = await program.methods
+    .extendPairLookupTable([])
+    .accountsPartial({
+      pairConfig,
+      protocolAuthority,
+      lookupTable,
+      meteoraLbPair: hasLbPair ? pair.meteoraLbPair : null,
+      admin: user.publicKey,
+      addressLookupTableProgram: AddressLookupTableProgram.programId,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([user])
+    .rpc();
+
+  const table = (await provider.connection.getAddressLookupTable(lookupTable)).value;
+  console.log(`✅ Lookup table holds ${table?.state.addresses.length ?? 0} addresses`);
+  console.log('📝 Transaction:', tx);
+
+  const currentState = JSON.parse(readFileSync('./scripts/state.json', 'utf8'));
+  currentState.lookupTable = lookupTable.toString();
+  writeFileSync('./scripts/state.json', JSON.stringify(currentState, null, 2));
+  console.log('💾 Updated state file with lookup table');
+}
+
+if (require.main === module) {
+  syncLookupTable().catch(console.error);
+}
@@ -130,7 +130,7 @@ class PositionMonitor {
         rangeMax,
         totalRebalances: position.totalRebalances.toNumber(),
         lastRebalancePrice: position.lastRebalancePrice.toNumber() / 1e6,
-        isPaused: position.pauseFlag,
+        isPaused: position.pauseFlag === 1,
         createdAt: new Date(position.createdAt.toNumber() * 1000)
       };
     } catch (error) {
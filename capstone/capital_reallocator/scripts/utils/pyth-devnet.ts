@@ -20,11 +20,6 @@ const DEVNET_FEEDS = {
   'ETH/USD': '0xca80ba6dc32e08d06f1aa886011eed1d77c77be9eb761cc10d72b7d0a2fd57a6',
 };
 
-// Real program IDs for external protocols
-const METEORA_DLMM_PROGRAM = new PublicKey("LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo");
-const KAMINO_LENDING_PROGRAM = new PublicKey("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
-const JUPITER_PROGRAM = new PublicKey("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
-
 export class PythDevnet {
   private hermesClient: HermesClient;
   private pythSolanaReceiver: PythSolanaReceiver;
@@ -155,7 +150,18 @@ export class PythDevnet {
     await this.executeStatusCheck(positionPubkey);
   }
 
+  // Registered pair config for the position's mints
+  private async pairConfigFor(positionPubkey: PublicKey): Promise<PublicKey> {
+    const position = await this.program.account.position.fetch(positionPubkey);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("pair"), position.tokenAMint.toBuffer(), position.tokenBMint.toBuffer()],
+      this.program.programId
+    )[0];
+  }
+
   private async executeStatusCheck(positionPubkey: PublicKey): Promise<void> {
+    const pairConfig = await this.pairConfigFor(positionPubkey);
+    
     // Fetch price update data
     const priceUpdateData = await this.hermesClient.getLatestPriceUpdates(
       [DEVNET_FEEDS['SOL/USD']],
@@ -177,6 +183,7 @@ export class PythDevnet {
           .checkPositionStatus()
           .accountsPartial({
             position: positionPubkey,
+            pairConfig,
             priceUpdate: priceUpdateAccount,
           })
           .instruction();
@@ -288,6 +295,14 @@ export class PythDevnet {
       positionPubkey,
       true
     );
+    const pairConfig = await this.pairConfigFor(positionPubkey);
+    
+    // Venue programs come from the protocol config, not hard-coded IDs
+    const [protocolAuthority] = PublicKey.findProgramAddressSync(
+      [Buffer.from("protocol")],
+      this.program.programId
+    );
+    const { externalPrograms } = await this.program.account.protocolAuthority.fetch(protocolAuthority);
     
     // Build transaction
     const transactionBuilder = this.pythSolanaReceiver.newTransactionBuilder({
@@ -301,23 +316,25 @@ export class PythDevnet {
         const priceUpdateAccount = getPriceUpdateAccount(DEVNET_FEEDS['SOL/USD']);
         
         const instruction = await this.program.methods
-          .rebalancePosition()
+          .rebalancePosition(Buffer.alloc(0), new BN(0))
           .accountsPartial({
             position: positionPubkey,
+            pairConfig,
             priceUpdate: priceUpdateAccount,
+            protocolAuthority,
             positionTokenAVault,
             positionTokenBVault,
-            meteoraProgram: METEORA_DLMM_PROGRAM,
+            meteoraProgram: externalPrograms.meteoraDlmm,
             meteoraLbPair: null, // Would be actual accounts in production
             meteoraPosition: null,
             meteoraBinArrayLower: null,
             meteoraBinArrayUpper: null,
-            kaminoProgram: KAMINO_LENDING_PROGRAM,
+            kaminoProgram: externalPrograms.kaminoLending,
             kaminoLendingMarket: null, // Would be actual accounts in production
             kaminoObligation: null,
             kaminoReserveA: null,
             kaminoReserveB: null,
-            jupiterProgram: JUPITER_PROGRAM,
+            jupiterProgram: externalPrograms.jupiter,
             systemProgram: SystemProgram.programId,
             tokenProgram: TOKEN_PROGRAM_ID,
           })
@@ -0,0 +1,75 @@
+// scripts/utils/set-pair.ts
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { CapitalReallocator } from "../../target/types/capital_reallocator";
+import { PublicKey, SystemProgram } from "@solana/web3.js";
+import { BN } from "bn.js";
+import { loadState, loadUserKeypair } from './setup';
+import { writeFileSync, readFileSync } from 'fs';
+
+// Pyth SOL/USD feed (token B quoted in token A)
+const SOL_USD_FEED_ID = "ef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
+
+async function setPair() {
+  console.log('🔗 Registering token pair...');
+  
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.CapitalReallocator as Program<CapitalReallocator>;
+  
+  // The protocol admin is the account that ran init-protocol
+  const user = loadUserKeypair();
+  const state = loadState();
+  
+  const tokenAMint = new PublicKey(state.tokenAMint);
+  const tokenBMint = new PublicKey(state.tokenBMint);
+  
+  const [pairConfig] = PublicKey.findProgramAddressSync(
+    [Buffer.from("pair"), tokenAMint.toBuffer(), tokenBMint.toBuffer()],
+    program.programId
+  );
+  
+  // Venue accounts are unset until real Meteora/Kamino markets exist for the pair
+  const params = {
+    priceFeedId: Array.from(Buffer.from(SOL_USD_FEED_ID, "hex")),
+    meteoraLbPair: PublicKey.default,
+    kaminoLendingMarket: PublicKey.default,
+    kaminoReserveA: PublicKey.default,
+    kaminoReserveB: PublicKey.default,
+    minPositionA: new BN(0),
+    minPositionB: new BN(0),
+    maxPositionA: new BN(0),
+    maxPositionB: new BN(0),
+    enabled: true,
+    // Hold lending while in range if LP fees trail its APY by more than 2%
+    yieldCheckEnabled: true,
+    yieldMarginBps: 200,
+  };
+  
+  const tx = await program.methods
+    .setPairConfig(params)
+    .accountsPartial({
+      pairConfig,
+      protocolAuthority: new PublicKey(state.protocolAuthority),
+      tokenAMint,
+      tokenBMint,
+      admin: user.publicKey,
+      systemProgram: SystemProgram.programId,
+    })
+    .signers([user])
+    .rpc();
+  
+  const pair = await program.account.pairConfig.fetch(pairConfig);
+  console.log('✅ Pair registered:', pairConfig.toString());
+  console.log(`🔢 Decimals: A=${pair.tokenADecimals}, B=${pair.tokenBDecimals}`);
+  console.log('📝 Transaction:', tx);
+  
+  const currentState = JSON.parse(readFileSync('./scripts/state.json', 'utf8'));
+  currentState.pairConfig = pairConfig.toString();
+  writeFileSync('./scripts/state.json', JSON.stringify(currentState, null, 2));
+  console.log('💾 Updated state file with pair config');
+}
+
+if (require.main === module) {
+  setPair().catch(console.error);
+}
@@ -29,8 +29,14 @@ interface TestState {
   // Added by init-protocol.ts
   feeRecipient?: string;
   feeRecipientSecretKey?: string;
-  feeTokenA?: string;
-  feeTokenB?: string;
+  feeVaultA?: string;
+  feeVaultB?: string;
+  
+  // Added by set-pair.ts
+  pairConfig?: string;
+  
+  // Added by lookup-table.ts
+  lookupTable?: string;
   
   // Added by create-position.ts
   position?: string;
@@ -0,0 +1,58 @@
+// scripts/utils/sweep-fees.ts
+import * as anchor from "@coral-xyz/anchor";
+import { Program } from "@coral-xyz/anchor";
+import { CapitalReallocator } from "../../target/types/capital_reallocator";
+import { PublicKey } from "@solana/web3.js";
+import { TOKEN_PROGRAM_ID, getAccount } from "@solana/spl-token";
+import { loadState, loadUserKeypair } from './setup';
+
+// Usage: yarn sweep-fees <a|b> <recipient token account>:<share bps> [...]
+// e.g. yarn sweep-fees a <treasury>:7000 <team>:3000
+async function sweepFees() {
+  console.log('🧹 Sweeping protocol fees...');
+
+  const args = process.argv.slice(2);
+  const token = (args[0] || 'a').toLowerCase();
+  const splits = args.slice(1).map((arg) => {
+    const [account, share] = arg.split(':');
+    return { account: new PublicKey(account), shareBps: parseInt(share) };
+  });
+  if (splits.length === 0) {
+    throw new Error('Pass at least one <recipient token account>:<share bps>');
+  }
+
+  const provider = anchor.AnchorProvider.env();
+  anchor.setProvider(provider);
+  const program = anchor.workspace.CapitalReallocator as Program<CapitalReallocator>;
+
+  // The protocol admin is the account that ran init-protocol
+  const user = loadUserKeypair();
+  const state = loadState();
+
+  const mint = new PublicKey(token === 'b' ? state.tokenBMint : state.tokenAMint);
+  const feeVault = new PublicKey(token === 'b' ? state.feeVaultB : state.feeVaultA);
+
+  const vault = await getAccount(provider.connection, feeVault);
+  console.log('💰 Vault balance:', vault.amount.toString());
+
+  const tx = await program.methods
+    .sweepFees(splits.map((split) => split.shareBps))
+    .accountsPartial({
+      feeVault,
+      protocolAuthority: new PublicKey(state.protocolAuthority),
+      mint,
+      admin: user.publicKey,
+      tokenProgram: TOKEN_PROGRAM_ID,
+    })
+    .remainingAccounts(splits.map((split) => ({ pubkey: split.account, isSigner: false, isWritable: true })))
+    .signers([user])
+    .rpc();
+
+  console.log('✅ Fees swept');
+  console.log('📝 Transaction:', tx);
+}
+
+sweepFees().catch((error) => {
+  console.error('❌ Sweep failed:', error);
+  process.exit(1);
+});
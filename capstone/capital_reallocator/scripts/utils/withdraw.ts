@@ -75,8 +75,9 @@ async function withdrawTokens() {
       userTokenB: new PublicKey(state.userTokenB),
       positionTokenAVault: new PublicKey(state.positionTokenAVault),
       positionTokenBVault: new PublicKey(state.positionTokenBVault),
-      feeTokenA: new PublicKey(state.feeTokenA),
-      feeTokenB: new PublicKey(state.feeTokenB),
+      feeVaultA: new PublicKey(state.feeVaultA),
+      feeVaultB: new PublicKey(state.feeVaultB),
+      userMainAccount: new PublicKey(state.userMainAccount),
       owner: user.publicKey,
       tokenAMint: new PublicKey(state.tokenAMint),
       tokenBMint: new PublicKey(state.tokenBMint),
@@ -92,8 +93,8 @@ async function withdrawTokens() {
   const userAccountAAfter = await getAccount(provider.connection, new PublicKey(state.userTokenA));
   const userAccountBAfter = await getAccount(provider.connection, new PublicKey(state.userTokenB));
   const positionStateAfter = await program.account.position.fetch(new PublicKey(state.position));
-  const feeAccountA = await getAccount(provider.connection, new PublicKey(state.feeTokenA));
-  const feeAccountB = await getAccount(provider.connection, new PublicKey(state.feeTokenB));
+  const feeAccountA = await getAccount(provider.connection, new PublicKey(state.feeVaultA));
+  const feeAccountB = await getAccount(provider.connection, new PublicKey(state.feeVaultB));
   
   console.log('\n📊 After withdrawal:');
   console.log('User Token A balance:', (Number(userAccountAAfter.amount) / 10**6).toFixed(6));
@@ -107,6 +108,12 @@ async function withdrawTokens() {
   console.log('Token A in lending:', (positionStateAfter.tokenAInLending.toNumber() / 10**6).toFixed(6));
   console.log('Token B in lending:', (positionStateAfter.tokenBInLending.toNumber() / 10**9).toFixed(9));
   
+  if (positionStateAfter.queuedWithdrawA.toNumber() > 0 || positionStateAfter.queuedWithdrawB.toNumber() > 0) {
+    console.log('\n⏳ Queued until LP/lending liquidity frees up:');
+    console.log('Token A queued:', (positionStateAfter.queuedWithdrawA.toNumber() / 10**6).toFixed(6));
+    console.log('Token B queued:', (positionStateAfter.queuedWithdrawB.toNumber() / 10**9).toFixed(9));
+  }
+  
   // Calculate amounts received
   const receivedA = Number(userAccountAAfter.amount) - Number(userAccountABefore.amount);
   const receivedB = Number(userAccountBAfter.amount) - Number(userAccountBBefore.amount);